@@ -0,0 +1,79 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use libsimbolmudah::bench::{keysyms, Definition};
+
+const KEYSYMDEF: &str = "x11-defs/keysymdef.h.br";
+const COMPOSEDEF: &str = "x11-defs/Compose.pre.br";
+const ANNOTATIONS: &str = concat!(env!("CARGO_MANIFEST_DIR"), "\\cldr");
+
+fn definition() -> Definition {
+    Definition::rebuild(KEYSYMDEF, COMPOSEDEF, ANNOTATIONS).expect("bundled files build")
+}
+
+fn rebuild(c: &mut Criterion) {
+    let mut group = c.benchmark_group("rebuild");
+    // A rebuild takes long enough that the default of 100 samples would take minutes
+    group.sample_size(10);
+    group.bench_function("bundled", |b| b.iter(definition));
+    group.finish();
+}
+
+fn type_sequence(c: &mut Criterion) {
+    let definition = definition();
+    let mut group = c.benchmark_group("type_sequence");
+
+    for sequence in ["oc", "->", "fl", "wkwk", "qqq"] {
+        let keysyms = keysyms(sequence);
+        group.bench_with_input(
+            BenchmarkId::from_parameter(sequence),
+            &keysyms,
+            |b, keysyms| b.iter(|| definition.type_sequence(black_box(keysyms)).unwrap()),
+        );
+    }
+    group.finish();
+}
+
+fn potential_prefix(c: &mut Criterion) {
+    let definition = definition();
+    let mut group = c.benchmark_group("potential_prefix");
+
+    for prefix in ["", "o", "-"] {
+        for limit in [1, 10, 100, 1000] {
+            group.bench_with_input(
+                BenchmarkId::new(format!("'{prefix}'"), limit),
+                &limit,
+                |b, &limit| {
+                    b.iter(|| {
+                        definition
+                            .potential_prefix(black_box(prefix), limit)
+                            .unwrap()
+                    })
+                },
+            );
+        }
+    }
+    group.finish();
+}
+
+fn search(c: &mut Criterion) {
+    let definition = definition();
+    let mut group = c.benchmark_group("search");
+
+    for query in [
+        "arrow",
+        "right arrow",
+        "thumbs up",
+        "latin small letter a",
+        "→",
+        "zzzz qqqq",
+    ] {
+        for limit in [10, 100] {
+            group.bench_with_input(BenchmarkId::new(query, limit), &limit, |b, &limit| {
+                b.iter(|| definition.search(black_box(query), limit).unwrap())
+            });
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, rebuild, type_sequence, potential_prefix, search);
+criterion_main!(benches);
@@ -1,4 +1,4 @@
-use std::{fs, io::Write};
+use std::{fs, io::Write, sync::Mutex};
 
 use brotli::{
     enc::{backward_references::BrotliEncoderMode, BrotliEncoderParams},
@@ -45,6 +45,8 @@ fn generate_bindgen() {
 
 fn compress_annotations() {
     println!("cargo:rerun-if-changed=../git-deps/cldr");
+    println!("cargo:rerun-if-changed=shortcodes.txt");
+    println!("cargo:rerun-if-changed=html_entities.txt");
 
     let cldr_dir = "cldr";
     if fs::metadata(cldr_dir).is_err() {
@@ -58,31 +60,54 @@ fn compress_annotations() {
         ..Default::default()
     };
 
-    std::thread::scope(|s| {
-        for locale in ["en", "fr", "id", "jv"] {
-            let locale_clone = locale.to_string();
-            let params_clone = params.clone();
-            s.spawn(move || {
-                let outpath = format!("cldr/{}-annotations.xml.br", locale_clone);
-                let mut output = fs::File::create(outpath).unwrap();
-                let mut compressed =
-                    CompressorWriter::with_params(&mut output, 4096, &params_clone);
-                let inpath = format!("../git-deps/cldr/common/annotations/{}.xml", locale_clone);
-                let input = fs::read(inpath).unwrap();
-                compressed.write_all(input.as_slice()).unwrap();
-            });
+    // Every locale present in CLDR, e.g. `../git-deps/cldr/common/annotations/fr_CA.xml` goes to
+    // `cldr/fr_CA-annotations.xml.br`
+    let mut jobs = vec![
+        (
+            "../git-deps/cldr/common/supplemental/supplementalData.xml".to_string(),
+            "cldr/supplementalData.xml.br".to_string(),
+        ),
+        // Emoji groups and qualification status, kept by CLDR next to its tools
+        (
+            "../git-deps/cldr/tools/cldr-code/src/main/resources/org/unicode/cldr/util/data/emoji/emoji-test.txt".to_string(),
+            "cldr/emoji-test.txt.br".to_string(),
+        ),
+        ("shortcodes.txt".to_string(), "cldr/shortcodes.txt.br".to_string()),
+        (
+            "html_entities.txt".to_string(),
+            "cldr/html_entities.txt.br".to_string(),
+        ),
+    ];
+    for variant in ["annotations", "annotationsDerived"] {
+        for entry in fs::read_dir(format!("../git-deps/cldr/common/{variant}")).unwrap() {
+            let path = entry.unwrap().path();
+            if let Some(locale) = path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .and_then(|n| n.strip_suffix(".xml"))
+            {
+                jobs.push((
+                    path.to_string_lossy().into_owned(),
+                    format!("cldr/{locale}-{variant}.xml.br"),
+                ));
+            }
+        }
+    }
 
-            let locale_clone = locale.to_string();
+    let jobs = Mutex::new(jobs);
+    let workers = std::thread::available_parallelism().map_or(4, |n| n.get());
+    std::thread::scope(|s| {
+        for _ in 0..workers {
             let params_clone = params.clone();
-            s.spawn(move || {
-                let outpath = format!("cldr/{}-annotationsDerived.xml.br", locale_clone);
+            let jobs = &jobs;
+            s.spawn(move || loop {
+                let job = jobs.lock().unwrap().pop();
+                let Some((inpath, outpath)) = job else {
+                    break;
+                };
                 let mut output = fs::File::create(outpath).unwrap();
                 let mut compressed =
                     CompressorWriter::with_params(&mut output, 4096, &params_clone);
-                let inpath = format!(
-                    "../git-deps/cldr/common/annotationsDerived/{}.xml",
-                    locale_clone
-                );
                 let input = fs::read(inpath).unwrap();
                 compressed.write_all(input.as_slice()).unwrap();
             });
@@ -0,0 +1,53 @@
+use windows::{
+    core::{Error, HSTRING},
+    Foundation::Collections::IVectorView,
+};
+use windows_core::{Interface, Result};
+
+use crate::{
+    bindings,
+    sequence_definition::{char_to_keysym, SequenceDefinition},
+};
+
+/// A sequence definition for the benchmarks in `benches`, which can only reach the crate
+/// through its public items.
+pub struct Definition(bindings::SequenceDefinition);
+
+impl Definition {
+    /// Builds from the bundled files, searching in English only so that results do not depend
+    /// on the languages of the machine.
+    pub fn rebuild(keysymdef: &str, composedef: &str, annotations: &str) -> Result<Self> {
+        let definition: bindings::SequenceDefinition = SequenceDefinition::default().into();
+        let languages: IVectorView<HSTRING> = vec![HSTRING::from("en")].try_into()?;
+        definition.SetSearchLanguages(&languages)?;
+        definition.Rebuild(&keysymdef.into(), &composedef.into(), &annotations.into())?;
+        Ok(Self(definition))
+    }
+
+    /// Looks up every prefix of `keysyms` in turn, as the keyboard translator does while a
+    /// sequence is typed, and returns the result of the last one.
+    pub fn type_sequence(&self, keysyms: &[u32]) -> Result<Option<String>> {
+        let definition = self.0.cast_object_ref::<SequenceDefinition>()?;
+        let mut result = None;
+        for end in 1..=keysyms.len() {
+            result = definition
+                .match_sequence(&keysyms[..end])
+                .map_err(|e| Into::<Error>::into(e))?
+                .value;
+        }
+        Ok(result)
+    }
+
+    pub fn potential_prefix(&self, sequence: &str, limit: u32) -> Result<u32> {
+        self.0.PotentialPrefix(&sequence.into(), limit)?.Size()
+    }
+
+    pub fn search(&self, query: &str, limit: u32) -> Result<u32> {
+        self.0.Search(&query.into(), limit)?.Size()
+    }
+}
+
+/// Keysyms of a sequence written with its characters, e.g. `oc` for `©`.
+pub fn keysyms(sequence: &str) -> Vec<u32> {
+    sequence.chars().map(char_to_keysym).collect()
+}
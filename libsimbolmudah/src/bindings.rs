@@ -107,6 +107,8 @@ pub struct IKeyboardTranslator_Vtbl {
         *mut core::ffi::c_void,
         windows::Foundation::EventRegistrationToken,
     ) -> windows_core::HRESULT,
+    pub SetAmbiguousConfirmation:
+        unsafe extern "system" fn(*mut core::ffi::c_void, bool, u32) -> windows_core::HRESULT,
 }
 windows_core::imp::define_interface!(
     IKeyboardTranslatorFactory,
@@ -191,6 +193,41 @@ pub struct INotifyIconFactory_Vtbl {
         *mut *mut core::ffi::c_void,
     ) -> windows_core::HRESULT,
 }
+windows_core::imp::define_interface!(
+    IRebuildReport,
+    IRebuildReport_Vtbl,
+    0x7da8ffbf_ee3a_5702_b120_1bf6f557c848
+);
+impl windows_core::RuntimeType for IRebuildReport {
+    const SIGNATURE: windows_core::imp::ConstBuffer =
+        windows_core::imp::ConstBuffer::for_interface::<Self>();
+}
+#[repr(C)]
+pub struct IRebuildReport_Vtbl {
+    pub base__: windows_core::IInspectable_Vtbl,
+    pub GetSequenceCounts: unsafe extern "system" fn(
+        *mut core::ffi::c_void,
+        *mut *mut core::ffi::c_void,
+    ) -> windows_core::HRESULT,
+    pub GetSkippedLineCount:
+        unsafe extern "system" fn(*mut core::ffi::c_void, *mut u32) -> windows_core::HRESULT,
+    pub GetSkippedLineSamples: unsafe extern "system" fn(
+        *mut core::ffi::c_void,
+        *mut *mut core::ffi::c_void,
+    ) -> windows_core::HRESULT,
+    pub GetLocales: unsafe extern "system" fn(
+        *mut core::ffi::c_void,
+        *mut *mut core::ffi::c_void,
+    ) -> windows_core::HRESULT,
+    pub GetPhaseTimings: unsafe extern "system" fn(
+        *mut core::ffi::c_void,
+        *mut *mut core::ffi::c_void,
+    ) -> windows_core::HRESULT,
+    pub GetTableSizes: unsafe extern "system" fn(
+        *mut core::ffi::c_void,
+        *mut *mut core::ffi::c_void,
+    ) -> windows_core::HRESULT,
+}
 windows_core::imp::define_interface!(
     ISenderStatics,
     ISenderStatics_Vtbl,
@@ -244,6 +281,203 @@ pub struct ISequenceDefinition_Vtbl {
         u32,
         *mut core::mem::MaybeUninit<SequenceDescription>,
     ) -> windows_core::HRESULT,
+    pub Export: unsafe extern "system" fn(
+        *mut core::ffi::c_void,
+        ExportFormat,
+        *mut core::mem::MaybeUninit<windows_core::HSTRING>,
+    ) -> windows_core::HRESULT,
+    pub CheatSheet: unsafe extern "system" fn(
+        *mut core::ffi::c_void,
+        CheatSheetFormat,
+        core::mem::MaybeUninit<windows_core::HSTRING>,
+        *mut core::mem::MaybeUninit<windows_core::HSTRING>,
+    ) -> windows_core::HRESULT,
+    pub SetSearchLanguages: unsafe extern "system" fn(
+        *mut core::ffi::c_void,
+        *mut core::ffi::c_void,
+    ) -> windows_core::HRESULT,
+    pub GetSearchLanguages: unsafe extern "system" fn(
+        *mut core::ffi::c_void,
+        *mut *mut core::ffi::c_void,
+    ) -> windows_core::HRESULT,
+    pub GetEmojiVariants: unsafe extern "system" fn(
+        *mut core::ffi::c_void,
+        core::mem::MaybeUninit<windows_core::HSTRING>,
+        *mut *mut core::ffi::c_void,
+    ) -> windows_core::HRESULT,
+    pub SetPreferredSkinTone:
+        unsafe extern "system" fn(*mut core::ffi::c_void, u8) -> windows_core::HRESULT,
+    pub GetPreferredSkinTone:
+        unsafe extern "system" fn(*mut core::ffi::c_void, *mut u8) -> windows_core::HRESULT,
+    pub GetEmojiInfo: unsafe extern "system" fn(
+        *mut core::ffi::c_void,
+        core::mem::MaybeUninit<windows_core::HSTRING>,
+        *mut core::mem::MaybeUninit<EmojiInfo>,
+    ) -> windows_core::HRESULT,
+    pub GetEmojiGroups: unsafe extern "system" fn(
+        *mut core::ffi::c_void,
+        *mut *mut core::ffi::c_void,
+    ) -> windows_core::HRESULT,
+    pub GetCategories: unsafe extern "system" fn(
+        *mut core::ffi::c_void,
+        *mut *mut core::ffi::c_void,
+    ) -> windows_core::HRESULT,
+    pub GetCategorySize: unsafe extern "system" fn(
+        *mut core::ffi::c_void,
+        core::mem::MaybeUninit<windows_core::HSTRING>,
+        *mut u32,
+    ) -> windows_core::HRESULT,
+    pub GetCategoryMembers: unsafe extern "system" fn(
+        *mut core::ffi::c_void,
+        core::mem::MaybeUninit<windows_core::HSTRING>,
+        u32,
+        u32,
+        *mut *mut core::ffi::c_void,
+    ) -> windows_core::HRESULT,
+    pub ShortcodePrefix: unsafe extern "system" fn(
+        *mut core::ffi::c_void,
+        core::mem::MaybeUninit<windows_core::HSTRING>,
+        u32,
+        *mut *mut core::ffi::c_void,
+    ) -> windows_core::HRESULT,
+    pub MatchSequence: unsafe extern "system" fn(
+        *mut core::ffi::c_void,
+        core::mem::MaybeUninit<windows_core::HSTRING>,
+        *mut core::mem::MaybeUninit<SequenceMatch>,
+    ) -> windows_core::HRESULT,
+    pub SetMatchingOptions:
+        unsafe extern "system" fn(*mut core::ffi::c_void, bool, bool) -> windows_core::HRESULT,
+    pub GetRebuildReport: unsafe extern "system" fn(
+        *mut core::ffi::c_void,
+        *mut *mut core::ffi::c_void,
+    ) -> windows_core::HRESULT,
+    pub GetRebuildStatistics: unsafe extern "system" fn(
+        *mut core::ffi::c_void,
+        *mut *mut core::ffi::c_void,
+    ) -> windows_core::HRESULT,
+    pub RebuildAsync: unsafe extern "system" fn(
+        *mut core::ffi::c_void,
+        core::mem::MaybeUninit<windows_core::HSTRING>,
+        core::mem::MaybeUninit<windows_core::HSTRING>,
+        core::mem::MaybeUninit<windows_core::HSTRING>,
+        *mut *mut core::ffi::c_void,
+    ) -> windows_core::HRESULT,
+    pub SetHotReload:
+        unsafe extern "system" fn(*mut core::ffi::c_void, bool, u32) -> windows_core::HRESULT,
+    pub OnReloadFailed: unsafe extern "system" fn(
+        *mut core::ffi::c_void,
+        *mut core::ffi::c_void,
+        *mut windows::Foundation::EventRegistrationToken,
+    ) -> windows_core::HRESULT,
+    pub RemoveOnReloadFailed: unsafe extern "system" fn(
+        *mut core::ffi::c_void,
+        windows::Foundation::EventRegistrationToken,
+    ) -> windows_core::HRESULT,
+}
+#[repr(transparent)]
+#[derive(PartialEq, Eq, Copy, Clone, Default)]
+pub struct CheatSheetFormat(pub i32);
+impl CheatSheetFormat {
+    pub const Markdown: Self = Self(0i32);
+    pub const Html: Self = Self(1i32);
+}
+impl windows_core::TypeKind for CheatSheetFormat {
+    type TypeKind = windows_core::CopyType;
+}
+impl core::fmt::Debug for CheatSheetFormat {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_tuple("CheatSheetFormat").field(&self.0).finish()
+    }
+}
+impl windows_core::RuntimeType for CheatSheetFormat {
+    const SIGNATURE: windows_core::imp::ConstBuffer =
+        windows_core::imp::ConstBuffer::from_slice(b"enum(LibSimbolMudah.CheatSheetFormat;i4)");
+}
+#[repr(transparent)]
+#[derive(PartialEq, Eq, Copy, Clone, Default)]
+pub struct DiagnosticKind(pub i32);
+impl DiagnosticKind {
+    pub const UnknownKeysym: Self = Self(0i32);
+    pub const MalformedLine: Self = Self(1i32);
+    pub const BadEscape: Self = Self(2i32);
+    pub const IncludeFailed: Self = Self(3i32);
+    pub const LocaleMissing: Self = Self(4i32);
+    pub const Io: Self = Self(5i32);
+}
+impl windows_core::TypeKind for DiagnosticKind {
+    type TypeKind = windows_core::CopyType;
+}
+impl core::fmt::Debug for DiagnosticKind {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_tuple("DiagnosticKind").field(&self.0).finish()
+    }
+}
+impl windows_core::RuntimeType for DiagnosticKind {
+    const SIGNATURE: windows_core::imp::ConstBuffer =
+        windows_core::imp::ConstBuffer::from_slice(b"enum(LibSimbolMudah.DiagnosticKind;i4)");
+}
+#[repr(C)]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EmojiInfo {
+    pub group: windows_core::HSTRING,
+    pub subgroup: windows_core::HSTRING,
+    pub version: windows_core::HSTRING,
+    pub qualification: EmojiQualification,
+    pub fullyQualified: windows_core::HSTRING,
+}
+impl windows_core::TypeKind for EmojiInfo {
+    type TypeKind = windows_core::CloneType;
+}
+impl windows_core::RuntimeType for EmojiInfo {
+    const SIGNATURE: windows_core::imp::ConstBuffer = windows_core::imp::ConstBuffer::from_slice(
+        b"struct(LibSimbolMudah.EmojiInfo;string;string;string;enum(LibSimbolMudah.EmojiQualification;i4);string)",
+    );
+}
+impl Default for EmojiInfo {
+    fn default() -> Self {
+        unsafe { core::mem::zeroed() }
+    }
+}
+#[repr(transparent)]
+#[derive(PartialEq, Eq, Copy, Clone, Default)]
+pub struct EmojiQualification(pub i32);
+impl EmojiQualification {
+    pub const Component: Self = Self(0i32);
+    pub const FullyQualified: Self = Self(1i32);
+    pub const MinimallyQualified: Self = Self(2i32);
+    pub const Unqualified: Self = Self(3i32);
+}
+impl windows_core::TypeKind for EmojiQualification {
+    type TypeKind = windows_core::CopyType;
+}
+impl core::fmt::Debug for EmojiQualification {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_tuple("EmojiQualification").field(&self.0).finish()
+    }
+}
+impl windows_core::RuntimeType for EmojiQualification {
+    const SIGNATURE: windows_core::imp::ConstBuffer =
+        windows_core::imp::ConstBuffer::from_slice(b"enum(LibSimbolMudah.EmojiQualification;i4)");
+}
+#[repr(transparent)]
+#[derive(PartialEq, Eq, Copy, Clone, Default)]
+pub struct ExportFormat(pub i32);
+impl ExportFormat {
+    pub const XCompose: Self = Self(0i32);
+    pub const Json: Self = Self(1i32);
+    pub const Csv: Self = Self(2i32);
+}
+impl windows_core::TypeKind for ExportFormat {
+    type TypeKind = windows_core::CopyType;
+}
+impl core::fmt::Debug for ExportFormat {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_tuple("ExportFormat").field(&self.0).finish()
+    }
+}
+impl windows_core::RuntimeType for ExportFormat {
+    const SIGNATURE: windows_core::imp::ConstBuffer =
+        windows_core::imp::ConstBuffer::from_slice(b"enum(LibSimbolMudah.ExportFormat;i4)");
 }
 #[repr(transparent)]
 #[derive(PartialEq, Eq, Debug, Clone)]
@@ -530,6 +764,21 @@ impl KeyboardTranslator {
         > = windows_core::imp::FactoryCache::new();
         SHARED.call(callback)
     }
+    pub fn SetAmbiguousConfirmation(
+        &self,
+        enabled: bool,
+        timeoutmilliseconds: u32,
+    ) -> windows_core::Result<()> {
+        let this = self;
+        unsafe {
+            (windows_core::Interface::vtable(this).SetAmbiguousConfirmation)(
+                windows_core::Interface::as_raw(this),
+                enabled,
+                timeoutmilliseconds,
+            )
+            .ok()
+        }
+    }
 }
 impl windows_core::RuntimeType for KeyboardTranslator {
     const SIGNATURE: windows_core::imp::ConstBuffer =
@@ -544,6 +793,205 @@ impl windows_core::RuntimeName for KeyboardTranslator {
 }
 unsafe impl Send for KeyboardTranslator {}
 unsafe impl Sync for KeyboardTranslator {}
+#[repr(C)]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RebuildDiagnostic {
+    pub kind: DiagnosticKind,
+    pub skipped: bool,
+    pub file: windows_core::HSTRING,
+    pub line: u32,
+    pub message: windows_core::HSTRING,
+}
+impl windows_core::TypeKind for RebuildDiagnostic {
+    type TypeKind = windows_core::CloneType;
+}
+impl windows_core::RuntimeType for RebuildDiagnostic {
+    const SIGNATURE: windows_core::imp::ConstBuffer = windows_core::imp::ConstBuffer::from_slice(
+        b"struct(LibSimbolMudah.RebuildDiagnostic;enum(LibSimbolMudah.DiagnosticKind;i4);b1;string;u4;string)",
+    );
+}
+impl Default for RebuildDiagnostic {
+    fn default() -> Self {
+        unsafe { core::mem::zeroed() }
+    }
+}
+#[repr(transparent)]
+#[derive(PartialEq, Eq, Copy, Clone, Default)]
+pub struct RebuildPhase(pub i32);
+impl RebuildPhase {
+    pub const Decompress: Self = Self(0i32);
+    pub const Parse: Self = Self(1i32);
+    pub const Annotations: Self = Self(2i32);
+    pub const NameLookup: Self = Self(3i32);
+    pub const FstBuild: Self = Self(4i32);
+}
+impl windows_core::TypeKind for RebuildPhase {
+    type TypeKind = windows_core::CopyType;
+}
+impl core::fmt::Debug for RebuildPhase {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_tuple("RebuildPhase").field(&self.0).finish()
+    }
+}
+impl windows_core::RuntimeType for RebuildPhase {
+    const SIGNATURE: windows_core::imp::ConstBuffer =
+        windows_core::imp::ConstBuffer::from_slice(b"enum(LibSimbolMudah.RebuildPhase;i4)");
+}
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct RebuildPhaseTiming {
+    pub phase: RebuildPhase,
+    pub microseconds: u64,
+}
+impl windows_core::TypeKind for RebuildPhaseTiming {
+    type TypeKind = windows_core::CopyType;
+}
+impl windows_core::RuntimeType for RebuildPhaseTiming {
+    const SIGNATURE: windows_core::imp::ConstBuffer = windows_core::imp::ConstBuffer::from_slice(
+        b"struct(LibSimbolMudah.RebuildPhaseTiming;enum(LibSimbolMudah.RebuildPhase;i4);u8)",
+    );
+}
+#[repr(transparent)]
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct RebuildReport(windows_core::IUnknown);
+windows_core::imp::interface_hierarchy!(
+    RebuildReport,
+    windows_core::IUnknown,
+    windows_core::IInspectable
+);
+impl RebuildReport {
+    pub fn GetSequenceCounts(
+        &self,
+    ) -> windows_core::Result<windows::Foundation::Collections::IVectorView<RebuildSequenceCount>>
+    {
+        let this = self;
+        unsafe {
+            let mut result__ = core::mem::zeroed();
+            (windows_core::Interface::vtable(this).GetSequenceCounts)(
+                windows_core::Interface::as_raw(this),
+                &mut result__,
+            )
+            .and_then(|| windows_core::Type::from_abi(result__))
+        }
+    }
+    pub fn GetSkippedLineCount(&self) -> windows_core::Result<u32> {
+        let this = self;
+        unsafe {
+            let mut result__ = core::mem::zeroed();
+            (windows_core::Interface::vtable(this).GetSkippedLineCount)(
+                windows_core::Interface::as_raw(this),
+                &mut result__,
+            )
+            .map(|| result__)
+        }
+    }
+    pub fn GetSkippedLineSamples(
+        &self,
+    ) -> windows_core::Result<windows::Foundation::Collections::IVectorView<RebuildDiagnostic>>
+    {
+        let this = self;
+        unsafe {
+            let mut result__ = core::mem::zeroed();
+            (windows_core::Interface::vtable(this).GetSkippedLineSamples)(
+                windows_core::Interface::as_raw(this),
+                &mut result__,
+            )
+            .and_then(|| windows_core::Type::from_abi(result__))
+        }
+    }
+    pub fn GetLocales(
+        &self,
+    ) -> windows_core::Result<windows::Foundation::Collections::IVectorView<windows_core::HSTRING>>
+    {
+        let this = self;
+        unsafe {
+            let mut result__ = core::mem::zeroed();
+            (windows_core::Interface::vtable(this).GetLocales)(
+                windows_core::Interface::as_raw(this),
+                &mut result__,
+            )
+            .and_then(|| windows_core::Type::from_abi(result__))
+        }
+    }
+    pub fn GetPhaseTimings(
+        &self,
+    ) -> windows_core::Result<windows::Foundation::Collections::IVectorView<RebuildPhaseTiming>>
+    {
+        let this = self;
+        unsafe {
+            let mut result__ = core::mem::zeroed();
+            (windows_core::Interface::vtable(this).GetPhaseTimings)(
+                windows_core::Interface::as_raw(this),
+                &mut result__,
+            )
+            .and_then(|| windows_core::Type::from_abi(result__))
+        }
+    }
+    pub fn GetTableSizes(
+        &self,
+    ) -> windows_core::Result<windows::Foundation::Collections::IVectorView<RebuildTableSize>> {
+        let this = self;
+        unsafe {
+            let mut result__ = core::mem::zeroed();
+            (windows_core::Interface::vtable(this).GetTableSizes)(
+                windows_core::Interface::as_raw(this),
+                &mut result__,
+            )
+            .and_then(|| windows_core::Type::from_abi(result__))
+        }
+    }
+}
+impl windows_core::RuntimeType for RebuildReport {
+    const SIGNATURE: windows_core::imp::ConstBuffer =
+        windows_core::imp::ConstBuffer::for_class::<Self, IRebuildReport>();
+}
+unsafe impl windows_core::Interface for RebuildReport {
+    type Vtable = IRebuildReport_Vtbl;
+    const IID: windows_core::GUID = <IRebuildReport as windows_core::Interface>::IID;
+}
+impl windows_core::RuntimeName for RebuildReport {
+    const NAME: &'static str = "LibSimbolMudah.RebuildReport";
+}
+unsafe impl Send for RebuildReport {}
+unsafe impl Sync for RebuildReport {}
+#[repr(C)]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RebuildSequenceCount {
+    pub source: windows_core::HSTRING,
+    pub count: u32,
+}
+impl windows_core::TypeKind for RebuildSequenceCount {
+    type TypeKind = windows_core::CloneType;
+}
+impl windows_core::RuntimeType for RebuildSequenceCount {
+    const SIGNATURE: windows_core::imp::ConstBuffer = windows_core::imp::ConstBuffer::from_slice(
+        b"struct(LibSimbolMudah.RebuildSequenceCount;string;u4)",
+    );
+}
+impl Default for RebuildSequenceCount {
+    fn default() -> Self {
+        unsafe { core::mem::zeroed() }
+    }
+}
+#[repr(C)]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RebuildTableSize {
+    pub name: windows_core::HSTRING,
+    pub bytes: u64,
+}
+impl windows_core::TypeKind for RebuildTableSize {
+    type TypeKind = windows_core::CloneType;
+}
+impl windows_core::RuntimeType for RebuildTableSize {
+    const SIGNATURE: windows_core::imp::ConstBuffer = windows_core::imp::ConstBuffer::from_slice(
+        b"struct(LibSimbolMudah.RebuildTableSize;string;u8)",
+    );
+}
+impl Default for RebuildTableSize {
+    fn default() -> Self {
+        unsafe { core::mem::zeroed() }
+    }
+}
 #[repr(transparent)]
 #[derive(PartialEq, Eq, Debug, Clone)]
 pub struct NotifyIcon(windows_core::IUnknown);
@@ -822,6 +1270,313 @@ impl SequenceDefinition {
             .and_then(|| windows_core::Type::from_abi(result__))
         }
     }
+    pub fn Export(&self, format: ExportFormat) -> windows_core::Result<windows_core::HSTRING> {
+        let this = self;
+        unsafe {
+            let mut result__ = core::mem::zeroed();
+            (windows_core::Interface::vtable(this).Export)(
+                windows_core::Interface::as_raw(this),
+                format,
+                &mut result__,
+            )
+            .and_then(|| windows_core::Type::from_abi(result__))
+        }
+    }
+    pub fn CheatSheet(
+        &self,
+        format: CheatSheetFormat,
+        sources: &windows_core::HSTRING,
+    ) -> windows_core::Result<windows_core::HSTRING> {
+        let this = self;
+        unsafe {
+            let mut result__ = core::mem::zeroed();
+            (windows_core::Interface::vtable(this).CheatSheet)(
+                windows_core::Interface::as_raw(this),
+                format,
+                core::mem::transmute_copy(sources),
+                &mut result__,
+            )
+            .and_then(|| windows_core::Type::from_abi(result__))
+        }
+    }
+    pub fn SetSearchLanguages<P0>(&self, languages: P0) -> windows_core::Result<()>
+    where
+        P0: windows_core::Param<
+            windows::Foundation::Collections::IVectorView<windows_core::HSTRING>,
+        >,
+    {
+        let this = self;
+        unsafe {
+            (windows_core::Interface::vtable(this).SetSearchLanguages)(
+                windows_core::Interface::as_raw(this),
+                languages.param().abi(),
+            )
+            .ok()
+        }
+    }
+    pub fn GetSearchLanguages(
+        &self,
+    ) -> windows_core::Result<windows::Foundation::Collections::IVectorView<windows_core::HSTRING>>
+    {
+        let this = self;
+        unsafe {
+            let mut result__ = core::mem::zeroed();
+            (windows_core::Interface::vtable(this).GetSearchLanguages)(
+                windows_core::Interface::as_raw(this),
+                &mut result__,
+            )
+            .and_then(|| windows_core::Type::from_abi(result__))
+        }
+    }
+    pub fn GetEmojiVariants(
+        &self,
+        emoji: &windows_core::HSTRING,
+    ) -> windows_core::Result<windows::Foundation::Collections::IVectorView<SequenceDescription>>
+    {
+        let this = self;
+        unsafe {
+            let mut result__ = core::mem::zeroed();
+            (windows_core::Interface::vtable(this).GetEmojiVariants)(
+                windows_core::Interface::as_raw(this),
+                core::mem::transmute_copy(emoji),
+                &mut result__,
+            )
+            .and_then(|| windows_core::Type::from_abi(result__))
+        }
+    }
+    pub fn SetPreferredSkinTone(&self, tone: u8) -> windows_core::Result<()> {
+        let this = self;
+        unsafe {
+            (windows_core::Interface::vtable(this).SetPreferredSkinTone)(
+                windows_core::Interface::as_raw(this),
+                tone,
+            )
+            .ok()
+        }
+    }
+    pub fn GetPreferredSkinTone(&self) -> windows_core::Result<u8> {
+        let this = self;
+        unsafe {
+            let mut result__ = core::mem::zeroed();
+            (windows_core::Interface::vtable(this).GetPreferredSkinTone)(
+                windows_core::Interface::as_raw(this),
+                &mut result__,
+            )
+            .map(|| result__)
+        }
+    }
+    pub fn GetEmojiInfo(&self, emoji: &windows_core::HSTRING) -> windows_core::Result<EmojiInfo> {
+        let this = self;
+        unsafe {
+            let mut result__ = core::mem::zeroed();
+            (windows_core::Interface::vtable(this).GetEmojiInfo)(
+                windows_core::Interface::as_raw(this),
+                core::mem::transmute_copy(emoji),
+                &mut result__,
+            )
+            .and_then(|| windows_core::Type::from_abi(result__))
+        }
+    }
+    pub fn GetEmojiGroups(
+        &self,
+    ) -> windows_core::Result<windows::Foundation::Collections::IVectorView<windows_core::HSTRING>>
+    {
+        let this = self;
+        unsafe {
+            let mut result__ = core::mem::zeroed();
+            (windows_core::Interface::vtable(this).GetEmojiGroups)(
+                windows_core::Interface::as_raw(this),
+                &mut result__,
+            )
+            .and_then(|| windows_core::Type::from_abi(result__))
+        }
+    }
+    pub fn GetCategories(
+        &self,
+    ) -> windows_core::Result<windows::Foundation::Collections::IVectorView<windows_core::HSTRING>>
+    {
+        let this = self;
+        unsafe {
+            let mut result__ = core::mem::zeroed();
+            (windows_core::Interface::vtable(this).GetCategories)(
+                windows_core::Interface::as_raw(this),
+                &mut result__,
+            )
+            .and_then(|| windows_core::Type::from_abi(result__))
+        }
+    }
+    pub fn GetCategorySize(&self, category: &windows_core::HSTRING) -> windows_core::Result<u32> {
+        let this = self;
+        unsafe {
+            let mut result__ = core::mem::zeroed();
+            (windows_core::Interface::vtable(this).GetCategorySize)(
+                windows_core::Interface::as_raw(this),
+                core::mem::transmute_copy(category),
+                &mut result__,
+            )
+            .map(|| result__)
+        }
+    }
+    pub fn GetCategoryMembers(
+        &self,
+        category: &windows_core::HSTRING,
+        offset: u32,
+        limit: u32,
+    ) -> windows_core::Result<windows::Foundation::Collections::IVectorView<SequenceDescription>>
+    {
+        let this = self;
+        unsafe {
+            let mut result__ = core::mem::zeroed();
+            (windows_core::Interface::vtable(this).GetCategoryMembers)(
+                windows_core::Interface::as_raw(this),
+                core::mem::transmute_copy(category),
+                offset,
+                limit,
+                &mut result__,
+            )
+            .and_then(|| windows_core::Type::from_abi(result__))
+        }
+    }
+    pub fn ShortcodePrefix(
+        &self,
+        prefix: &windows_core::HSTRING,
+        limit: u32,
+    ) -> windows_core::Result<windows::Foundation::Collections::IVectorView<SequenceDescription>>
+    {
+        let this = self;
+        unsafe {
+            let mut result__ = core::mem::zeroed();
+            (windows_core::Interface::vtable(this).ShortcodePrefix)(
+                windows_core::Interface::as_raw(this),
+                core::mem::transmute_copy(prefix),
+                limit,
+                &mut result__,
+            )
+            .and_then(|| windows_core::Type::from_abi(result__))
+        }
+    }
+    pub fn MatchSequence(
+        &self,
+        sequence: &windows_core::HSTRING,
+    ) -> windows_core::Result<SequenceMatch> {
+        let this = self;
+        unsafe {
+            let mut result__ = core::mem::zeroed();
+            (windows_core::Interface::vtable(this).MatchSequence)(
+                windows_core::Interface::as_raw(this),
+                core::mem::transmute_copy(sequence),
+                &mut result__,
+            )
+            .and_then(|| windows_core::Type::from_abi(result__))
+        }
+    }
+    pub fn SetMatchingOptions(
+        &self,
+        swappedorder: bool,
+        casefolded: bool,
+    ) -> windows_core::Result<()> {
+        let this = self;
+        unsafe {
+            (windows_core::Interface::vtable(this).SetMatchingOptions)(
+                windows_core::Interface::as_raw(this),
+                swappedorder,
+                casefolded,
+            )
+            .ok()
+        }
+    }
+    pub fn GetRebuildReport(
+        &self,
+    ) -> windows_core::Result<windows::Foundation::Collections::IVectorView<RebuildDiagnostic>>
+    {
+        let this = self;
+        unsafe {
+            let mut result__ = core::mem::zeroed();
+            (windows_core::Interface::vtable(this).GetRebuildReport)(
+                windows_core::Interface::as_raw(this),
+                &mut result__,
+            )
+            .and_then(|| windows_core::Type::from_abi(result__))
+        }
+    }
+    pub fn GetRebuildStatistics(&self) -> windows_core::Result<RebuildReport> {
+        let this = self;
+        unsafe {
+            let mut result__ = core::mem::zeroed();
+            (windows_core::Interface::vtable(this).GetRebuildStatistics)(
+                windows_core::Interface::as_raw(this),
+                &mut result__,
+            )
+            .and_then(|| windows_core::Type::from_abi(result__))
+        }
+    }
+    pub fn RebuildAsync(
+        &self,
+        keysymdef: &windows_core::HSTRING,
+        composedef: &windows_core::HSTRING,
+        annotations: &windows_core::HSTRING,
+    ) -> windows_core::Result<windows::Foundation::IAsyncActionWithProgress<RebuildPhase>> {
+        let this = self;
+        unsafe {
+            let mut result__ = core::mem::zeroed();
+            (windows_core::Interface::vtable(this).RebuildAsync)(
+                windows_core::Interface::as_raw(this),
+                core::mem::transmute_copy(keysymdef),
+                core::mem::transmute_copy(composedef),
+                core::mem::transmute_copy(annotations),
+                &mut result__,
+            )
+            .and_then(|| windows_core::Type::from_abi(result__))
+        }
+    }
+    pub fn SetHotReload(
+        &self,
+        enabled: bool,
+        debouncemilliseconds: u32,
+    ) -> windows_core::Result<()> {
+        let this = self;
+        unsafe {
+            (windows_core::Interface::vtable(this).SetHotReload)(
+                windows_core::Interface::as_raw(this),
+                enabled,
+                debouncemilliseconds,
+            )
+            .ok()
+        }
+    }
+    pub fn OnReloadFailed<P0>(
+        &self,
+        handler: P0,
+    ) -> windows_core::Result<windows::Foundation::EventRegistrationToken>
+    where
+        P0: windows_core::Param<
+            windows::Foundation::TypedEventHandler<SequenceDefinition, windows_core::HSTRING>,
+        >,
+    {
+        let this = self;
+        unsafe {
+            let mut result__ = core::mem::zeroed();
+            (windows_core::Interface::vtable(this).OnReloadFailed)(
+                windows_core::Interface::as_raw(this),
+                handler.param().abi(),
+                &mut result__,
+            )
+            .map(|| result__)
+        }
+    }
+    pub fn RemoveOnReloadFailed(
+        &self,
+        token: windows::Foundation::EventRegistrationToken,
+    ) -> windows_core::Result<()> {
+        let this = self;
+        unsafe {
+            (windows_core::Interface::vtable(this).RemoveOnReloadFailed)(
+                windows_core::Interface::as_raw(this),
+                token,
+            )
+            .ok()
+        }
+    }
 }
 impl windows_core::RuntimeType for SequenceDefinition {
     const SIGNATURE: windows_core::imp::ConstBuffer =
@@ -842,13 +1597,14 @@ pub struct SequenceDescription {
     pub sequence: windows_core::HSTRING,
     pub result: windows_core::HSTRING,
     pub description: windows_core::HSTRING,
+    pub keywords: windows_core::HSTRING,
 }
 impl windows_core::TypeKind for SequenceDescription {
     type TypeKind = windows_core::CloneType;
 }
 impl windows_core::RuntimeType for SequenceDescription {
     const SIGNATURE: windows_core::imp::ConstBuffer = windows_core::imp::ConstBuffer::from_slice(
-        b"struct(LibSimbolMudah.SequenceDescription;string;string;string)",
+        b"struct(LibSimbolMudah.SequenceDescription;string;string;string;string)",
     );
 }
 impl Default for SequenceDescription {
@@ -856,6 +1612,46 @@ impl Default for SequenceDescription {
         unsafe { core::mem::zeroed() }
     }
 }
+#[repr(transparent)]
+#[derive(PartialEq, Eq, Copy, Clone, Default)]
+pub struct SequenceFallback(pub i32);
+impl SequenceFallback {
+    pub const Exact: Self = Self(0i32);
+    pub const SwappedOrder: Self = Self(1i32);
+    pub const CaseFolded: Self = Self(2i32);
+}
+impl windows_core::TypeKind for SequenceFallback {
+    type TypeKind = windows_core::CopyType;
+}
+impl core::fmt::Debug for SequenceFallback {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_tuple("SequenceFallback").field(&self.0).finish()
+    }
+}
+impl windows_core::RuntimeType for SequenceFallback {
+    const SIGNATURE: windows_core::imp::ConstBuffer =
+        windows_core::imp::ConstBuffer::from_slice(b"enum(LibSimbolMudah.SequenceFallback;i4)");
+}
+#[repr(C)]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SequenceMatch {
+    pub result: windows_core::HSTRING,
+    pub continuations: u32,
+    pub fallback: SequenceFallback,
+}
+impl windows_core::TypeKind for SequenceMatch {
+    type TypeKind = windows_core::CloneType;
+}
+impl windows_core::RuntimeType for SequenceMatch {
+    const SIGNATURE: windows_core::imp::ConstBuffer = windows_core::imp::ConstBuffer::from_slice(
+        b"struct(LibSimbolMudah.SequenceMatch;string;u4;enum(LibSimbolMudah.SequenceFallback;i4))",
+    );
+}
+impl Default for SequenceMatch {
+    fn default() -> Self {
+        unsafe { core::mem::zeroed() }
+    }
+}
 pub trait IKeyboardHook_Impl: Sized {
     fn ResetStage(&self) -> windows_core::Result<()>;
     fn OnStateChanged(
@@ -1070,6 +1866,11 @@ pub trait IKeyboardTranslator_Impl: Sized {
         &self,
         token: &windows::Foundation::EventRegistrationToken,
     ) -> windows_core::Result<()>;
+    fn SetAmbiguousConfirmation(
+        &self,
+        enabled: bool,
+        timeoutmilliseconds: u32,
+    ) -> windows_core::Result<()>;
 }
 impl windows_core::RuntimeName for IKeyboardTranslator {
     const NAME: &'static str = "LibSimbolMudah.IKeyboardTranslator";
@@ -1228,8 +2029,23 @@ impl IKeyboardTranslator_Vtbl {
             IKeyboardTranslator_Impl::RemoveOnKeyTranslated(this, core::mem::transmute(&token))
                 .into()
         }
-        Self {
-            base__: windows_core::IInspectable_Vtbl::new::<Identity, IKeyboardTranslator, OFFSET>(),
+        unsafe extern "system" fn SetAmbiguousConfirmation<
+            Identity: windows_core::IUnknownImpl,
+            const OFFSET: isize,
+        >(
+            this: *mut core::ffi::c_void,
+            enabled: bool,
+            timeoutmilliseconds: u32,
+        ) -> windows_core::HRESULT
+        where
+            Identity: IKeyboardTranslator_Impl,
+        {
+            let this: &Identity = &*((this as *const *const ()).offset(OFFSET) as *const Identity);
+            IKeyboardTranslator_Impl::SetAmbiguousConfirmation(this, enabled, timeoutmilliseconds)
+                .into()
+        }
+        Self {
+            base__: windows_core::IInspectable_Vtbl::new::<Identity, IKeyboardTranslator, OFFSET>(),
             TranslateAndForward: TranslateAndForward::<Identity, OFFSET>,
             CheckLayoutAndUpdate: CheckLayoutAndUpdate::<Identity, OFFSET>,
             OnTranslated: OnTranslated::<Identity, OFFSET>,
@@ -1238,6 +2054,7 @@ impl IKeyboardTranslator_Vtbl {
             RemoveOnInvalid: RemoveOnInvalid::<Identity, OFFSET>,
             OnKeyTranslated: OnKeyTranslated::<Identity, OFFSET>,
             RemoveOnKeyTranslated: RemoveOnKeyTranslated::<Identity, OFFSET>,
+            SetAmbiguousConfirmation: SetAmbiguousConfirmation::<Identity, OFFSET>,
         }
     }
     pub fn matches(iid: &windows_core::GUID) -> bool {
@@ -1529,6 +2346,166 @@ impl INotifyIconFactory_Vtbl {
         iid == &<INotifyIconFactory as windows_core::Interface>::IID
     }
 }
+pub trait IRebuildReport_Impl: Sized {
+    fn GetSequenceCounts(
+        &self,
+    ) -> windows_core::Result<windows::Foundation::Collections::IVectorView<RebuildSequenceCount>>;
+    fn GetSkippedLineCount(&self) -> windows_core::Result<u32>;
+    fn GetSkippedLineSamples(
+        &self,
+    ) -> windows_core::Result<windows::Foundation::Collections::IVectorView<RebuildDiagnostic>>;
+    fn GetLocales(
+        &self,
+    ) -> windows_core::Result<windows::Foundation::Collections::IVectorView<windows_core::HSTRING>>;
+    fn GetPhaseTimings(
+        &self,
+    ) -> windows_core::Result<windows::Foundation::Collections::IVectorView<RebuildPhaseTiming>>;
+    fn GetTableSizes(
+        &self,
+    ) -> windows_core::Result<windows::Foundation::Collections::IVectorView<RebuildTableSize>>;
+}
+impl windows_core::RuntimeName for IRebuildReport {
+    const NAME: &'static str = "LibSimbolMudah.IRebuildReport";
+}
+impl IRebuildReport_Vtbl {
+    pub const fn new<Identity: windows_core::IUnknownImpl, const OFFSET: isize>(
+    ) -> IRebuildReport_Vtbl
+    where
+        Identity: IRebuildReport_Impl,
+    {
+        unsafe extern "system" fn GetSequenceCounts<
+            Identity: windows_core::IUnknownImpl,
+            const OFFSET: isize,
+        >(
+            this: *mut core::ffi::c_void,
+            result__: *mut *mut core::ffi::c_void,
+        ) -> windows_core::HRESULT
+        where
+            Identity: IRebuildReport_Impl,
+        {
+            let this: &Identity = &*((this as *const *const ()).offset(OFFSET) as *const Identity);
+            match IRebuildReport_Impl::GetSequenceCounts(this) {
+                Ok(ok__) => {
+                    result__.write(core::mem::transmute_copy(&ok__));
+                    core::mem::forget(ok__);
+                    windows_core::HRESULT(0)
+                }
+                Err(err) => err.into(),
+            }
+        }
+        unsafe extern "system" fn GetSkippedLineCount<
+            Identity: windows_core::IUnknownImpl,
+            const OFFSET: isize,
+        >(
+            this: *mut core::ffi::c_void,
+            result__: *mut u32,
+        ) -> windows_core::HRESULT
+        where
+            Identity: IRebuildReport_Impl,
+        {
+            let this: &Identity = &*((this as *const *const ()).offset(OFFSET) as *const Identity);
+            match IRebuildReport_Impl::GetSkippedLineCount(this) {
+                Ok(ok__) => {
+                    result__.write(core::mem::transmute_copy(&ok__));
+                    windows_core::HRESULT(0)
+                }
+                Err(err) => err.into(),
+            }
+        }
+        unsafe extern "system" fn GetSkippedLineSamples<
+            Identity: windows_core::IUnknownImpl,
+            const OFFSET: isize,
+        >(
+            this: *mut core::ffi::c_void,
+            result__: *mut *mut core::ffi::c_void,
+        ) -> windows_core::HRESULT
+        where
+            Identity: IRebuildReport_Impl,
+        {
+            let this: &Identity = &*((this as *const *const ()).offset(OFFSET) as *const Identity);
+            match IRebuildReport_Impl::GetSkippedLineSamples(this) {
+                Ok(ok__) => {
+                    result__.write(core::mem::transmute_copy(&ok__));
+                    core::mem::forget(ok__);
+                    windows_core::HRESULT(0)
+                }
+                Err(err) => err.into(),
+            }
+        }
+        unsafe extern "system" fn GetLocales<
+            Identity: windows_core::IUnknownImpl,
+            const OFFSET: isize,
+        >(
+            this: *mut core::ffi::c_void,
+            result__: *mut *mut core::ffi::c_void,
+        ) -> windows_core::HRESULT
+        where
+            Identity: IRebuildReport_Impl,
+        {
+            let this: &Identity = &*((this as *const *const ()).offset(OFFSET) as *const Identity);
+            match IRebuildReport_Impl::GetLocales(this) {
+                Ok(ok__) => {
+                    result__.write(core::mem::transmute_copy(&ok__));
+                    core::mem::forget(ok__);
+                    windows_core::HRESULT(0)
+                }
+                Err(err) => err.into(),
+            }
+        }
+        unsafe extern "system" fn GetPhaseTimings<
+            Identity: windows_core::IUnknownImpl,
+            const OFFSET: isize,
+        >(
+            this: *mut core::ffi::c_void,
+            result__: *mut *mut core::ffi::c_void,
+        ) -> windows_core::HRESULT
+        where
+            Identity: IRebuildReport_Impl,
+        {
+            let this: &Identity = &*((this as *const *const ()).offset(OFFSET) as *const Identity);
+            match IRebuildReport_Impl::GetPhaseTimings(this) {
+                Ok(ok__) => {
+                    result__.write(core::mem::transmute_copy(&ok__));
+                    core::mem::forget(ok__);
+                    windows_core::HRESULT(0)
+                }
+                Err(err) => err.into(),
+            }
+        }
+        unsafe extern "system" fn GetTableSizes<
+            Identity: windows_core::IUnknownImpl,
+            const OFFSET: isize,
+        >(
+            this: *mut core::ffi::c_void,
+            result__: *mut *mut core::ffi::c_void,
+        ) -> windows_core::HRESULT
+        where
+            Identity: IRebuildReport_Impl,
+        {
+            let this: &Identity = &*((this as *const *const ()).offset(OFFSET) as *const Identity);
+            match IRebuildReport_Impl::GetTableSizes(this) {
+                Ok(ok__) => {
+                    result__.write(core::mem::transmute_copy(&ok__));
+                    core::mem::forget(ok__);
+                    windows_core::HRESULT(0)
+                }
+                Err(err) => err.into(),
+            }
+        }
+        Self {
+            base__: windows_core::IInspectable_Vtbl::new::<Identity, IRebuildReport, OFFSET>(),
+            GetSequenceCounts: GetSequenceCounts::<Identity, OFFSET>,
+            GetSkippedLineCount: GetSkippedLineCount::<Identity, OFFSET>,
+            GetSkippedLineSamples: GetSkippedLineSamples::<Identity, OFFSET>,
+            GetLocales: GetLocales::<Identity, OFFSET>,
+            GetPhaseTimings: GetPhaseTimings::<Identity, OFFSET>,
+            GetTableSizes: GetTableSizes::<Identity, OFFSET>,
+        }
+    }
+    pub fn matches(iid: &windows_core::GUID) -> bool {
+        iid == &<IRebuildReport as windows_core::Interface>::IID
+    }
+}
 pub trait ISenderStatics_Impl: Sized {
     fn SendTextClipboard(
         &self,
@@ -1592,6 +2569,70 @@ pub trait ISequenceDefinition_Impl: Sized {
         limit: u32,
     ) -> windows_core::Result<windows::Foundation::Collections::IVectorView<SequenceDescription>>;
     fn GetLocalizedName(&self, codepoint: u32) -> windows_core::Result<SequenceDescription>;
+    fn Export(&self, format: ExportFormat) -> windows_core::Result<windows_core::HSTRING>;
+    fn CheatSheet(
+        &self,
+        format: CheatSheetFormat,
+        sources: &windows_core::HSTRING,
+    ) -> windows_core::Result<windows_core::HSTRING>;
+    fn SetSearchLanguages(
+        &self,
+        languages: Option<&windows::Foundation::Collections::IVectorView<windows_core::HSTRING>>,
+    ) -> windows_core::Result<()>;
+    fn GetSearchLanguages(
+        &self,
+    ) -> windows_core::Result<windows::Foundation::Collections::IVectorView<windows_core::HSTRING>>;
+    fn GetEmojiVariants(
+        &self,
+        emoji: &windows_core::HSTRING,
+    ) -> windows_core::Result<windows::Foundation::Collections::IVectorView<SequenceDescription>>;
+    fn SetPreferredSkinTone(&self, tone: u8) -> windows_core::Result<()>;
+    fn GetPreferredSkinTone(&self) -> windows_core::Result<u8>;
+    fn GetEmojiInfo(&self, emoji: &windows_core::HSTRING) -> windows_core::Result<EmojiInfo>;
+    fn GetEmojiGroups(
+        &self,
+    ) -> windows_core::Result<windows::Foundation::Collections::IVectorView<windows_core::HSTRING>>;
+    fn GetCategories(
+        &self,
+    ) -> windows_core::Result<windows::Foundation::Collections::IVectorView<windows_core::HSTRING>>;
+    fn GetCategorySize(&self, category: &windows_core::HSTRING) -> windows_core::Result<u32>;
+    fn GetCategoryMembers(
+        &self,
+        category: &windows_core::HSTRING,
+        offset: u32,
+        limit: u32,
+    ) -> windows_core::Result<windows::Foundation::Collections::IVectorView<SequenceDescription>>;
+    fn ShortcodePrefix(
+        &self,
+        prefix: &windows_core::HSTRING,
+        limit: u32,
+    ) -> windows_core::Result<windows::Foundation::Collections::IVectorView<SequenceDescription>>;
+    fn MatchSequence(
+        &self,
+        sequence: &windows_core::HSTRING,
+    ) -> windows_core::Result<SequenceMatch>;
+    fn SetMatchingOptions(&self, swappedorder: bool, casefolded: bool) -> windows_core::Result<()>;
+    fn GetRebuildReport(
+        &self,
+    ) -> windows_core::Result<windows::Foundation::Collections::IVectorView<RebuildDiagnostic>>;
+    fn GetRebuildStatistics(&self) -> windows_core::Result<RebuildReport>;
+    fn RebuildAsync(
+        &self,
+        keysymdef: &windows_core::HSTRING,
+        composedef: &windows_core::HSTRING,
+        annotations: &windows_core::HSTRING,
+    ) -> windows_core::Result<windows::Foundation::IAsyncActionWithProgress<RebuildPhase>>;
+    fn SetHotReload(&self, enabled: bool, debouncemilliseconds: u32) -> windows_core::Result<()>;
+    fn OnReloadFailed(
+        &self,
+        handler: Option<
+            &windows::Foundation::TypedEventHandler<SequenceDefinition, windows_core::HSTRING>,
+        >,
+    ) -> windows_core::Result<windows::Foundation::EventRegistrationToken>;
+    fn RemoveOnReloadFailed(
+        &self,
+        token: &windows::Foundation::EventRegistrationToken,
+    ) -> windows_core::Result<()>;
 }
 impl windows_core::RuntimeName for ISequenceDefinition {
     const NAME: &'static str = "LibSimbolMudah.ISequenceDefinition";
@@ -1692,12 +2733,456 @@ impl ISequenceDefinition_Vtbl {
                 Err(err) => err.into(),
             }
         }
+        unsafe extern "system" fn Export<
+            Identity: windows_core::IUnknownImpl,
+            const OFFSET: isize,
+        >(
+            this: *mut core::ffi::c_void,
+            format: ExportFormat,
+            result__: *mut core::mem::MaybeUninit<windows_core::HSTRING>,
+        ) -> windows_core::HRESULT
+        where
+            Identity: ISequenceDefinition_Impl,
+        {
+            let this: &Identity = &*((this as *const *const ()).offset(OFFSET) as *const Identity);
+            match ISequenceDefinition_Impl::Export(this, format) {
+                Ok(ok__) => {
+                    result__.write(core::mem::transmute_copy(&ok__));
+                    core::mem::forget(ok__);
+                    windows_core::HRESULT(0)
+                }
+                Err(err) => err.into(),
+            }
+        }
+        unsafe extern "system" fn CheatSheet<
+            Identity: windows_core::IUnknownImpl,
+            const OFFSET: isize,
+        >(
+            this: *mut core::ffi::c_void,
+            format: CheatSheetFormat,
+            sources: core::mem::MaybeUninit<windows_core::HSTRING>,
+            result__: *mut core::mem::MaybeUninit<windows_core::HSTRING>,
+        ) -> windows_core::HRESULT
+        where
+            Identity: ISequenceDefinition_Impl,
+        {
+            let this: &Identity = &*((this as *const *const ()).offset(OFFSET) as *const Identity);
+            match ISequenceDefinition_Impl::CheatSheet(this, format, core::mem::transmute(&sources))
+            {
+                Ok(ok__) => {
+                    result__.write(core::mem::transmute_copy(&ok__));
+                    core::mem::forget(ok__);
+                    windows_core::HRESULT(0)
+                }
+                Err(err) => err.into(),
+            }
+        }
+        unsafe extern "system" fn SetSearchLanguages<
+            Identity: windows_core::IUnknownImpl,
+            const OFFSET: isize,
+        >(
+            this: *mut core::ffi::c_void,
+            languages: *mut core::ffi::c_void,
+        ) -> windows_core::HRESULT
+        where
+            Identity: ISequenceDefinition_Impl,
+        {
+            let this: &Identity = &*((this as *const *const ()).offset(OFFSET) as *const Identity);
+            ISequenceDefinition_Impl::SetSearchLanguages(
+                this,
+                windows_core::from_raw_borrowed(&languages),
+            )
+            .into()
+        }
+        unsafe extern "system" fn GetSearchLanguages<
+            Identity: windows_core::IUnknownImpl,
+            const OFFSET: isize,
+        >(
+            this: *mut core::ffi::c_void,
+            result__: *mut *mut core::ffi::c_void,
+        ) -> windows_core::HRESULT
+        where
+            Identity: ISequenceDefinition_Impl,
+        {
+            let this: &Identity = &*((this as *const *const ()).offset(OFFSET) as *const Identity);
+            match ISequenceDefinition_Impl::GetSearchLanguages(this) {
+                Ok(ok__) => {
+                    result__.write(core::mem::transmute_copy(&ok__));
+                    core::mem::forget(ok__);
+                    windows_core::HRESULT(0)
+                }
+                Err(err) => err.into(),
+            }
+        }
+        unsafe extern "system" fn GetEmojiVariants<
+            Identity: windows_core::IUnknownImpl,
+            const OFFSET: isize,
+        >(
+            this: *mut core::ffi::c_void,
+            emoji: core::mem::MaybeUninit<windows_core::HSTRING>,
+            result__: *mut *mut core::ffi::c_void,
+        ) -> windows_core::HRESULT
+        where
+            Identity: ISequenceDefinition_Impl,
+        {
+            let this: &Identity = &*((this as *const *const ()).offset(OFFSET) as *const Identity);
+            match ISequenceDefinition_Impl::GetEmojiVariants(this, core::mem::transmute(&emoji)) {
+                Ok(ok__) => {
+                    result__.write(core::mem::transmute_copy(&ok__));
+                    core::mem::forget(ok__);
+                    windows_core::HRESULT(0)
+                }
+                Err(err) => err.into(),
+            }
+        }
+        unsafe extern "system" fn SetPreferredSkinTone<
+            Identity: windows_core::IUnknownImpl,
+            const OFFSET: isize,
+        >(
+            this: *mut core::ffi::c_void,
+            tone: u8,
+        ) -> windows_core::HRESULT
+        where
+            Identity: ISequenceDefinition_Impl,
+        {
+            let this: &Identity = &*((this as *const *const ()).offset(OFFSET) as *const Identity);
+            ISequenceDefinition_Impl::SetPreferredSkinTone(this, tone).into()
+        }
+        unsafe extern "system" fn GetPreferredSkinTone<
+            Identity: windows_core::IUnknownImpl,
+            const OFFSET: isize,
+        >(
+            this: *mut core::ffi::c_void,
+            result__: *mut u8,
+        ) -> windows_core::HRESULT
+        where
+            Identity: ISequenceDefinition_Impl,
+        {
+            let this: &Identity = &*((this as *const *const ()).offset(OFFSET) as *const Identity);
+            match ISequenceDefinition_Impl::GetPreferredSkinTone(this) {
+                Ok(ok__) => {
+                    result__.write(core::mem::transmute_copy(&ok__));
+                    windows_core::HRESULT(0)
+                }
+                Err(err) => err.into(),
+            }
+        }
+        unsafe extern "system" fn GetEmojiInfo<
+            Identity: windows_core::IUnknownImpl,
+            const OFFSET: isize,
+        >(
+            this: *mut core::ffi::c_void,
+            emoji: core::mem::MaybeUninit<windows_core::HSTRING>,
+            result__: *mut core::mem::MaybeUninit<EmojiInfo>,
+        ) -> windows_core::HRESULT
+        where
+            Identity: ISequenceDefinition_Impl,
+        {
+            let this: &Identity = &*((this as *const *const ()).offset(OFFSET) as *const Identity);
+            match ISequenceDefinition_Impl::GetEmojiInfo(this, core::mem::transmute(&emoji)) {
+                Ok(ok__) => {
+                    result__.write(core::mem::transmute_copy(&ok__));
+                    core::mem::forget(ok__);
+                    windows_core::HRESULT(0)
+                }
+                Err(err) => err.into(),
+            }
+        }
+        unsafe extern "system" fn GetEmojiGroups<
+            Identity: windows_core::IUnknownImpl,
+            const OFFSET: isize,
+        >(
+            this: *mut core::ffi::c_void,
+            result__: *mut *mut core::ffi::c_void,
+        ) -> windows_core::HRESULT
+        where
+            Identity: ISequenceDefinition_Impl,
+        {
+            let this: &Identity = &*((this as *const *const ()).offset(OFFSET) as *const Identity);
+            match ISequenceDefinition_Impl::GetEmojiGroups(this) {
+                Ok(ok__) => {
+                    result__.write(core::mem::transmute_copy(&ok__));
+                    core::mem::forget(ok__);
+                    windows_core::HRESULT(0)
+                }
+                Err(err) => err.into(),
+            }
+        }
+        unsafe extern "system" fn GetCategories<
+            Identity: windows_core::IUnknownImpl,
+            const OFFSET: isize,
+        >(
+            this: *mut core::ffi::c_void,
+            result__: *mut *mut core::ffi::c_void,
+        ) -> windows_core::HRESULT
+        where
+            Identity: ISequenceDefinition_Impl,
+        {
+            let this: &Identity = &*((this as *const *const ()).offset(OFFSET) as *const Identity);
+            match ISequenceDefinition_Impl::GetCategories(this) {
+                Ok(ok__) => {
+                    result__.write(core::mem::transmute_copy(&ok__));
+                    core::mem::forget(ok__);
+                    windows_core::HRESULT(0)
+                }
+                Err(err) => err.into(),
+            }
+        }
+        unsafe extern "system" fn GetCategorySize<
+            Identity: windows_core::IUnknownImpl,
+            const OFFSET: isize,
+        >(
+            this: *mut core::ffi::c_void,
+            category: core::mem::MaybeUninit<windows_core::HSTRING>,
+            result__: *mut u32,
+        ) -> windows_core::HRESULT
+        where
+            Identity: ISequenceDefinition_Impl,
+        {
+            let this: &Identity = &*((this as *const *const ()).offset(OFFSET) as *const Identity);
+            match ISequenceDefinition_Impl::GetCategorySize(this, core::mem::transmute(&category)) {
+                Ok(ok__) => {
+                    result__.write(core::mem::transmute_copy(&ok__));
+                    windows_core::HRESULT(0)
+                }
+                Err(err) => err.into(),
+            }
+        }
+        unsafe extern "system" fn GetCategoryMembers<
+            Identity: windows_core::IUnknownImpl,
+            const OFFSET: isize,
+        >(
+            this: *mut core::ffi::c_void,
+            category: core::mem::MaybeUninit<windows_core::HSTRING>,
+            offset: u32,
+            limit: u32,
+            result__: *mut *mut core::ffi::c_void,
+        ) -> windows_core::HRESULT
+        where
+            Identity: ISequenceDefinition_Impl,
+        {
+            let this: &Identity = &*((this as *const *const ()).offset(OFFSET) as *const Identity);
+            match ISequenceDefinition_Impl::GetCategoryMembers(
+                this,
+                core::mem::transmute(&category),
+                offset,
+                limit,
+            ) {
+                Ok(ok__) => {
+                    result__.write(core::mem::transmute_copy(&ok__));
+                    core::mem::forget(ok__);
+                    windows_core::HRESULT(0)
+                }
+                Err(err) => err.into(),
+            }
+        }
+        unsafe extern "system" fn ShortcodePrefix<
+            Identity: windows_core::IUnknownImpl,
+            const OFFSET: isize,
+        >(
+            this: *mut core::ffi::c_void,
+            prefix: core::mem::MaybeUninit<windows_core::HSTRING>,
+            limit: u32,
+            result__: *mut *mut core::ffi::c_void,
+        ) -> windows_core::HRESULT
+        where
+            Identity: ISequenceDefinition_Impl,
+        {
+            let this: &Identity = &*((this as *const *const ()).offset(OFFSET) as *const Identity);
+            match ISequenceDefinition_Impl::ShortcodePrefix(
+                this,
+                core::mem::transmute(&prefix),
+                limit,
+            ) {
+                Ok(ok__) => {
+                    result__.write(core::mem::transmute_copy(&ok__));
+                    core::mem::forget(ok__);
+                    windows_core::HRESULT(0)
+                }
+                Err(err) => err.into(),
+            }
+        }
+        unsafe extern "system" fn MatchSequence<
+            Identity: windows_core::IUnknownImpl,
+            const OFFSET: isize,
+        >(
+            this: *mut core::ffi::c_void,
+            sequence: core::mem::MaybeUninit<windows_core::HSTRING>,
+            result__: *mut core::mem::MaybeUninit<SequenceMatch>,
+        ) -> windows_core::HRESULT
+        where
+            Identity: ISequenceDefinition_Impl,
+        {
+            let this: &Identity = &*((this as *const *const ()).offset(OFFSET) as *const Identity);
+            match ISequenceDefinition_Impl::MatchSequence(this, core::mem::transmute(&sequence)) {
+                Ok(ok__) => {
+                    result__.write(core::mem::transmute_copy(&ok__));
+                    core::mem::forget(ok__);
+                    windows_core::HRESULT(0)
+                }
+                Err(err) => err.into(),
+            }
+        }
+        unsafe extern "system" fn SetMatchingOptions<
+            Identity: windows_core::IUnknownImpl,
+            const OFFSET: isize,
+        >(
+            this: *mut core::ffi::c_void,
+            swappedorder: bool,
+            casefolded: bool,
+        ) -> windows_core::HRESULT
+        where
+            Identity: ISequenceDefinition_Impl,
+        {
+            let this: &Identity = &*((this as *const *const ()).offset(OFFSET) as *const Identity);
+            ISequenceDefinition_Impl::SetMatchingOptions(this, swappedorder, casefolded).into()
+        }
+        unsafe extern "system" fn GetRebuildReport<
+            Identity: windows_core::IUnknownImpl,
+            const OFFSET: isize,
+        >(
+            this: *mut core::ffi::c_void,
+            result__: *mut *mut core::ffi::c_void,
+        ) -> windows_core::HRESULT
+        where
+            Identity: ISequenceDefinition_Impl,
+        {
+            let this: &Identity = &*((this as *const *const ()).offset(OFFSET) as *const Identity);
+            match ISequenceDefinition_Impl::GetRebuildReport(this) {
+                Ok(ok__) => {
+                    result__.write(core::mem::transmute_copy(&ok__));
+                    core::mem::forget(ok__);
+                    windows_core::HRESULT(0)
+                }
+                Err(err) => err.into(),
+            }
+        }
+        unsafe extern "system" fn GetRebuildStatistics<
+            Identity: windows_core::IUnknownImpl,
+            const OFFSET: isize,
+        >(
+            this: *mut core::ffi::c_void,
+            result__: *mut *mut core::ffi::c_void,
+        ) -> windows_core::HRESULT
+        where
+            Identity: ISequenceDefinition_Impl,
+        {
+            let this: &Identity = &*((this as *const *const ()).offset(OFFSET) as *const Identity);
+            match ISequenceDefinition_Impl::GetRebuildStatistics(this) {
+                Ok(ok__) => {
+                    result__.write(core::mem::transmute_copy(&ok__));
+                    core::mem::forget(ok__);
+                    windows_core::HRESULT(0)
+                }
+                Err(err) => err.into(),
+            }
+        }
+        unsafe extern "system" fn RebuildAsync<
+            Identity: windows_core::IUnknownImpl,
+            const OFFSET: isize,
+        >(
+            this: *mut core::ffi::c_void,
+            keysymdef: core::mem::MaybeUninit<windows_core::HSTRING>,
+            composedef: core::mem::MaybeUninit<windows_core::HSTRING>,
+            annotations: core::mem::MaybeUninit<windows_core::HSTRING>,
+            result__: *mut *mut core::ffi::c_void,
+        ) -> windows_core::HRESULT
+        where
+            Identity: ISequenceDefinition_Impl,
+        {
+            let this: &Identity = &*((this as *const *const ()).offset(OFFSET) as *const Identity);
+            match ISequenceDefinition_Impl::RebuildAsync(
+                this,
+                core::mem::transmute(&keysymdef),
+                core::mem::transmute(&composedef),
+                core::mem::transmute(&annotations),
+            ) {
+                Ok(ok__) => {
+                    result__.write(core::mem::transmute_copy(&ok__));
+                    core::mem::forget(ok__);
+                    windows_core::HRESULT(0)
+                }
+                Err(err) => err.into(),
+            }
+        }
+        unsafe extern "system" fn SetHotReload<
+            Identity: windows_core::IUnknownImpl,
+            const OFFSET: isize,
+        >(
+            this: *mut core::ffi::c_void,
+            enabled: bool,
+            debouncemilliseconds: u32,
+        ) -> windows_core::HRESULT
+        where
+            Identity: ISequenceDefinition_Impl,
+        {
+            let this: &Identity = &*((this as *const *const ()).offset(OFFSET) as *const Identity);
+            ISequenceDefinition_Impl::SetHotReload(this, enabled, debouncemilliseconds).into()
+        }
+        unsafe extern "system" fn OnReloadFailed<
+            Identity: windows_core::IUnknownImpl,
+            const OFFSET: isize,
+        >(
+            this: *mut core::ffi::c_void,
+            handler: *mut core::ffi::c_void,
+            result__: *mut windows::Foundation::EventRegistrationToken,
+        ) -> windows_core::HRESULT
+        where
+            Identity: ISequenceDefinition_Impl,
+        {
+            let this: &Identity = &*((this as *const *const ()).offset(OFFSET) as *const Identity);
+            match ISequenceDefinition_Impl::OnReloadFailed(
+                this,
+                windows_core::from_raw_borrowed(&handler),
+            ) {
+                Ok(ok__) => {
+                    result__.write(core::mem::transmute_copy(&ok__));
+                    windows_core::HRESULT(0)
+                }
+                Err(err) => err.into(),
+            }
+        }
+        unsafe extern "system" fn RemoveOnReloadFailed<
+            Identity: windows_core::IUnknownImpl,
+            const OFFSET: isize,
+        >(
+            this: *mut core::ffi::c_void,
+            token: windows::Foundation::EventRegistrationToken,
+        ) -> windows_core::HRESULT
+        where
+            Identity: ISequenceDefinition_Impl,
+        {
+            let this: &Identity = &*((this as *const *const ()).offset(OFFSET) as *const Identity);
+            ISequenceDefinition_Impl::RemoveOnReloadFailed(this, core::mem::transmute(&token))
+                .into()
+        }
         Self {
             base__: windows_core::IInspectable_Vtbl::new::<Identity, ISequenceDefinition, OFFSET>(),
             Rebuild: Rebuild::<Identity, OFFSET>,
             PotentialPrefix: PotentialPrefix::<Identity, OFFSET>,
             Search: Search::<Identity, OFFSET>,
             GetLocalizedName: GetLocalizedName::<Identity, OFFSET>,
+            Export: Export::<Identity, OFFSET>,
+            CheatSheet: CheatSheet::<Identity, OFFSET>,
+            SetSearchLanguages: SetSearchLanguages::<Identity, OFFSET>,
+            GetSearchLanguages: GetSearchLanguages::<Identity, OFFSET>,
+            GetEmojiVariants: GetEmojiVariants::<Identity, OFFSET>,
+            SetPreferredSkinTone: SetPreferredSkinTone::<Identity, OFFSET>,
+            GetPreferredSkinTone: GetPreferredSkinTone::<Identity, OFFSET>,
+            GetEmojiInfo: GetEmojiInfo::<Identity, OFFSET>,
+            GetEmojiGroups: GetEmojiGroups::<Identity, OFFSET>,
+            GetCategories: GetCategories::<Identity, OFFSET>,
+            GetCategorySize: GetCategorySize::<Identity, OFFSET>,
+            GetCategoryMembers: GetCategoryMembers::<Identity, OFFSET>,
+            ShortcodePrefix: ShortcodePrefix::<Identity, OFFSET>,
+            MatchSequence: MatchSequence::<Identity, OFFSET>,
+            SetMatchingOptions: SetMatchingOptions::<Identity, OFFSET>,
+            GetRebuildReport: GetRebuildReport::<Identity, OFFSET>,
+            GetRebuildStatistics: GetRebuildStatistics::<Identity, OFFSET>,
+            RebuildAsync: RebuildAsync::<Identity, OFFSET>,
+            SetHotReload: SetHotReload::<Identity, OFFSET>,
+            OnReloadFailed: OnReloadFailed::<Identity, OFFSET>,
+            RemoveOnReloadFailed: RemoveOnReloadFailed::<Identity, OFFSET>,
         }
     }
     pub fn matches(iid: &windows_core::GUID) -> bool {
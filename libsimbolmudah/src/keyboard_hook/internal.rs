@@ -8,7 +8,9 @@ use windows::{
         System::LibraryLoader::GetModuleHandleW,
         UI::{
             Input::KeyboardAndMouse::{
-                GetKeyState, KEYBDINPUT, KEYBD_EVENT_FLAGS, KEYEVENTF_EXTENDEDKEY, KEYEVENTF_KEYUP, KEYEVENTF_SCANCODE, VIRTUAL_KEY, VK_CAPITAL, VK_ESCAPE, VK_LSHIFT, VK_RMENU, VK_RSHIFT, VK_SHIFT, VK_U
+                GetKeyState, KEYBDINPUT, KEYBD_EVENT_FLAGS, KEYEVENTF_EXTENDEDKEY, KEYEVENTF_KEYUP,
+                KEYEVENTF_SCANCODE, VIRTUAL_KEY, VK_CAPITAL, VK_ESCAPE, VK_LCONTROL, VK_LSHIFT,
+                VK_RMENU, VK_RSHIFT, VK_SHIFT, VK_U,
             },
             WindowsAndMessaging::{
                 CallNextHookEx, SetWindowsHookExW, HC_ACTION, HHOOK, KBDLLHOOKSTRUCT,
@@ -19,11 +21,13 @@ use windows::{
     },
 };
 
+use super::COLON_KEYS;
 use crate::{
     bindings,
+    keyboard_translator::{active_layout, KeyCombination},
     utils::{
         delegate_storage::DelegateStorage,
-        functions::{fail_message, get_strong_ref},
+        functions::{fail, fail_message, get_strong_ref},
         sender::send_keybdinput,
         single_threaded::{single_threaded, SingleThreaded},
     },
@@ -66,6 +70,9 @@ enum Stage {
     SearchMode = 5,
     /// Unicode mode. If receives a hexadecimal keydown, push the key to the unicode state.
     UnicodeMode = 6,
+    /// Shortcode mode, entered by typing a colon after the compose key.
+    /// Intercept and send keydowns to the shortcode translator until the closing colon.
+    ShortcodeMode = 7,
 }
 
 pub(super) static INTERNAL: SingleThreaded<KeyboardHookInternal> =
@@ -142,6 +149,10 @@ impl KeyboardHookInternal {
                 self.has_shift = is_keydown;
                 return Err(fail_message("do not capture shift key"));
             }
+            VK_LCONTROL if input.wScan & 0x200 != 0 => {
+                // Layouts with AltGr send a fake left control event along with right alt.
+                return Err(fail_message("do not capture AltGr's fake control key"));
+            }
             VK_RMENU => {
                 self.has_altgr = is_keydown;
             }
@@ -173,6 +184,9 @@ impl KeyboardHookInternal {
             Stage::ComposeKeydownFirst => {
                 if !is_keydown && input.wVk == VK_RMENU {
                     self.stage = Stage::ComposeKeyupFirst;
+                    // Queued on the translator's thread, so the next key can arrive before the
+                    // colon keys of a new layout do
+                    get_strong_ref(&self.keyboard_translator)?.CheckLayoutAndUpdate()?;
                 } else {
                     send_keybdinput(self.input_buffer.drain(..).collect())?;
                     self.stage = Stage::Idle;
@@ -184,10 +198,10 @@ impl KeyboardHookInternal {
                     self.stage = Stage::ComposeKeydownSecond;
                 } else if is_keydown && input.wVk == VK_U {
                     self.stage = Stage::UnicodeMode;
-                    get_strong_ref(&self.keyboard_translator)?.CheckLayoutAndUpdate()?;
+                } else if is_keydown && self.is_shortcode_key(input)? {
+                    self.stage = Stage::ShortcodeMode;
                 } else {
                     self.stage = Stage::SequenceMode;
-                    get_strong_ref(&self.keyboard_translator)?.CheckLayoutAndUpdate()?;
                     self.translate_and_forward(input)?;
                 }
                 self.report_state()
@@ -199,7 +213,6 @@ impl KeyboardHookInternal {
                     // TODO: yield control to search engine
                 } else {
                     self.stage = Stage::SequenceMode;
-                    get_strong_ref(&self.keyboard_translator)?.CheckLayoutAndUpdate()?;
                     self.translate_and_forward(input)?;
                 }
                 self.report_state()
@@ -216,6 +229,12 @@ impl KeyboardHookInternal {
                 }
                 Ok(())
             }
+            Stage::ShortcodeMode => {
+                if is_keydown {
+                    self.translate_and_forward(input)?;
+                }
+                Ok(())
+            }
             Stage::SearchMode => Err(fail_message("shouldn't reach here")),
         }
     }
@@ -244,11 +263,25 @@ impl KeyboardHookInternal {
             match self.stage {
                 Stage::SequenceMode => 0,
                 Stage::UnicodeMode => 1,
+                Stage::ShortcodeMode => 2,
                 _ => panic!("Invalid stage"),
             },
         )
     }
 
+    /// Whether the key types a colon on the active layout. No key does while the colon keys
+    /// belong to another layout; the keyboard translator then takes a colon that starts a
+    /// sequence as the start of a shortcode.
+    fn is_shortcode_key(&self, input: KEYBDINPUT) -> Result<bool> {
+        let colon_keys = COLON_KEYS.read().map_err(fail)?;
+        Ok(colon_keys.0 == active_layout().0 as isize
+            && colon_keys.1.contains(&KeyCombination {
+                vkcode: input.wVk.0 as u8,
+                shift: self.has_shift,
+                altgr: self.has_altgr,
+            }))
+    }
+
     pub(super) fn reset_state(&mut self) -> Result<()> {
         self.stage = Stage::Idle;
         self.input_buffer.clear();
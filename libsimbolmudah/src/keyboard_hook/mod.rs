@@ -2,16 +2,21 @@ mod internal;
 
 use crate::{
     bindings,
-    utils::{delegate_storage::event_registration, functions::get_strong_ref},
+    keyboard_translator::KeyCombination,
+    utils::{
+        delegate_storage::event_registration,
+        functions::{fail, get_strong_ref},
+    },
 };
 use internal::{KeyboardHookInternal, INTERNAL};
-use std::{fmt::Debug, usize};
+use std::{fmt::Debug, sync::RwLock, usize};
 use windows::{
     core::{implement, Error, IInspectable, Interface, Result, HSTRING},
     Foundation::TypedEventHandler,
     Win32::{
         Foundation::{E_NOTIMPL, E_POINTER},
         System::WinRT::{IActivationFactory, IActivationFactory_Impl},
+        UI::Input::KeyboardAndMouse::HKL,
     },
 };
 
@@ -42,6 +47,17 @@ impl bindings::IKeyboardHook_Impl for KeyboardHook_Impl {
     event_registration!(OnKeyEvent, TypedEventHandler<bindings::KeyboardHook, HSTRING>);
 }
 
+/// Keys that type a colon, with the layout they were found on. The keyboard translator replaces
+/// them from its own thread, so the hook never waits for either queue to read them.
+static COLON_KEYS: RwLock<(isize, Vec<KeyCombination>)> = RwLock::new((0, Vec::new()));
+
+/// Replaces the keys that start shortcode mode. Called by the keyboard translator after it
+/// analyzes a layout.
+pub(crate) fn set_colon_keys(layout: HKL, keys: Vec<KeyCombination>) -> Result<()> {
+    *COLON_KEYS.write().map_err(fail)? = (layout.0 as isize, keys);
+    Ok(())
+}
+
 #[implement(IActivationFactory, bindings::IKeyboardHookFactory)]
 pub(super) struct KeyboardHookFactory;
 
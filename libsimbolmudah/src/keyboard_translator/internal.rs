@@ -1,17 +1,21 @@
-use std::{collections::HashMap, fmt::Debug, ptr::null_mut};
+use std::{fmt::Debug, ptr::null_mut, time::Duration};
 
 use windows::{
     core::{h, Error, Interface, Result, Weak, HRESULT, HSTRING},
     Foundation::TypedEventHandler,
+    System::{DispatcherQueue, DispatcherQueueTimer},
     Win32::{
         Foundation::{ERROR_NO_UNICODE_TRANSLATION, E_INVALIDARG, E_POINTER},
         UI::Input::KeyboardAndMouse::{ToUnicodeEx, HKL, VK_CONTROL, VK_MENU, VK_SHIFT, VK_SPACE},
     },
 };
 
+use super::layout::{special_key, KeyCombination, KeyOutput, LayoutDescription, TranslatedKey};
 use crate::{
-    bindings,
-    sequence_definition::{SequenceDefinition, SequenceDefinitionError},
+    bindings, keyboard_hook,
+    sequence_definition::{
+        keysym_to_char, SequenceDefinition, SequenceDefinitionError, SequenceMatch,
+    },
     utils::{
         delegate_storage::DelegateStorage,
         functions::get_strong_ref,
@@ -28,21 +32,6 @@ enum VKToUnicodeError {
     NoTranslation,
 }
 
-#[derive(Clone)]
-enum StringVariant {
-    LiveString(String),
-    DeadString(String),
-}
-
-impl Into<String> for StringVariant {
-    fn into(self) -> String {
-        match self {
-            StringVariant::LiveString(s) => s,
-            StringVariant::DeadString(s) => s,
-        }
-    }
-}
-
 #[allow(non_snake_case)]
 pub(super) struct KeyboardTranslatorInternal {
     pub(super) keyboard_layout: HKL,
@@ -51,9 +40,19 @@ pub(super) struct KeyboardTranslatorInternal {
         DelegateStorage<TypedEventHandler<bindings::KeyboardTranslator, HSTRING>>,
     pub(super) OnKeyTranslated:
         DelegateStorage<TypedEventHandler<bindings::KeyboardTranslator, HSTRING>>,
-    possible_altgr: HashMap<String, String>,
-    possible_dead: HashMap<String, u16>,
-    pub(super) state: String,
+    layout: LayoutDescription,
+    pub(super) state: Vec<u32>,
+    /// Whether a colon that the keyboard hook did not recognize opened a shortcode, so that
+    /// the following sequence keys go to the shortcode translator.
+    pub(super) shortcode: bool,
+    /// Hold the result of a sequence that longer sequences start with, until a key that does
+    /// not continue it, Enter or the timeout.
+    pub(super) confirm_ambiguous: bool,
+    /// Zero to wait for a key only.
+    pub(super) ambiguity_timeout: Duration,
+    /// Result of the ambiguous sequence typed so far.
+    pub(super) pending: Option<String>,
+    ambiguity_timer: Option<DispatcherQueueTimer>,
     pub(super) sequence_definition: Weak<bindings::SequenceDefinition>,
     pub(super) parent: Weak<bindings::KeyboardTranslator>,
 }
@@ -68,9 +67,13 @@ impl KeyboardTranslatorInternal {
             OnInvalid: DelegateStorage::new(),
             OnTranslated: DelegateStorage::new(),
             OnKeyTranslated: DelegateStorage::new(),
-            possible_altgr: HashMap::new(),
-            possible_dead: HashMap::new(),
-            state: String::new(),
+            layout: LayoutDescription::default(),
+            state: Vec::new(),
+            shortcode: false,
+            confirm_ambiguous: false,
+            ambiguity_timeout: Duration::ZERO,
+            pending: None,
+            ambiguity_timer: None,
             sequence_definition,
             parent,
         }
@@ -86,9 +89,20 @@ impl KeyboardTranslatorInternal {
         vkcode: u32,
         scancode: u32,
         keystate: &[u8; 256],
-    ) -> Result<String> {
+    ) -> Result<TranslatedKey> {
+        if let Some(key) = special_key(vkcode) {
+            return Ok(key);
+        }
+
         match vk_to_unicode(vkcode, scancode, &keystate, self.keyboard_layout) {
-            Ok(s) => Ok(s.into()),
+            Ok(output) => {
+                let combination = KeyCombination {
+                    vkcode: vkcode as u8,
+                    shift: keystate[VK_SHIFT.0 as usize] & 0x80 != 0,
+                    altgr: keystate[VK_MENU.0 as usize] & 0x80 != 0,
+                };
+                Ok(self.layout.interpret(&combination, output))
+            }
             Err(e) => {
                 self.report_invalid(h!("Invalid VK code"))?;
 
@@ -106,17 +120,52 @@ impl KeyboardTranslatorInternal {
     pub(super) fn forward(
         &mut self,
         destination: u8,
-        value: String,
+        key: impl Into<TranslatedKey>,
     ) -> std::result::Result<String, SequenceDefinitionError> {
+        let key = key.into();
         match destination {
+            0 if self.shortcode => {
+                let result = self.forward(2, key);
+                self.shortcode = result == Err(SequenceDefinitionError::Incomplete);
+                result
+            }
+            // The keyboard hook only knows the colon keys once this layout has been analyzed
+            0 if self.state.is_empty() && key.value == ":" => {
+                self.shortcode = true;
+                Err(SequenceDefinitionError::Incomplete)
+            }
             0 => {
                 // Forward to SequenceTranslator
-                self.state.push_str(&value);
-                match self
-                    .get_seqdef_ref()?
-                    .cast_object_ref::<SequenceDefinition>()?
-                    .translate_sequence(&self.state)
-                {
+                let seqdef = self.get_seqdef_ref()?;
+                let seqdef = seqdef.cast_object_ref::<SequenceDefinition>()?;
+                let pending = self.pending.take();
+
+                match (self.compose(seqdef, &key), pending) {
+                    // A key that does not continue an ambiguous sequence confirms it, then
+                    // counts on its own. Enter only confirms.
+                    (Err(SequenceDefinitionError::ValueNotFound), Some(pending)) => {
+                        if key.value == "\r" {
+                            return Ok(pending);
+                        }
+                        match self.compose(seqdef, &key) {
+                            Ok(s) => Ok(pending + &s),
+                            Err(SequenceDefinitionError::Incomplete) => Ok(pending),
+                            Err(SequenceDefinitionError::ValueNotFound)
+                                if key.text_keysym().and_then(keysym_to_char).is_some() =>
+                            {
+                                Ok(pending + &key.value)
+                            }
+                            Err(SequenceDefinitionError::ValueNotFound) => Ok(pending),
+                            Err(e) => Err(e),
+                        }
+                    }
+                    (result, _) => result,
+                }
+            }
+            1 => {
+                // Forward to UnicodeTranslator
+                self.state.extend(key.text_keysym());
+                match self.parse_as_unicode() {
                     Ok(s) => {
                         self.state.clear();
                         Ok(s)
@@ -130,10 +179,14 @@ impl KeyboardTranslatorInternal {
                     }
                 }
             }
-            1 => {
-                // Forward to UnicodeTranslator
-                self.state.push_str(&value);
-                match self.parse_as_unicode() {
+            2 => {
+                // Forward to ShortcodeTranslator
+                let seqdef = self.get_seqdef_ref()?;
+                let seqdef = seqdef.cast_object_ref::<SequenceDefinition>()?;
+                let keysym = key
+                    .text_keysym()
+                    .ok_or(SequenceDefinitionError::ValueNotFound)?;
+                match self.translate_shortcode(seqdef, keysym) {
                     Ok(s) => {
                         self.state.clear();
                         Ok(s)
@@ -151,6 +204,78 @@ impl KeyboardTranslatorInternal {
         }
     }
 
+    /// Matches the key against the sequence typed so far. The state is kept only while the
+    /// sequence is incomplete.
+    fn compose(
+        &mut self,
+        seqdef: &SequenceDefinition,
+        key: &TranslatedKey,
+    ) -> std::result::Result<String, SequenceDefinitionError> {
+        // Take the first keysym of the key that continues the sequence.
+        let mut result = Err(SequenceDefinitionError::ValueNotFound);
+        for &keysym in &key.keysyms {
+            let sequence = [self.state.as_slice(), &[keysym]].concat();
+            result = match seqdef.match_sequence(&sequence)? {
+                SequenceMatch {
+                    value: None,
+                    continuations: 0,
+                    ..
+                } => continue,
+                SequenceMatch {
+                    value: Some(value),
+                    continuations,
+                    ..
+                } if continuations == 0 || !self.confirm_ambiguous => Ok(value),
+                SequenceMatch { value, .. } => {
+                    self.state = sequence;
+                    self.pending = value;
+                    Err(SequenceDefinitionError::Incomplete)
+                }
+            };
+            break;
+        }
+
+        if result != Err(SequenceDefinitionError::Incomplete) {
+            self.state.clear();
+            self.pending = None;
+        }
+        result
+    }
+
+    /// Commits the pending result once the timeout passes without another key.
+    pub(super) fn schedule_pending(&mut self) -> Result<()> {
+        if let Some(timer) = &self.ambiguity_timer {
+            timer.Stop()?;
+        }
+        if self.pending.is_none() || self.ambiguity_timeout.is_zero() {
+            return Ok(());
+        }
+
+        let timer = match &self.ambiguity_timer {
+            Some(timer) => timer.clone(),
+            None => {
+                let timer = DispatcherQueue::GetForCurrentThread()?.CreateTimer()?;
+                timer.SetIsRepeating(false)?;
+                timer.Tick(&TypedEventHandler::new(|_, _| {
+                    INTERNAL.with_borrow_mut(|internal| internal.commit_pending())
+                }))?;
+                self.ambiguity_timer.insert(timer).clone()
+            }
+        };
+        timer.SetInterval(self.ambiguity_timeout.into())?;
+        timer.Start()
+    }
+
+    fn commit_pending(&mut self) -> Result<()> {
+        match self.pending.take() {
+            Some(s) => {
+                self.state.clear();
+                self.report(Ok(s))
+            }
+            None => Ok(()),
+        }
+    }
+
     pub(super) fn report(
         &mut self,
         result: std::result::Result<String, SequenceDefinitionError>,
@@ -179,7 +304,7 @@ impl KeyboardTranslatorInternal {
     }
 
     pub(super) fn analyze_layout(&mut self) -> Result<()> {
-        let mut no_altgr = vec![String::new(); 512];
+        let mut entries = Vec::new();
         let mut keystate = [0; 256];
 
         for i in 0..0x400 {
@@ -201,23 +326,26 @@ impl KeyboardTranslatorInternal {
                 keystate[VK_MENU.0 as usize] = 0;
             }
 
-            if let Ok(s) = vk_to_unicode(vk_code, 0, &keystate, self.keyboard_layout) {
-                if has_altgr {
-                    self.possible_altgr
-                        .insert(s.clone().into(), no_altgr[i as usize - 0x200].clone());
-                } else {
-                    no_altgr[i as usize] = s.clone().into();
-                }
-
-                if let StringVariant::DeadString(s) = s {
-                    self.possible_dead.insert(s, i as u16);
-                }
+            if let Ok(output) = vk_to_unicode(vk_code, 0, &keystate, self.keyboard_layout) {
+                let combination = KeyCombination {
+                    vkcode: vk_code as u8,
+                    shift: has_shift,
+                    altgr: has_altgr,
+                };
+                entries.push((combination, output));
             }
 
             to_unicode_ex_clear_state();
         }
 
-        Ok(())
+        self.layout = LayoutDescription::from_entries(entries);
+        self.publish_colon_keys()
+    }
+
+    /// Gives the keyboard hook the keys that start shortcode mode on the active layout, so that
+    /// it does not have to ask for them while a key is held.
+    fn publish_colon_keys(&self) -> Result<()> {
+        keyboard_hook::set_colon_keys(self.keyboard_layout, self.layout.colon_keys().to_vec())
     }
 
     fn get_seqdef_ref(&self) -> Result<bindings::SequenceDefinition> {
@@ -226,17 +354,63 @@ impl KeyboardTranslatorInternal {
             .ok_or_else(|| Error::new(E_POINTER, "Weak pointer died"))
     }
 
+    /// Reads the hexadecimal code point typed so far once a whitespace key, Enter or Tab ends it.
     fn parse_as_unicode(&self) -> std::result::Result<String, SequenceDefinitionError> {
-        if self.state.ends_with(char::is_whitespace) {
-            Ok(char::from_u32(
-                u32::from_str_radix(&self.state.trim_end(), 16)
-                    .map_err(|_| SequenceDefinitionError::ValueNotFound)?,
-            )
-            .ok_or(SequenceDefinitionError::ValueNotFound)?
-            .to_string())
-        } else {
-            Err(SequenceDefinitionError::Incomplete)
+        let Some((&last, digits)) = self.state.split_last() else {
+            return Err(SequenceDefinitionError::Incomplete);
+        };
+        let digits = digits
+            .iter()
+            .map(|&k| keysym_to_char(k).ok_or(SequenceDefinitionError::ValueNotFound))
+            .collect::<std::result::Result<String, _>>()?;
+
+        match keysym_to_char(last) {
+            Some(c) if c.is_whitespace() => {}
+            // BackSpace, Escape and Delete
+            Some(c) if c.is_control() => return Err(SequenceDefinitionError::ValueNotFound),
+            Some(_) => return Err(SequenceDefinitionError::Incomplete),
+            None => return Err(SequenceDefinitionError::ValueNotFound),
         }
+
+        Ok(char::from_u32(
+            u32::from_str_radix(&digits, 16).map_err(|_| SequenceDefinitionError::ValueNotFound)?,
+        )
+        .ok_or(SequenceDefinitionError::ValueNotFound)?
+        .to_string())
+    }
+
+    /// Collects a shortcode until the closing colon. A complete Compose sequence that begins
+    /// with a colon, such as `: -` for `÷`, wins over the shortcodes starting with the same text.
+    /// Once no shortcode starts with the typed text, the keys continue those Compose sequences.
+    fn translate_shortcode(
+        &mut self,
+        seqdef: &SequenceDefinition,
+        keysym: u32,
+    ) -> std::result::Result<String, SequenceDefinitionError> {
+        let text = self
+            .state
+            .iter()
+            .chain([&keysym])
+            .map(|&k| keysym_to_char(k))
+            .collect::<Option<String>>();
+
+        if let Some(code) = text.as_deref().and_then(|t| t.strip_suffix(':')) {
+            if let Ok(s) = seqdef.translate_shortcode(code) {
+                return Ok(s);
+            }
+        }
+
+        self.state.push(keysym);
+        let sequence = [&[COLON_KEYSYM], self.state.as_slice()].concat();
+        if let Some(value) = seqdef.match_sequence(&sequence)?.value {
+            return Ok(value);
+        }
+        if let Some(text) = text {
+            if seqdef.is_shortcode_prefix(&text)? {
+                return Err(SequenceDefinitionError::Incomplete);
+            }
+        }
+        seqdef.translate_sequence(&sequence)
     }
 }
 
@@ -246,9 +420,9 @@ impl Debug for KeyboardTranslatorInternal {
             .field("keyboard_layout", &self.keyboard_layout)
             .field("report_invalid", &self.OnInvalid)
             .field("report_translated", &self.OnTranslated)
-            .field("possible_altgr", &self.possible_altgr)
-            .field("possible_dead", &self.possible_dead)
+            .field("layout", &self.layout)
             .field("state", &self.state)
+            .field("pending", &self.pending)
             .finish()
     }
 }
@@ -258,13 +432,13 @@ fn vk_to_unicode(
     scancode: u32,
     keystate: &[u8; 256],
     keyboard_layout: HKL,
-) -> std::result::Result<StringVariant, VKToUnicodeError> {
+) -> std::result::Result<KeyOutput, VKToUnicodeError> {
     let mut buffer = [0; 8];
     let status =
         unsafe { ToUnicodeEx(vkcode, scancode, keystate, &mut buffer, 4, keyboard_layout) };
 
     if status > 0 {
-        Ok(StringVariant::LiveString(
+        Ok(KeyOutput::Live(
             String::from_utf16(&buffer[..status as usize])
                 .map_err(|_| VKToUnicodeError::InvalidReturn)?,
         ))
@@ -273,7 +447,7 @@ fn vk_to_unicode(
             unsafe { ToUnicodeEx(vkcode, scancode, keystate, &mut buffer, 4, keyboard_layout) };
 
         if status > 0 {
-            Ok(StringVariant::DeadString(
+            Ok(KeyOutput::Dead(
                 String::from_utf16(&buffer[..status as usize])
                     .map_err(|_| VKToUnicodeError::InvalidReturn)?,
             ))
@@ -285,6 +459,9 @@ fn vk_to_unicode(
     }
 }
 
+/// Keysym of `:`, which opens and closes a shortcode.
+const COLON_KEYSYM: u32 = 0x3a;
+
 const EMPTY_KEYSTATE: [u8; 256] = [0; 256];
 
 fn to_unicode_ex_clear_state() {
@@ -0,0 +1,445 @@
+use std::collections::HashMap;
+
+use crate::sequence_definition::{char_to_keysym, keysym_to_char};
+
+/// Output of a single key combination on a keyboard layout, as reported by ToUnicodeEx.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(super) enum KeyOutput {
+    /// The key produces the string immediately.
+    Live(String),
+    /// The key is a dead key. The string is its spacing form, e.g. "´" for an acute dead key.
+    Dead(String),
+}
+
+/// A virtual key code together with the modifiers that matter for character output.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub(crate) struct KeyCombination {
+    pub(crate) vkcode: u8,
+    pub(crate) shift: bool,
+    pub(crate) altgr: bool,
+}
+
+/// A dead key known to X11, with its dead_* keysym and the key that Multi_key rules use for the
+/// same accent.
+#[derive(Debug, PartialEq, Eq)]
+pub(super) struct DeadKey {
+    pub(super) spacing: char,
+    pub(super) keysym: u32,
+    pub(super) multi_key_equivalent: Option<char>,
+}
+
+const DEAD_KEYS: [DeadKey; 17] = [
+    DeadKey {
+        spacing: '`',
+        keysym: 0xfe50,
+        multi_key_equivalent: None,
+    },
+    DeadKey {
+        spacing: '´',
+        keysym: 0xfe51,
+        multi_key_equivalent: Some('\''),
+    },
+    DeadKey {
+        spacing: '\'',
+        keysym: 0xfe51,
+        multi_key_equivalent: None,
+    },
+    DeadKey {
+        spacing: '^',
+        keysym: 0xfe52,
+        multi_key_equivalent: None,
+    },
+    DeadKey {
+        spacing: 'ˆ',
+        keysym: 0xfe52,
+        multi_key_equivalent: Some('^'),
+    },
+    DeadKey {
+        spacing: '~',
+        keysym: 0xfe53,
+        multi_key_equivalent: None,
+    },
+    DeadKey {
+        spacing: '˜',
+        keysym: 0xfe53,
+        multi_key_equivalent: Some('~'),
+    },
+    DeadKey {
+        spacing: '¯',
+        keysym: 0xfe54,
+        multi_key_equivalent: Some('_'),
+    },
+    DeadKey {
+        spacing: '˘',
+        keysym: 0xfe55,
+        multi_key_equivalent: Some('U'),
+    },
+    DeadKey {
+        spacing: '˙',
+        keysym: 0xfe56,
+        multi_key_equivalent: Some('.'),
+    },
+    DeadKey {
+        spacing: '¨',
+        keysym: 0xfe57,
+        multi_key_equivalent: Some('"'),
+    },
+    DeadKey {
+        spacing: '"',
+        keysym: 0xfe57,
+        multi_key_equivalent: None,
+    },
+    DeadKey {
+        spacing: '°',
+        keysym: 0xfe58,
+        multi_key_equivalent: Some('o'),
+    },
+    DeadKey {
+        spacing: '˝',
+        keysym: 0xfe59,
+        multi_key_equivalent: Some('='),
+    },
+    DeadKey {
+        spacing: 'ˇ',
+        keysym: 0xfe5a,
+        multi_key_equivalent: Some('c'),
+    },
+    DeadKey {
+        spacing: '¸',
+        keysym: 0xfe5b,
+        multi_key_equivalent: Some(','),
+    },
+    DeadKey {
+        spacing: '˛',
+        keysym: 0xfe5c,
+        multi_key_equivalent: Some(';'),
+    },
+];
+
+pub(super) fn find_dead_key(spacing: &str) -> Option<&'static DeadKey> {
+    let mut chars = spacing.chars();
+    let c = chars.next()?;
+    // ToUnicodeEx may return the spacing character twice when the dead key is pressed again.
+    if chars.any(|other| other != c) {
+        return None;
+    }
+    DEAD_KEYS.iter().find(|d| d.spacing == c)
+}
+
+/// A key event translated for the sequence engine.
+/// `value` is what the user sees. `keysyms` are tried in order until one continues the current
+/// sequence, e.g. the dead key itself before its spacing form, or an AltGr key before its base key.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub(super) struct TranslatedKey {
+    pub(super) value: String,
+    pub(super) keysyms: Vec<u32>,
+}
+
+impl TranslatedKey {
+    /// The keysym of the character the key types, for the modes that read text. Keypad keys
+    /// list their keypad keysym first, which stands for no character.
+    pub(super) fn text_keysym(&self) -> Option<u32> {
+        self.keysyms
+            .iter()
+            .copied()
+            .find(|&keysym| keysym_to_char(keysym).is_some())
+            .or_else(|| self.keysyms.first().copied())
+    }
+}
+
+impl From<String> for TranslatedKey {
+    fn from(value: String) -> Self {
+        // Only single characters have a keysym. Strings from ligature keys match nothing.
+        let mut chars = value.chars();
+        let keysyms = match (chars.next(), chars.next()) {
+            (Some(c), None) => vec![char_to_keysym(c)],
+            _ => Vec::new(),
+        };
+        Self { value, keysyms }
+    }
+}
+
+impl From<&str> for TranslatedKey {
+    fn from(value: &str) -> Self {
+        value.to_string().into()
+    }
+}
+
+/// Keys that produce no character but have their own keysym.
+const SPECIAL_KEYS: [(u8, u32, &str); 24] = [
+    (0x08, 0xff08, "BackSpace"),
+    (0x09, 0xff09, "Tab"),
+    (0x21, 0xff55, "Prior"),
+    (0x22, 0xff56, "Next"),
+    (0x23, 0xff57, "End"),
+    (0x24, 0xff50, "Home"),
+    (0x25, 0xff51, "Left"),
+    (0x26, 0xff52, "Up"),
+    (0x27, 0xff53, "Right"),
+    (0x28, 0xff54, "Down"),
+    (0x2D, 0xff63, "Insert"),
+    (0x2E, 0xffff, "Delete"),
+    (0x70, 0xffbe, "F1"),
+    (0x71, 0xffbf, "F2"),
+    (0x72, 0xffc0, "F3"),
+    (0x73, 0xffc1, "F4"),
+    (0x74, 0xffc2, "F5"),
+    (0x75, 0xffc3, "F6"),
+    (0x76, 0xffc4, "F7"),
+    (0x77, 0xffc5, "F8"),
+    (0x78, 0xffc6, "F9"),
+    (0x79, 0xffc7, "F10"),
+    (0x7A, 0xffc8, "F11"),
+    (0x7B, 0xffc9, "F12"),
+];
+
+/// Numeric keypad keys, VK_NUMPAD0 to VK_DIVIDE. They type a character but have their own keysym.
+const KEYPAD_KEYS: [(u8, u32); 16] = [
+    (0x60, 0xffb0),
+    (0x61, 0xffb1),
+    (0x62, 0xffb2),
+    (0x63, 0xffb3),
+    (0x64, 0xffb4),
+    (0x65, 0xffb5),
+    (0x66, 0xffb6),
+    (0x67, 0xffb7),
+    (0x68, 0xffb8),
+    (0x69, 0xffb9),
+    (0x6A, 0xffaa),
+    (0x6B, 0xffab),
+    (0x6C, 0xffac),
+    (0x6D, 0xffad),
+    (0x6E, 0xffae),
+    (0x6F, 0xffaf),
+];
+
+/// Translates a key without character output, shown by its keysym name.
+pub(super) fn special_key(vkcode: u32) -> Option<TranslatedKey> {
+    SPECIAL_KEYS
+        .iter()
+        .find(|(vk, _, _)| u32::from(*vk) == vkcode)
+        .map(|(_, keysym, name)| TranslatedKey {
+            value: format!("<{}>", name),
+            keysyms: vec![*keysym],
+        })
+}
+
+/// Description of a keyboard layout, captured once per layout change.
+/// It does not call into Windows, so it can be built from a captured table as well.
+#[derive(Debug, Default)]
+pub(super) struct LayoutDescription {
+    altgr_base: HashMap<String, String>,
+    /// Keys that type a colon, which starts a shortcode after the compose key.
+    colon_keys: Vec<KeyCombination>,
+}
+
+impl LayoutDescription {
+    pub(super) fn from_entries(
+        entries: impl IntoIterator<Item = (KeyCombination, KeyOutput)>,
+    ) -> Self {
+        let keys: HashMap<_, _> = entries.into_iter().collect();
+        let mut altgr_base = HashMap::new();
+        let mut colon_keys = Vec::new();
+
+        // Visit the keys in a fixed order so that the lowest virtual key wins when AltGr produces
+        // the same string on two keys.
+        let mut combinations: Vec<_> = keys.keys().collect();
+        combinations.sort_by_key(|c| (c.vkcode, c.shift, c.altgr));
+
+        for combination in combinations {
+            let output = &keys[combination];
+            if *output == KeyOutput::Live(":".into()) {
+                colon_keys.push(*combination);
+            }
+            if combination.altgr {
+                let base = KeyCombination {
+                    altgr: false,
+                    ..*combination
+                };
+                if let (Some(KeyOutput::Live(base)), KeyOutput::Live(s)) = (keys.get(&base), output)
+                {
+                    if s != base {
+                        altgr_base.entry(s.clone()).or_insert_with(|| base.clone());
+                    }
+                }
+            }
+        }
+
+        Self {
+            altgr_base,
+            colon_keys,
+        }
+    }
+
+    /// Returns the string produced by the same key without AltGr.
+    pub(super) fn altgr_base(&self, value: &str) -> Option<&str> {
+        self.altgr_base.get(value).map(|s| s.as_str())
+    }
+
+    /// The keys that type a colon on this layout.
+    pub(super) fn colon_keys(&self) -> &[KeyCombination] {
+        &self.colon_keys
+    }
+
+    /// Interprets a key event for the sequence engine.
+    /// Dead keys are reported with their spacing form first, then with the key that Multi_key
+    /// rules use for the same accent. AltGr combinations fall back to their base key, and keypad
+    /// keys try their keypad keysym before their character.
+    pub(super) fn interpret(
+        &self,
+        combination: &KeyCombination,
+        output: KeyOutput,
+    ) -> TranslatedKey {
+        match output {
+            KeyOutput::Dead(s) => match find_dead_key(&s) {
+                Some(dead_key) => {
+                    let mut keysyms = vec![dead_key.keysym, char_to_keysym(dead_key.spacing)];
+                    keysyms.extend(dead_key.multi_key_equivalent.map(char_to_keysym));
+                    TranslatedKey {
+                        value: dead_key.spacing.to_string(),
+                        keysyms,
+                    }
+                }
+                None => s.into(),
+            },
+            KeyOutput::Live(s) => {
+                let mut key = TranslatedKey::from(s);
+                if let Some((_, keysym)) =
+                    KEYPAD_KEYS.iter().find(|(vk, _)| *vk == combination.vkcode)
+                {
+                    key.keysyms.insert(0, *keysym);
+                }
+                if combination.altgr {
+                    if let Some(base) = self.altgr_base(&key.value) {
+                        key.keysyms.extend(TranslatedKey::from(base).keysyms);
+                    }
+                }
+                key
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VK_2: u8 = 0x32;
+    const VK_E: u8 = 0x45;
+    const VK_Q: u8 = 0x51;
+    const VK_OEM_4: u8 = 0xDB;
+    const VK_OEM_6: u8 = 0xDD;
+    const VK_OEM_PERIOD: u8 = 0xBE;
+
+    fn key(vkcode: u8, shift: bool, altgr: bool) -> KeyCombination {
+        KeyCombination {
+            vkcode,
+            shift,
+            altgr,
+        }
+    }
+
+    /// A small excerpt of the German (T1) layout.
+    fn german_layout() -> LayoutDescription {
+        LayoutDescription::from_entries([
+            (key(VK_Q, false, false), KeyOutput::Live("q".into())),
+            (key(VK_Q, true, false), KeyOutput::Live("Q".into())),
+            (key(VK_Q, false, true), KeyOutput::Live("@".into())),
+            (key(VK_E, false, false), KeyOutput::Live("e".into())),
+            (key(VK_E, false, true), KeyOutput::Live("€".into())),
+            (key(VK_2, false, false), KeyOutput::Live("2".into())),
+            (key(VK_2, false, true), KeyOutput::Live("²".into())),
+            (key(VK_OEM_6, false, false), KeyOutput::Dead("´".into())),
+            (key(VK_OEM_6, true, false), KeyOutput::Dead("`".into())),
+            (key(VK_OEM_4, false, false), KeyOutput::Live("ß".into())),
+            (
+                key(VK_OEM_PERIOD, false, false),
+                KeyOutput::Live(".".into()),
+            ),
+            (key(VK_OEM_PERIOD, true, false), KeyOutput::Live(":".into())),
+        ])
+    }
+
+    #[test]
+    fn test_altgr_maps_to_base_key() {
+        let layout = german_layout();
+        assert_eq!(layout.altgr_base("@"), Some("q"));
+        assert_eq!(layout.altgr_base("€"), Some("e"));
+        assert_eq!(layout.altgr_base("²"), Some("2"));
+        assert_eq!(layout.altgr_base("q"), None);
+    }
+
+    #[test]
+    fn test_colon_key() {
+        let layout = german_layout();
+        assert_eq!(layout.colon_keys(), [key(VK_OEM_PERIOD, true, false)]);
+    }
+
+    #[test]
+    fn test_interpret_altgr() {
+        let layout = german_layout();
+        let combination = key(VK_Q, false, true);
+        let translated = layout.interpret(&combination, KeyOutput::Live("@".into()));
+
+        assert_eq!(translated.value, "@");
+        assert_eq!(translated.keysyms, [0x40, 0x71]);
+    }
+
+    #[test]
+    fn test_interpret_plain_key_has_no_alternative() {
+        let layout = german_layout();
+        let combination = key(VK_OEM_4, false, false);
+        let translated = layout.interpret(&combination, KeyOutput::Live("ß".into()));
+
+        assert_eq!(translated, TranslatedKey::from("ß"));
+    }
+
+    #[test]
+    fn test_interpret_dead_key() {
+        let layout = german_layout();
+        let combination = key(VK_OEM_6, false, false);
+        // Pressing the dead key twice returns its spacing form twice.
+        let translated = layout.interpret(&combination, KeyOutput::Dead("´´".into()));
+
+        assert_eq!(translated.value, "´");
+        // dead_acute, then acute, then apostrophe
+        assert_eq!(translated.keysyms, [0xfe51, 0xb4, 0x27]);
+    }
+
+    #[test]
+    fn test_find_dead_key() {
+        assert_eq!(find_dead_key("¨").unwrap().keysym, 0xfe57);
+        assert_eq!(find_dead_key("ˇ").unwrap().multi_key_equivalent, Some('c'));
+        assert!(find_dead_key("a").is_none());
+        assert!(find_dead_key("´`").is_none());
+    }
+
+    #[test]
+    fn test_translated_key_from_string() {
+        assert_eq!(TranslatedKey::from("→").keysyms, [0x1002192]);
+        assert!(TranslatedKey::from("ab").keysyms.is_empty());
+    }
+
+    #[test]
+    fn test_special_key() {
+        let left = special_key(0x25).unwrap();
+        assert_eq!(left.value, "<Left>");
+        assert_eq!(left.keysyms, [0xff51]);
+        assert!(special_key(0x41).is_none());
+        assert!(special_key(0x6F).is_none());
+    }
+
+    #[test]
+    fn test_interpret_keypad() {
+        let layout = german_layout();
+        let divide = layout.interpret(&key(0x6F, false, false), KeyOutput::Live("/".into()));
+        assert_eq!(divide.value, "/");
+        // KP_Divide, then slash
+        assert_eq!(divide.keysyms, [0xffaf, 0x2f]);
+
+        let nine = layout.interpret(&key(0x69, false, false), KeyOutput::Live("9".into()));
+        assert_eq!(nine.keysyms, [0xffb9, 0x39]);
+        assert_eq!(nine.text_keysym(), Some(0x39));
+        assert_eq!(special_key(0x25).unwrap().text_keysym(), Some(0xff51));
+    }
+}
@@ -1,7 +1,11 @@
 mod internal;
+mod layout;
+
+use std::time::Duration;
 
 use crate::{bindings, utils::delegate_storage::event_registration};
 use internal::{KeyboardTranslatorInternal, INTERNAL};
+pub(crate) use layout::KeyCombination;
 use windows::{
     core::{implement, Error, IInspectable, Interface, Result, HSTRING},
     Foundation::TypedEventHandler,
@@ -10,7 +14,7 @@ use windows::{
         System::WinRT::{IActivationFactory, IActivationFactory_Impl},
         UI::{
             Input::KeyboardAndMouse::{
-                GetKeyboardLayout, VK_CAPITAL, VK_CONTROL, VK_MENU, VK_SHIFT,
+                GetKeyboardLayout, HKL, VK_CAPITAL, VK_CONTROL, VK_MENU, VK_SHIFT,
             },
             WindowsAndMessaging::{GetForegroundWindow, GetWindowThreadProcessId},
         },
@@ -33,9 +37,10 @@ impl bindings::IKeyboardTranslator_Impl for KeyboardTranslator_Impl {
     ) -> Result<()> {
         INTERNAL.with_borrow_mut(move |internal| {
             let keystate = calculate_bg_keystate(hascapslock, hasshift, hasaltgr);
-            if let Ok(value) = internal.translate(vkcode, scancode, &keystate) {
-                internal.report_key(&value)?;
-                let result = internal.forward(destination, value);
+            if let Ok(key) = internal.translate(vkcode, scancode, &keystate) {
+                internal.report_key(&key.value)?;
+                let result = internal.forward(destination, key);
+                internal.schedule_pending()?;
                 internal.report(result)
             } else {
                 // Even though the translation failed, the state might be stored inside ToUnicodeEx's internal buffer.
@@ -47,10 +52,7 @@ impl bindings::IKeyboardTranslator_Impl for KeyboardTranslator_Impl {
 
     fn CheckLayoutAndUpdate(&self) -> Result<()> {
         INTERNAL.with_borrow_mut(|internal| {
-            let foreground_window = unsafe { GetForegroundWindow() };
-            let tid = unsafe { GetWindowThreadProcessId(foreground_window, None) };
-            let active_layout = unsafe { GetKeyboardLayout(tid) };
-
+            let active_layout = active_layout();
             if internal.keyboard_layout == active_layout {
                 return Ok(());
             }
@@ -60,11 +62,26 @@ impl bindings::IKeyboardTranslator_Impl for KeyboardTranslator_Impl {
         })
     }
 
+    fn SetAmbiguousConfirmation(&self, enabled: bool, timeoutmilliseconds: u32) -> Result<()> {
+        INTERNAL.with_borrow_mut(move |internal| {
+            internal.confirm_ambiguous = enabled;
+            internal.ambiguity_timeout = Duration::from_millis(timeoutmilliseconds.into());
+            Ok(())
+        })
+    }
+
     event_registration!(OnInvalid, TypedEventHandler<bindings::KeyboardTranslator, HSTRING>);
     event_registration!(OnTranslated, TypedEventHandler<bindings::KeyboardTranslator, HSTRING>);
     event_registration!(OnKeyTranslated, TypedEventHandler<bindings::KeyboardTranslator, HSTRING>);
 }
 
+/// The keyboard layout of the foreground window, which receives the translated text.
+pub(crate) fn active_layout() -> HKL {
+    let foreground_window = unsafe { GetForegroundWindow() };
+    let tid = unsafe { GetWindowThreadProcessId(foreground_window, None) };
+    unsafe { GetKeyboardLayout(tid) }
+}
+
 const fn calculate_bg_keystate(has_capslock: bool, has_shift: bool, has_altgr: bool) -> [u8; 256] {
     let mut keystate = [0; 256];
     if has_capslock {
@@ -112,8 +129,11 @@ impl bindings::IKeyboardTranslatorFactory_Impl for KeyboardTranslatorFactory_Imp
 #[cfg(test)]
 mod tests {
     use bindings::IKeyboardTranslatorFactory_Impl;
+    use layout::TranslatedKey;
 
-    use crate::sequence_definition::{SequenceDefinitionError, SequenceDefinitionFactory};
+    use crate::sequence_definition::{
+        SequenceDefinition, SequenceDefinitionError, SequenceDefinitionFactory,
+    };
 
     use super::*;
     use windows_core::{ComObjectInner, Result};
@@ -162,13 +182,24 @@ mod tests {
             .into_object()
             .CreateInstance(Some(&seqdef))?;
 
-        // Assuming "omg" is an invalid sequence for this test
+        // Assuming "om" is an invalid sequence for this test
         INTERNAL.with_borrow_mut(|internal| {
             assert_eq!(
-                internal.forward(0, "omg".to_string()),
+                internal.forward(0, "o".to_string()),
+                Err(SequenceDefinitionError::Incomplete)
+            );
+            assert_eq!(
+                internal.forward(0, "m".to_string()),
                 Err(SequenceDefinitionError::ValueNotFound)
             );
             assert!(internal.state.is_empty());
+
+            // The next key starts a new sequence
+            assert_eq!(
+                internal.forward(0, "g".to_string()),
+                Err(SequenceDefinitionError::Incomplete)
+            );
+            assert_eq!(internal.state, [0x67]);
             Ok(())
         })?;
 
@@ -203,4 +234,293 @@ mod tests {
 
         INTERNAL.destroy()?.get()
     }
+
+    #[test]
+    fn test_alternative_continues_sequence() -> Result<()> {
+        // Create and build the SequenceDefinition
+        let seqdef = SequenceDefinitionFactory
+            .into_object()
+            .ActivateInstance()?
+            .cast::<bindings::SequenceDefinition>()?;
+
+        seqdef.Rebuild(&KEYSYMDEF.into(), &COMPOSEDEF.into(), &ANNOTATIONS.into())?;
+
+        // Create a new instance of KeyboardTranslator
+        let _instance = KeyboardTranslatorFactory
+            .into_object()
+            .CreateInstance(Some(&seqdef))?;
+
+        // An AltGr key that does not continue the sequence falls back to its base key
+        INTERNAL.with_borrow_mut(|internal| {
+            let altgr_key = TranslatedKey {
+                value: "¬".to_string(),
+                keysyms: vec![0xac, 0x2f],
+            };
+            assert_eq!(
+                internal.forward(0, altgr_key),
+                Err(SequenceDefinitionError::Incomplete)
+            );
+            assert_eq!(internal.state, [0x2f]);
+            assert_eq!(internal.forward(0, "=".to_string()), Ok("≠".to_string()));
+            Ok(())
+        })?;
+
+        INTERNAL.destroy()?.get()
+    }
+
+    #[test]
+    fn test_unicode() -> Result<()> {
+        // Unicode mode needs no sequences
+        let seqdef = SequenceDefinitionFactory
+            .into_object()
+            .ActivateInstance()?
+            .cast::<bindings::SequenceDefinition>()?;
+
+        // Create a new instance of KeyboardTranslator
+        let _instance = KeyboardTranslatorFactory
+            .into_object()
+            .CreateInstance(Some(&seqdef))?;
+
+        INTERNAL.with_borrow_mut(|internal| {
+            for digit in ["2", "1", "9", "2"] {
+                assert_eq!(
+                    internal.forward(1, digit.to_string()),
+                    Err(SequenceDefinitionError::Incomplete)
+                );
+            }
+            // Enter types a carriage return
+            assert_eq!(internal.forward(1, "\r".to_string()), Ok("→".to_string()));
+            assert!(internal.state.is_empty());
+
+            for digit in ["a", "9"] {
+                let _ = internal.forward(1, digit.to_string());
+            }
+            let tab = layout::special_key(0x09).expect("Tab is a special key");
+            assert_eq!(internal.forward(1, tab), Ok("©".to_string()));
+
+            for digit in ["a", "9"] {
+                let _ = internal.forward(1, digit.to_string());
+            }
+            assert_eq!(internal.forward(1, " ".to_string()), Ok("©".to_string()));
+
+            // Keypad digits type their digit
+            let layout = layout::LayoutDescription::default();
+            let keypad = |vkcode, digit: &str| {
+                let combination = KeyCombination {
+                    vkcode,
+                    ..Default::default()
+                };
+                layout.interpret(&combination, layout::KeyOutput::Live(digit.into()))
+            };
+            for key in [
+                keypad(0x60, "0"),
+                keypad(0x60, "0"),
+                "e".into(),
+                keypad(0x69, "9"),
+            ] {
+                assert_eq!(
+                    internal.forward(1, key),
+                    Err(SequenceDefinitionError::Incomplete)
+                );
+            }
+            assert_eq!(internal.forward(1, " ".to_string()), Ok("é".to_string()));
+
+            // Other keys without a character end nothing
+            let _ = internal.forward(1, "a".to_string());
+            let left = layout::special_key(0x25).expect("Left is a special key");
+            assert_eq!(
+                internal.forward(1, left),
+                Err(SequenceDefinitionError::ValueNotFound)
+            );
+            assert!(internal.state.is_empty());
+            let _ = internal.forward(1, "a".to_string());
+            let backspace = layout::special_key(0x08).expect("BackSpace is a special key");
+            assert_eq!(
+                internal.forward(1, backspace),
+                Err(SequenceDefinitionError::ValueNotFound)
+            );
+            Ok(())
+        })?;
+
+        INTERNAL.destroy()?.get()
+    }
+
+    #[test]
+    fn test_shortcode() -> Result<()> {
+        // Create and build the SequenceDefinition
+        let seqdef = SequenceDefinitionFactory
+            .into_object()
+            .ActivateInstance()?
+            .cast::<bindings::SequenceDefinition>()?;
+
+        seqdef.Rebuild(&KEYSYMDEF.into(), &COMPOSEDEF.into(), &ANNOTATIONS.into())?;
+
+        // Create a new instance of KeyboardTranslator
+        let _instance = KeyboardTranslatorFactory
+            .into_object()
+            .CreateInstance(Some(&seqdef))?;
+
+        // The bundled data has both `: -` and `:-1:`
+        let inner = seqdef.cast_object_ref::<SequenceDefinition>()?;
+        assert_eq!(inner.translate_sequence(&[0x3a, 0x2d]), Ok("÷".to_string()));
+        assert_eq!(inner.translate_shortcode("-1"), Ok("👎".to_string()));
+
+        INTERNAL.with_borrow_mut(|internal| {
+            assert_eq!(
+                internal.forward(2, "+".to_string()),
+                Err(SequenceDefinitionError::Incomplete)
+            );
+            assert_eq!(
+                internal.forward(2, "1".to_string()),
+                Err(SequenceDefinitionError::Incomplete)
+            );
+            assert_eq!(internal.forward(2, ":".to_string()), Ok("👍".to_string()));
+            assert!(internal.state.is_empty());
+
+            // `:-1:` is a shortcode, but the Compose sequence `: -` is complete first
+            assert_eq!(internal.forward(2, "-".to_string()), Ok("÷".to_string()));
+            assert!(internal.state.is_empty());
+
+            // So is any other Compose sequence of the colon
+            assert_eq!(internal.forward(2, ")".to_string()), Ok("☺".to_string()));
+            assert!(internal.state.is_empty());
+
+            // The other shortcodes of the same emoji still work
+            for key in "thumbsdown".chars() {
+                assert_eq!(
+                    internal.forward(2, key.to_string()),
+                    Err(SequenceDefinitionError::Incomplete)
+                );
+            }
+            assert_eq!(internal.forward(2, ":".to_string()), Ok("👎".to_string()));
+            Ok(())
+        })?;
+
+        INTERNAL.destroy()?.get()
+    }
+
+    #[test]
+    fn test_shortcode_from_sequence_mode() -> Result<()> {
+        // Create and build the SequenceDefinition
+        let seqdef = SequenceDefinitionFactory
+            .into_object()
+            .ActivateInstance()?
+            .cast::<bindings::SequenceDefinition>()?;
+
+        seqdef.Rebuild(&KEYSYMDEF.into(), &COMPOSEDEF.into(), &ANNOTATIONS.into())?;
+
+        // Create a new instance of KeyboardTranslator
+        let _instance = KeyboardTranslatorFactory
+            .into_object()
+            .CreateInstance(Some(&seqdef))?;
+
+        // The keyboard hook sends the colon as a sequence key while its colon keys are stale
+        INTERNAL.with_borrow_mut(|internal| {
+            for key in [":", "+", "1"] {
+                assert_eq!(
+                    internal.forward(0, key.to_string()),
+                    Err(SequenceDefinitionError::Incomplete)
+                );
+            }
+            assert_eq!(internal.forward(0, ":".to_string()), Ok("👍".to_string()));
+            assert!(!internal.shortcode);
+            assert!(internal.state.is_empty());
+
+            assert_eq!(
+                internal.forward(0, ":".to_string()),
+                Err(SequenceDefinitionError::Incomplete)
+            );
+            assert_eq!(internal.forward(0, "-".to_string()), Ok("÷".to_string()));
+            assert!(!internal.shortcode);
+            Ok(())
+        })?;
+
+        INTERNAL.destroy()?.get()
+    }
+
+    #[test]
+    fn test_ambiguous_confirmation() -> Result<()> {
+        let seqdef: bindings::SequenceDefinition =
+            SequenceDefinition::from_sequences(&[("oc", "©"), ("ocd", "🐙")]).into();
+
+        // Create a new instance of KeyboardTranslator
+        let _instance = KeyboardTranslatorFactory
+            .into_object()
+            .CreateInstance(Some(&seqdef))?;
+
+        INTERNAL.with_borrow_mut(|internal| {
+            // Without confirmation, the shorter sequence wins
+            assert_eq!(
+                internal.forward(0, "o".to_string()),
+                Err(SequenceDefinitionError::Incomplete)
+            );
+            assert_eq!(internal.forward(0, "c".to_string()), Ok("©".to_string()));
+
+            internal.confirm_ambiguous = true;
+            assert_eq!(
+                internal.forward(0, "o".to_string()),
+                Err(SequenceDefinitionError::Incomplete)
+            );
+            assert_eq!(
+                internal.forward(0, "c".to_string()),
+                Err(SequenceDefinitionError::Incomplete)
+            );
+            assert_eq!(internal.pending.as_deref(), Some("©"));
+            assert_eq!(internal.forward(0, "d".to_string()), Ok("🐙".to_string()));
+            assert_eq!(internal.pending, None);
+
+            // A key that does not continue the sequence confirms it, then counts on its own
+            let _ = internal.forward(0, "o".to_string());
+            let _ = internal.forward(0, "c".to_string());
+            assert_eq!(internal.forward(0, "x".to_string()), Ok("©x".to_string()));
+            assert!(internal.state.is_empty());
+
+            let _ = internal.forward(0, "o".to_string());
+            let _ = internal.forward(0, "c".to_string());
+            assert_eq!(internal.forward(0, "o".to_string()), Ok("©".to_string()));
+            assert_eq!(internal.state, [0x6f]);
+            assert_eq!(
+                internal.forward(0, "c".to_string()),
+                Err(SequenceDefinitionError::Incomplete)
+            );
+
+            // Enter only confirms
+            assert_eq!(internal.forward(0, "\r".to_string()), Ok("©".to_string()));
+            assert!(internal.state.is_empty());
+            assert_eq!(internal.pending, None);
+            Ok(())
+        })?;
+
+        INTERNAL.destroy()?.get()
+    }
+
+    #[test]
+    fn test_swapped_order() -> Result<()> {
+        let seqdef: bindings::SequenceDefinition =
+            SequenceDefinition::from_sequences(&[("'e", "é")]).into();
+        seqdef.SetMatchingOptions(true, false)?;
+
+        // Create a new instance of KeyboardTranslator
+        let _instance = KeyboardTranslatorFactory
+            .into_object()
+            .CreateInstance(Some(&seqdef))?;
+
+        INTERNAL.with_borrow_mut(|internal| {
+            // The letter is the second key of `' e`, so it waits for the accent
+            assert_eq!(
+                internal.forward(0, "e".to_string()),
+                Err(SequenceDefinitionError::Incomplete)
+            );
+            assert_eq!(internal.forward(0, "'".to_string()), Ok("é".to_string()));
+            assert!(internal.state.is_empty());
+
+            assert_eq!(
+                internal.forward(0, "x".to_string()),
+                Err(SequenceDefinitionError::ValueNotFound)
+            );
+            Ok(())
+        })?;
+
+        INTERNAL.destroy()?.get()
+    }
 }
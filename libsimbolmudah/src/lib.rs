@@ -1,3 +1,5 @@
+#[cfg(feature = "bench")]
+pub mod bench;
 mod bindings;
 mod keyboard_hook;
 mod keyboard_translator;
@@ -0,0 +1,242 @@
+use windows::Win32::Globalization::{
+    u_charType, ublock_getCode, UBlockCode, UCharCategory, UBLOCK_INVALID_CODE, UBLOCK_NO_BLOCK,
+    U_CONTROL_CHAR, U_CURRENCY_SYMBOL, U_FORMAT_CHAR, U_LINE_SEPARATOR, U_MATH_SYMBOL,
+    U_PARAGRAPH_SEPARATOR, U_PRIVATE_USE_CHAR, U_SURROGATE, U_UNASSIGNED,
+};
+
+/// Where the characters of a symbol category come from.
+enum Members {
+    /// Unicode blocks, as inclusive code point ranges.
+    Blocks(&'static [(u32, u32)]),
+    /// Every character with this general category.
+    GeneralCategory(UCharCategory),
+}
+
+/// A set of symbols to browse, next to the emoji groups.
+pub(super) struct SymbolCategory {
+    pub(super) name: &'static str,
+    members: Members,
+}
+
+pub(super) static SYMBOL_CATEGORIES: [SymbolCategory; 11] = [
+    SymbolCategory {
+        name: "Arrows",
+        // Arrows, Supplemental Arrows-A, -B and -C
+        members: Members::Blocks(&[
+            (0x2190, 0x21ff),
+            (0x27f0, 0x27ff),
+            (0x2900, 0x297f),
+            (0x1f800, 0x1f8ff),
+        ]),
+    },
+    SymbolCategory {
+        name: "Currency",
+        members: Members::GeneralCategory(U_CURRENCY_SYMBOL),
+    },
+    SymbolCategory {
+        name: "Math Operators",
+        members: Members::GeneralCategory(U_MATH_SYMBOL),
+    },
+    SymbolCategory {
+        name: "Box Drawing",
+        // Box Drawing, Block Elements
+        members: Members::Blocks(&[(0x2500, 0x259f)]),
+    },
+    SymbolCategory {
+        name: "Geometric Shapes",
+        // Geometric Shapes, Geometric Shapes Extended
+        members: Members::Blocks(&[(0x25a0, 0x25ff), (0x1f780, 0x1f7ff)]),
+    },
+    SymbolCategory {
+        name: "Letterlike Symbols",
+        members: Members::Blocks(&[(0x2100, 0x214f)]),
+    },
+    SymbolCategory {
+        name: "Number Forms",
+        members: Members::Blocks(&[(0x2150, 0x218f)]),
+    },
+    SymbolCategory {
+        name: "Superscripts and Subscripts",
+        members: Members::Blocks(&[(0x2070, 0x209f)]),
+    },
+    SymbolCategory {
+        name: "Punctuation",
+        // General Punctuation, Supplemental Punctuation
+        members: Members::Blocks(&[(0x2000, 0x206f), (0x2e00, 0x2e7f)]),
+    },
+    SymbolCategory {
+        name: "Technical",
+        // Miscellaneous Technical
+        members: Members::Blocks(&[(0x2300, 0x23ff)]),
+    },
+    SymbolCategory {
+        name: "Dingbats",
+        members: Members::Blocks(&[(0x2700, 0x27bf)]),
+    },
+];
+
+impl SymbolCategory {
+    /// Whether the category has the character of the general category `category`.
+    fn includes(&self, value: char, category: UCharCategory) -> bool {
+        match self.members {
+            Members::Blocks(ranges) => {
+                is_visible(category)
+                    && ranges
+                        .iter()
+                        .any(|&(start, end)| (start..=end).contains(&(value as u32)))
+            }
+            Members::GeneralCategory(general) => category == general,
+        }
+    }
+}
+
+/// Characters of every symbol category, taken from the named characters of a rebuild rather
+/// than from a scan of every code point.
+#[derive(Debug, Default)]
+pub(super) struct CategoryMembers {
+    /// Characters of each entry of `SYMBOL_CATEGORIES`, in code point order.
+    members: Box<[Box<[char]>]>,
+}
+
+impl CategoryMembers {
+    pub(super) fn build(values: impl IntoIterator<Item = char>) -> Self {
+        let mut members = vec![Vec::new(); SYMBOL_CATEGORIES.len()];
+        for value in values {
+            let category = char_type(value);
+            for (symbols, chars) in SYMBOL_CATEGORIES.iter().zip(&mut members) {
+                if symbols.includes(value, category) {
+                    chars.push(value);
+                }
+            }
+        }
+
+        Self {
+            members: members
+                .into_iter()
+                .map(|mut chars| {
+                    chars.sort_unstable();
+                    chars.dedup();
+                    chars.into_boxed_slice()
+                })
+                .collect(),
+        }
+    }
+
+    /// Characters of the symbol category called `name`.
+    pub(super) fn find(&self, name: &str) -> Option<&[char]> {
+        let index = SYMBOL_CATEGORIES
+            .iter()
+            .position(|category| category.name == name)?;
+        self.members.get(index).map(|chars| &**chars)
+    }
+
+    /// Index in `SYMBOL_CATEGORIES` of the first category that has the character.
+    pub(super) fn position(&self, value: char) -> Option<usize> {
+        self.members
+            .iter()
+            .position(|chars| chars.binary_search(&value).is_ok())
+    }
+
+    /// Bytes taken by the members, for the rebuild statistics.
+    pub(super) fn size(&self) -> usize {
+        self.members
+            .iter()
+            .map(|chars| chars.len() * std::mem::size_of::<char>())
+            .sum()
+    }
+}
+
+/// Visible characters of the Unicode block of `value` other than itself, nearest first.
+pub(super) fn block_neighbours(value: char) -> impl Iterator<Item = char> {
+    let value = value as u32;
+    let block = block_code(value);
+    let (mut start, mut end) = (value, value);
+    if block != UBLOCK_NO_BLOCK && block != UBLOCK_INVALID_CODE {
+        while start > 0 && block_code(start - 1) == block {
+            start -= 1;
+        }
+        while end < char::MAX as u32 && block_code(end + 1) == block {
+            end += 1;
+        }
+    }
+
+    (1..=(end - value).max(value - start))
+        .flat_map(move |distance| {
+            [
+                Some(value + distance).filter(|&c| c <= end),
+                value.checked_sub(distance).filter(|&c| c >= start),
+            ]
+        })
+        .flatten()
+        .filter_map(char::from_u32)
+        .filter(|&c| is_visible(char_type(c)))
+}
+
+fn block_code(value: u32) -> UBlockCode {
+    unsafe { ublock_getCode(value as i32) }
+}
+
+fn char_type(value: char) -> UCharCategory {
+    UCharCategory(unsafe { u_charType(value as i32) }.into())
+}
+
+/// Whether a character can be shown on its own, unlike controls and separators.
+fn is_visible(category: UCharCategory) -> bool {
+    ![
+        U_UNASSIGNED,
+        U_CONTROL_CHAR,
+        U_FORMAT_CHAR,
+        U_LINE_SEPARATOR,
+        U_PARAGRAPH_SEPARATOR,
+        U_PRIVATE_USE_CHAR,
+        U_SURROGATE,
+    ]
+    .contains(&category)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn members() -> CategoryMembers {
+        CategoryMembers::build("a$¢£€≠∀←→⟶…\u{200d}".chars())
+    }
+
+    #[test]
+    fn test_blocks() {
+        let members = members();
+        let arrows = members.find("Arrows").unwrap();
+        assert_eq!(arrows, ['←', '→', '⟶']);
+        assert!(!arrows.contains(&'∀'));
+        assert_eq!(members.position('⟶'), Some(0));
+
+        // Format characters of General Punctuation are left out
+        assert_eq!(members.find("Punctuation").unwrap(), ['…']);
+    }
+
+    #[test]
+    fn test_general_category() {
+        let members = members();
+        assert_eq!(members.find("Currency").unwrap(), ['$', '¢', '£', '€']);
+        let math = members.find("Math Operators").unwrap();
+        assert!(math.contains(&'≠'));
+        assert!(!math.contains(&'$'));
+        assert_eq!(members.position('a'), None);
+        assert!(members.find("Smileys & Emotion").is_none());
+    }
+
+    #[test]
+    fn test_block_neighbours() {
+        let arrows = block_neighbours('→').collect::<Vec<_>>();
+        assert_eq!(arrows[..4], ['↓', '↑', '↔', '←']);
+        assert!(arrows.contains(&'⇿'));
+        assert!(!arrows.contains(&'→'));
+        assert!(!arrows.contains(&'∀'));
+
+        // Controls of Basic Latin are left out
+        let latin = block_neighbours('a').collect::<Vec<_>>();
+        assert!(latin.contains(&'z'));
+        assert!(!latin.contains(&'\n'));
+        assert!(!latin.contains(&'é'));
+    }
+}
@@ -0,0 +1,187 @@
+use std::collections::HashMap;
+
+use super::search_fold::SearchFold;
+use crate::utils::functions::fail_message;
+
+/// HTML named character references such as `rarr` for `&rarr;`.
+#[derive(Debug, Default)]
+pub(super) struct HtmlEntities(HashMap<Box<str>, Box<str>>);
+
+impl HtmlEntities {
+    /// Reads lines of a name and its characters separated by a tab, e.g. `rarr\t→`. Lines
+    /// starting with `#` are comments. The characters are not trimmed, as some are spaces.
+    pub(super) fn parse(text: &str) -> windows_core::Result<Self> {
+        let mut result = Self::default();
+        for line in text.lines() {
+            if line.trim().is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let (name, value) = line
+                .split_once('\t')
+                .filter(|(name, value)| {
+                    !name.is_empty()
+                        && name.bytes().all(|b| b.is_ascii_alphanumeric())
+                        && !value.is_empty()
+                })
+                .ok_or_else(|| fail_message(&format!("Invalid entity line: {line}")))?;
+            result.0.insert(name.into(), value.into());
+        }
+        Ok(result)
+    }
+
+    pub(super) fn get(&self, name: &str) -> Option<&str> {
+        self.0.get(name).map(|value| value.as_ref())
+    }
+
+    /// Bytes taken by the table, for the rebuild statistics.
+    pub(super) fn size(&self) -> usize {
+        self.0.iter().map(|(k, v)| k.len() + v.len()).sum()
+    }
+}
+
+/// The characters a query writes out rather than describes, when every token is a code point
+/// (`U+2192`, `0x2192`) or a character reference (`&rarr;`, `&#8594;`, `&#x2192;`).
+pub(super) fn parse_code_points(tokens: &[String], entities: &HtmlEntities) -> Option<String> {
+    let mut result = String::new();
+    for token in tokens {
+        if let Some(hex) = token
+            .strip_prefix("U+")
+            .or_else(|| token.strip_prefix("u+"))
+            .or_else(|| token.strip_prefix("0x"))
+            .or_else(|| token.strip_prefix("0X"))
+        {
+            result.push(parse_code_point(hex, 16)?);
+        } else if token.starts_with('&') {
+            // Several references can follow each other, as in `&#x1F1EE;&#x1F1E9;`
+            for reference in token.split_terminator(';') {
+                let reference = reference.strip_prefix('&')?;
+                match reference.strip_prefix('#') {
+                    Some(number) => result.push(match number.strip_prefix(['x', 'X']) {
+                        Some(hex) => parse_code_point(hex, 16)?,
+                        None => parse_code_point(number, 10)?,
+                    }),
+                    None => result.push_str(entities.get(reference)?),
+                }
+            }
+        } else {
+            return None;
+        }
+    }
+    (!result.is_empty()).then_some(result)
+}
+
+fn parse_code_point(digits: &str, radix: u32) -> Option<char> {
+    // `from_str_radix` would also take a sign
+    if digits.is_empty() || digits.len() > 7 || !digits.chars().all(|c| c.is_digit(radix)) {
+        return None;
+    }
+    char::from_u32(u32::from_str_radix(digits, radix).ok()?)
+}
+
+/// Characters grouped by the letter they are written with, so that a pasted `é` finds `e`, `É`
+/// and `ｅ`.
+#[derive(Debug, Default)]
+pub(super) struct LetterGroups {
+    /// Base letters from `SearchFold::base_letter` to their characters in code point order.
+    /// Letters alone in their group are left out.
+    groups: HashMap<Box<str>, Box<[char]>>,
+    fold: SearchFold,
+}
+
+impl LetterGroups {
+    pub(super) fn build(values: impl IntoIterator<Item = char>, fold: SearchFold) -> Self {
+        let mut groups: HashMap<String, Vec<char>> = HashMap::new();
+        for value in values {
+            if let Some(base) = fold.base_letter(value) {
+                groups.entry(base).or_default().push(value);
+            }
+        }
+
+        Self {
+            groups: groups
+                .into_iter()
+                .filter_map(|(base, mut letters)| {
+                    letters.sort_unstable();
+                    letters.dedup();
+                    (letters.len() > 1).then(|| (base.into(), letters.into_boxed_slice()))
+                })
+                .collect(),
+            fold,
+        }
+    }
+
+    /// Characters with the same base letter as `value`, including itself.
+    pub(super) fn related(&self, value: char) -> &[char] {
+        self.fold
+            .base_letter(value)
+            .and_then(|base| self.groups.get(base.as_str()))
+            .map(|letters| &**letters)
+            .unwrap_or_default()
+    }
+
+    /// Bytes taken by the groups, for the rebuild statistics.
+    pub(super) fn size(&self) -> usize {
+        self.groups
+            .iter()
+            .map(|(base, letters)| base.len() + letters.len() * std::mem::size_of::<char>())
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(query: &str) -> Option<String> {
+        let entities = HtmlEntities::parse("# Names\nrarr\t→\nnbsp\t\u{a0}\n").unwrap();
+        let tokens = query
+            .split_whitespace()
+            .map(String::from)
+            .collect::<Vec<_>>();
+        parse_code_points(&tokens, &entities)
+    }
+
+    #[test]
+    fn test_code_points() {
+        for query in [
+            "U+2192", "u+2192", "0x2192", "&rarr;", "&rarr", "&#8594;", "&#x2192;",
+        ] {
+            assert_eq!(parse(query).as_deref(), Some("→"), "{query}");
+        }
+        assert_eq!(parse("&nbsp;").as_deref(), Some("\u{a0}"));
+        assert_eq!(parse("U+1F1EE U+1F1E9").as_deref(), Some("🇮🇩"));
+        assert_eq!(parse("&#x1F1EE;&#x1F1E9;").as_deref(), Some("🇮🇩"));
+
+        assert_eq!(parse("arrow"), None);
+        assert_eq!(parse("U+2192 arrow"), None);
+        assert_eq!(parse("U+D800"), None);
+        assert_eq!(parse("U+110000"), None);
+        assert_eq!(parse("0x+41"), None);
+        assert_eq!(parse("&larr;"), None);
+        assert_eq!(parse("&;"), None);
+        assert_eq!(parse(""), None);
+    }
+
+    #[test]
+    fn test_invalid_entities() {
+        assert!(HtmlEntities::parse("rarr →").is_err());
+        assert!(HtmlEntities::parse("r-arr\t→").is_err());
+        assert!(HtmlEntities::parse("rarr\t").is_err());
+    }
+
+    #[test]
+    fn test_letter_groups() {
+        let groups = LetterGroups::build(
+            ['e', 'E', 'é', 'É', 'è', 'ｅ', 'a', 'å', 'Å', '→'],
+            SearchFold::for_language("sv"),
+        );
+
+        assert_eq!(groups.related('é'), ['E', 'e', 'É', 'è', 'é', 'ｅ']);
+        assert_eq!(groups.related('ⓔ'), groups.related('e'));
+        // `å` is a letter of its own in Swedish
+        assert_eq!(groups.related('å'), ['Å', 'å']);
+        assert!(groups.related('a').is_empty());
+        assert!(groups.related('→').is_empty());
+    }
+}
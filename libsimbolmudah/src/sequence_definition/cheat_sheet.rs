@@ -0,0 +1,297 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::Write,
+};
+
+use super::{
+    categories::{CategoryMembers, SYMBOL_CATEGORIES},
+    emoji_data::{EmojiData, Qualification},
+    export::ExportEntry,
+    keysym_sequence,
+    mapped_string::is_modifier,
+};
+
+/// Sections of the cheat sheet, in the order they are printed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub(super) enum Category {
+    Accents,
+    /// Index of a symbol category in `SYMBOL_CATEGORIES`.
+    Symbols(usize),
+    Emoji,
+    Other,
+}
+
+impl Category {
+    fn title(&self) -> &'static str {
+        match self {
+            Self::Accents => "Accents",
+            Self::Symbols(index) => SYMBOL_CATEGORIES[*index].name,
+            Self::Emoji => "Emoji",
+            Self::Other => "Other",
+        }
+    }
+}
+
+/// Category of a result. Emoji are looked up as typed, so a symbol without its emoji
+/// presentation stays with the other symbols. Other results are decided by their first
+/// character, using the first symbol category that has it.
+pub(super) fn categorize(
+    value: &str,
+    emoji_data: &EmojiData,
+    symbols: &CategoryMembers,
+) -> Category {
+    if emoji_data.get(value).is_some_and(|info| {
+        matches!(
+            info.qualification,
+            Qualification::FullyQualified | Qualification::Component
+        )
+    }) {
+        return Category::Emoji;
+    }
+
+    let Some(first) = value.chars().next() else {
+        return Category::Other;
+    };
+    if value.chars().nth(1).is_some_and(|c| !is_modifier(c)) {
+        // Words and other multi-character results
+        return Category::Other;
+    }
+
+    if let Some(index) = symbols.position(first) {
+        Category::Symbols(index)
+    } else if first.is_alphabetic() && !first.is_ascii() {
+        Category::Accents
+    } else {
+        Category::Other
+    }
+}
+
+fn group<'a>(
+    entries: &'a [ExportEntry],
+    emoji_data: &EmojiData,
+    symbols: &CategoryMembers,
+) -> BTreeMap<Category, Vec<&'a ExportEntry>> {
+    let mut result: BTreeMap<_, Vec<_>> = BTreeMap::new();
+    for entry in entries {
+        result
+            .entry(categorize(&entry.result, emoji_data, symbols))
+            .or_default()
+            .push(entry);
+    }
+    result
+}
+
+/// Keys of a sequence as shown to the user, starting with the compose key.
+fn keys(sequence: &[u32], names: &HashMap<u32, Box<str>>) -> Vec<String> {
+    std::iter::once("Compose".to_string())
+        .chain(
+            sequence
+                .iter()
+                .map(|&keysym| keysym_sequence::to_display(&[keysym], names)),
+        )
+        .collect()
+}
+
+/// Renders the cheat sheet as Markdown, with one table per category.
+pub(super) fn to_markdown(
+    entries: &[ExportEntry],
+    names: &HashMap<u32, Box<str>>,
+    emoji_data: &EmojiData,
+    symbols: &CategoryMembers,
+) -> String {
+    let mut result = String::from("# Compose sequences\n");
+    for (category, entries) in group(entries, emoji_data, symbols) {
+        let _ = write!(
+            result,
+            "\n## {}\n\n| Keys | Result | Name |\n| --- | --- | --- |\n",
+            category.title()
+        );
+        for entry in entries {
+            let keys = keys(&entry.sequence, names)
+                .iter()
+                .map(|key| markdown_code(key))
+                .collect::<Vec<_>>()
+                .join(" ");
+            let _ = writeln!(
+                result,
+                "| {} | {} | {} |",
+                keys,
+                markdown_cell(&entry.result),
+                markdown_cell(&entry.description)
+            );
+        }
+    }
+    result
+}
+
+/// Renders the cheat sheet as a standalone HTML page, with one table per category.
+pub(super) fn to_html(
+    entries: &[ExportEntry],
+    names: &HashMap<u32, Box<str>>,
+    emoji_data: &EmojiData,
+    symbols: &CategoryMembers,
+) -> String {
+    let mut result = String::from(concat!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n",
+        "<title>Compose sequences</title>\n</head>\n<body>\n<h1>Compose sequences</h1>\n"
+    ));
+    for (category, entries) in group(entries, emoji_data, symbols) {
+        let _ = write!(
+            result,
+            "<h2>{}</h2>\n<table>\n<tr><th>Keys</th><th>Result</th><th>Name</th></tr>\n",
+            category.title()
+        );
+        for entry in entries {
+            let keys = keys(&entry.sequence, names)
+                .iter()
+                .map(|key| format!("<kbd>{}</kbd>", html_escape(key)))
+                .collect::<String>();
+            let _ = writeln!(
+                result,
+                "<tr><td>{}</td><td>{}</td><td>{}</td></tr>",
+                keys,
+                html_escape(&entry.result),
+                html_escape(&entry.description)
+            );
+        }
+        result.push_str("</table>\n");
+    }
+    result.push_str("</body>\n</html>\n");
+    result
+}
+
+fn markdown_cell(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('|', "\\|")
+}
+
+fn markdown_code(value: &str) -> String {
+    let value = value.replace('|', "\\|");
+    if value.contains('`') {
+        format!("`` {} ``", value)
+    } else {
+        format!("`{}`", value)
+    }
+}
+
+fn html_escape(value: &str) -> String {
+    let mut result = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => result.push_str("&amp;"),
+            '<' => result.push_str("&lt;"),
+            '>' => result.push_str("&gt;"),
+            '"' => result.push_str("&quot;"),
+            c => result.push(c),
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sequence_definition::{char_to_keysym, compose_reader::SequenceSource};
+
+    fn entry(sequence: &str, result: &str, description: &str) -> ExportEntry {
+        ExportEntry {
+            sequence: sequence.chars().map(char_to_keysym).collect(),
+            result: result.into(),
+            description: description.into(),
+            source: SequenceSource::Compose,
+        }
+    }
+
+    const EMOJI_TEST: &str = "\
+# group: Smileys & Emotion
+1F923                                                  ; fully-qualified     # 🤣 E3.0 rolling on the floor laughing
+2764 FE0F                                              ; fully-qualified     # ❤️ E0.6 red heart
+2764                                                   ; unqualified         # ❤ E0.6 red heart
+
+# group: People & Body
+1F44D 1F3FD                                            ; fully-qualified     # 👍🏽 E1.0 thumbs up: medium skin tone
+
+# group: Symbols
+2194 FE0F                                              ; fully-qualified     # ↔️ E0.6 left-right arrow
+2194                                                   ; unqualified         # ↔ E0.6 left-right arrow
+";
+
+    fn symbols(name: &str) -> Category {
+        Category::Symbols(
+            SYMBOL_CATEGORIES
+                .iter()
+                .position(|category| category.name == name)
+                .unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_categorize() {
+        let emoji_data = EmojiData::parse(EMOJI_TEST).unwrap();
+        let members = CategoryMembers::build("éǘ€£→≠×↔@".chars());
+        let categorize = |value| categorize(value, &emoji_data, &members);
+
+        assert_eq!(categorize("é"), Category::Accents);
+        assert_eq!(categorize("ǘ"), Category::Accents);
+        assert_eq!(categorize("€"), symbols("Currency"));
+        assert_eq!(categorize("£"), symbols("Currency"));
+        assert_eq!(categorize("→"), symbols("Arrows"));
+        assert_eq!(categorize("≠"), symbols("Math Operators"));
+        assert_eq!(categorize("×"), symbols("Math Operators"));
+        assert_eq!(categorize("🤣"), Category::Emoji);
+        assert_eq!(categorize("❤️"), Category::Emoji);
+        assert_eq!(categorize("👍🏽"), Category::Emoji);
+        // Without their emoji presentation, symbols stay in their own category
+        assert_eq!(categorize("↔"), symbols("Arrows"));
+        assert_eq!(categorize("↔️"), Category::Emoji);
+        assert_eq!(categorize("peradaban"), Category::Other);
+        assert_eq!(categorize("@"), Category::Other);
+        assert_eq!(categorize(""), Category::Other);
+    }
+
+    #[test]
+    fn test_markdown() {
+        let entries = [
+            entry("->", "→", "RIGHTWARDS ARROW"),
+            entry("'e", "é", "LATIN SMALL LETTER E WITH ACUTE"),
+            entry("|`", "|", "VERTICAL LINE"),
+        ];
+        let names = HashMap::new();
+
+        assert_eq!(
+            to_markdown(
+                &entries,
+                &names,
+                &EmojiData::default(),
+                &CategoryMembers::build("→é|".chars())
+            ),
+            concat!(
+                "# Compose sequences\n",
+                "\n## Accents\n\n| Keys | Result | Name |\n| --- | --- | --- |\n",
+                "| `Compose` `'` `e` | é | LATIN SMALL LETTER E WITH ACUTE |\n",
+                "\n## Arrows\n\n| Keys | Result | Name |\n| --- | --- | --- |\n",
+                "| `Compose` `-` `>` | → | RIGHTWARDS ARROW |\n",
+                "\n## Other\n\n| Keys | Result | Name |\n| --- | --- | --- |\n",
+                "| `Compose` `\\|` `` ` `` | \\| | VERTICAL LINE |\n",
+            )
+        );
+    }
+
+    #[test]
+    fn test_html() {
+        let entries = [entry("<=", "≤", "LESS-THAN OR EQUAL TO")];
+        let names = HashMap::new();
+        let html = to_html(
+            &entries,
+            &names,
+            &EmojiData::default(),
+            &CategoryMembers::build(['≤']),
+        );
+
+        assert!(html.starts_with("<!DOCTYPE html>\n"));
+        assert!(html.contains("<h2>Math Operators</h2>\n"));
+        assert!(html.contains(
+            "<tr><td><kbd>Compose</kbd><kbd>&lt;</kbd><kbd>=</kbd></td><td>≤</td><td>LESS-THAN OR EQUAL TO</td></tr>\n"
+        ));
+        assert!(html.ends_with("</body>\n</html>\n"));
+    }
+}
@@ -1,18 +1,24 @@
-use std::{fmt::Display, io::Read, rc::Rc};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::Display,
+    sync::Arc,
+};
 
-use quick_xml::de::from_str;
+use quick_xml::{de::from_str, events::Event, Reader};
 use serde::Deserialize;
-use windows::{core::HSTRING, Globalization::Language, Win32::Foundation::E_INVALIDARG};
 
+use super::diagnostics::read_compressed;
 use crate::utils::functions::fail;
 
 #[derive(Deserialize, Debug)]
 struct TopLevel {
+    #[serde(default)]
     annotations: List,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Debug, Default)]
 struct List {
+    #[serde(default)]
     annotation: Vec<Annotation>,
 }
 
@@ -30,72 +36,498 @@ pub(super) struct Annotation {
 
 #[derive(Debug)]
 pub(super) struct AnnotationPair {
-    pub(super) char: Rc<str>,
+    pub(super) char: Arc<str>,
     pub(super) desc: Box<str>,
 }
 
-#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
-#[allow(non_camel_case_types)]
-pub(super) enum SupportedLocale {
-    en,
-    id,
-    fr,
-    jv,
+const ANNOTATION_VARIANTS: [&str; 2] = ["annotations", "annotationsDerived"];
+
+/// A CLDR locale id such as `fr_CA`, as used in the names of the annotation files.
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub(super) struct Locale(Box<str>);
+
+impl Locale {
+    pub(super) fn root() -> Self {
+        Self("root".into())
+    }
+
+    /// Converts a BCP-47 tag such as `zh-hant-tw` to the CLDR id `zh_Hant_TW`.
+    /// Extensions and private use subtags are dropped.
+    pub(super) fn from_bcp47(tag: &str) -> Option<Self> {
+        let mut subtags = tag.split(['-', '_']);
+        let language = subtags
+            .next()
+            .filter(|l| (2..=8).contains(&l.len()) && l.bytes().all(|b| b.is_ascii_alphabetic()))?;
+
+        let mut id = language.to_ascii_lowercase();
+        for subtag in subtags.take_while(|s| s.len() > 1) {
+            id.push('_');
+            match subtag.len() {
+                4 if subtag.bytes().all(|b| b.is_ascii_alphabetic()) => {
+                    id.push_str(&subtag[..1].to_ascii_uppercase());
+                    id.push_str(&subtag[1..].to_ascii_lowercase());
+                }
+                2 | 3 => id.push_str(&subtag.to_ascii_uppercase()),
+                _ => id.push_str(subtag),
+            }
+        }
+
+        Some(Self(id.into()))
+    }
+
+    /// The locale this one inherits from, `None` for root.
+    /// Explicit CLDR parents take precedence over removing the last subtag.
+    pub(super) fn parent(&self, parents: &ParentLocales) -> Option<Self> {
+        if self.0.as_ref() == "root" {
+            return None;
+        }
+        if let Some(parent) = parents.0.get(&self.0) {
+            return Some(parent.clone());
+        }
+
+        Some(match self.0.rsplit_once('_') {
+            Some((parent, _)) => Self(parent.into()),
+            None => Self::root(),
+        })
+    }
+
+    /// The language subtag, such as `sv` for `sv_FI`.
+    pub(super) fn language(&self) -> &str {
+        self.0.split('_').next().unwrap_or_default()
+    }
+
+    /// The locale followed by its ancestors, ending with root.
+    pub(super) fn fallback_chain(&self, parents: &ParentLocales) -> Vec<Self> {
+        let mut result = vec![self.clone()];
+        while let Some(parent) = result.last().and_then(|l| l.parent(parents)) {
+            if result.contains(&parent) {
+                break;
+            }
+            result.push(parent);
+        }
+        result
+    }
 }
 
-impl Display for SupportedLocale {
+impl Display for Locale {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let locale_str = match self {
-            SupportedLocale::en => "en",
-            SupportedLocale::id => "id",
-            SupportedLocale::fr => "fr",
-            SupportedLocale::jv => "jv",
-        };
-        write!(f, "{}", locale_str)
+        write!(f, "{}", self.0)
     }
 }
 
-impl TryFrom<Language> for SupportedLocale {
-    type Error = windows::core::Error;
-
-    fn try_from(value: Language) -> Result<Self, Self::Error> {
-        match value.LanguageTag()?.to_string().as_str() {
-            "en" => Ok(SupportedLocale::en),
-            "en-US" => Ok(SupportedLocale::en),
-            "en-GB" => Ok(SupportedLocale::en),
-            "en-CA" => Ok(SupportedLocale::en),
-            "id" => Ok(SupportedLocale::id),
-            "id-ID" => Ok(SupportedLocale::id),
-            "fr" => Ok(SupportedLocale::fr),
-            "fr-FR" => Ok(SupportedLocale::fr),
-            "fr-CA" => Ok(SupportedLocale::fr),
-            "jv" => Ok(SupportedLocale::jv),
-            "jv-Java" => Ok(SupportedLocale::jv),
-            _ => Err(windows::core::Error::new(
-                E_INVALIDARG,
-                "Unsupported locale",
-            )),
+/// Parent locales from the CLDR supplemental data, for locales whose parent is not found by
+/// removing the last subtag, e.g. `en_AU` → `en_001` or `pt_AO` → `pt_PT`.
+#[derive(Debug, Default)]
+pub(super) struct ParentLocales(HashMap<Box<str>, Locale>);
+
+impl ParentLocales {
+    /// Reads the `parentLocales` element without a `component` attribute. Parents that only
+    /// apply to collation or segmentation data are skipped.
+    pub(super) fn parse(xml: &str) -> windows_core::Result<Self> {
+        let mut reader = Reader::from_str(xml);
+        let mut result = HashMap::new();
+        let mut in_list = false;
+
+        loop {
+            match reader.read_event().map_err(fail)? {
+                Event::Start(e) if e.name().as_ref() == b"parentLocales" => {
+                    in_list = e.try_get_attribute("component").map_err(fail)?.is_none();
+                }
+                Event::End(e) if e.name().as_ref() == b"parentLocales" => in_list = false,
+                Event::Empty(e) if in_list && e.name().as_ref() == b"parentLocale" => {
+                    let (Some(parent), Some(locales)) = (
+                        e.try_get_attribute("parent").map_err(fail)?,
+                        e.try_get_attribute("locales").map_err(fail)?,
+                    ) else {
+                        continue;
+                    };
+                    let parent = Locale(parent.unescape_value().map_err(fail)?.into());
+                    for locale in locales.unescape_value().map_err(fail)?.split_whitespace() {
+                        result.insert(locale.into(), parent.clone());
+                    }
+                }
+                Event::Eof => break,
+                _ => {}
+            }
         }
+
+        Ok(Self(result))
     }
 }
 
-impl From<&str> for SupportedLocale {
-    fn from(s: &str) -> Self {
-        match s {
-            "en" => SupportedLocale::en,
-            "id" => SupportedLocale::id,
-            "fr" => SupportedLocale::fr,
-            "jv" => SupportedLocale::jv,
-            _ => panic!("Unsupported locale"),
+/// Finds the annotation files in a directory, by locale.
+/// Files are named `{locale}-annotations.xml.br` and `{locale}-annotationsDerived.xml.br`.
+pub(super) fn available_locales(dir: &str) -> windows_core::Result<HashMap<Locale, Vec<String>>> {
+    let mut result: HashMap<_, Vec<_>> = HashMap::new();
+    for entry in std::fs::read_dir(dir).map_err(fail)? {
+        let path = entry.map_err(fail)?.path();
+        let Some(name) = path.file_name().and_then(|n| n.to_str()) else {
+            continue;
+        };
+        let Some((locale, variant)) = name.strip_suffix(".xml.br").and_then(|n| n.split_once('-'))
+        else {
+            continue;
+        };
+
+        if ANNOTATION_VARIANTS.contains(&variant) {
+            result
+                .entry(Locale(locale.into()))
+                .or_default()
+                .push(path.to_string_lossy().into_owned());
         }
     }
+
+    for files in result.values_mut() {
+        files.sort();
+    }
+    Ok(result)
+}
+
+/// Picks the locales to load for the user languages, most preferred first.
+/// Each language is followed by its fallbacks that have annotations, and root comes last.
+/// Languages without any annotations are skipped.
+pub(super) fn resolve_locales<'a>(
+    languages: impl IntoIterator<Item = &'a str>,
+    available: &HashMap<Locale, Vec<String>>,
+    parents: &ParentLocales,
+) -> Vec<Locale> {
+    let mut result = Vec::new();
+    for locale in languages.into_iter().filter_map(Locale::from_bcp47) {
+        for fallback in locale.fallback_chain(parents) {
+            if fallback != Locale::root()
+                && available.contains_key(&fallback)
+                && !result.contains(&fallback)
+            {
+                result.push(fallback);
+            }
+        }
+    }
+
+    if available.contains_key(&Locale::root()) {
+        result.push(Locale::root());
+    }
+    result
 }
 
-pub(super) fn load_annotation_file(path: &HSTRING) -> windows_core::Result<Vec<Annotation>> {
-    let mut file = std::fs::File::open(path.to_string()).map_err(fail)?;
-    let mut input = brotli_decompressor::Decompressor::new(&mut file, 4096);
-    let mut buf = String::new();
-    let _num = input.read_to_string(&mut buf).map_err(fail)?;
-    let object: TopLevel = from_str(&buf).map_err(fail)?;
+pub(super) fn load_annotation_file(path: &str) -> windows_core::Result<Vec<Annotation>> {
+    parse_annotation_file(&read_compressed(path)?)
+}
+
+/// Search languages that have no annotations, not even through their parents.
+pub(super) fn unavailable_languages<'a>(
+    languages: impl IntoIterator<Item = &'a str>,
+    available: &HashMap<Locale, Vec<String>>,
+    parents: &ParentLocales,
+) -> Vec<&'a str> {
+    languages
+        .into_iter()
+        .filter(|language| {
+            Locale::from_bcp47(language).map_or(true, |locale| {
+                !locale
+                    .fallback_chain(parents)
+                    .iter()
+                    .any(|l| *l != Locale::root() && available.contains_key(l))
+            })
+        })
+        .collect()
+}
+
+pub(super) fn parse_annotation_file(xml: &str) -> windows_core::Result<Vec<Annotation>> {
+    let object: TopLevel = from_str(xml).map_err(fail)?;
     Ok(object.annotations.annotation)
 }
+
+/// Value CLDR uses for data inherited from the parent locale.
+const INHERITANCE_MARKER: &str = "↑↑↑";
+
+/// Annotations of one character. `None` is inherited from the parent locale.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(super) struct CharAnnotations {
+    pub(super) keywords: Option<Vec<Box<str>>>,
+    pub(super) tts: Option<Box<str>>,
+}
+
+/// Annotations of one locale by character, as given in its files.
+/// Regional locales only list what differs from their parent.
+#[derive(Debug, Default)]
+pub(super) struct LocaleAnnotations(BTreeMap<String, CharAnnotations>);
+
+impl LocaleAnnotations {
+    /// Adds the annotations of a file. Values already present are kept.
+    pub(super) fn extend(&mut self, annotations: Vec<Annotation>) {
+        for a in annotations {
+            let text = a.text.trim();
+            if text == INHERITANCE_MARKER {
+                continue;
+            }
+
+            let entry = self.0.entry(a.cp).or_default();
+            if a.r#type.as_deref() == Some("tts") {
+                entry.tts.get_or_insert_with(|| text.into());
+            } else {
+                entry.keywords.get_or_insert_with(|| {
+                    text.split_terminator('|')
+                        .map(|s| s.trim().into())
+                        .collect()
+                });
+            }
+        }
+    }
+
+    /// Resolves a locale from its fallback chain, most specific first.
+    /// Each value comes from the first locale in the chain that does not inherit it.
+    pub(super) fn resolve<'a>(chain: impl IntoIterator<Item = &'a LocaleAnnotations>) -> Self {
+        let mut result = BTreeMap::<String, CharAnnotations>::new();
+        for locale in chain {
+            for (cp, annotations) in &locale.0 {
+                let entry = result.entry(cp.clone()).or_default();
+                if entry.keywords.is_none() {
+                    entry.keywords = annotations.keywords.clone();
+                }
+                if entry.tts.is_none() {
+                    entry.tts = annotations.tts.clone();
+                }
+            }
+        }
+        Self(result)
+    }
+
+    pub(super) fn iter(&self) -> impl Iterator<Item = (&str, &CharAnnotations)> {
+        self.0
+            .iter()
+            .map(|(cp, annotations)| (cp.as_str(), annotations))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SUPPLEMENTAL: &str = r#"<?xml version="1.0" encoding="UTF-8" ?>
+<supplementalData>
+    <version number="$Revision$"/>
+    <parentLocales>
+        <parentLocale parent="root" locales="az_Arab zh_Hant"/>
+        <parentLocale parent="en_001" locales="en_AU en_GB"/>
+        <parentLocale parent="pt_PT" locales="pt_AO pt_MZ"/>
+    </parentLocales>
+    <parentLocales component="collations">
+        <parentLocale parent="zh_Hant" locales="yue"/>
+    </parentLocales>
+</supplementalData>
+"#;
+
+    fn locale(id: &str) -> Locale {
+        Locale(id.into())
+    }
+
+    fn available(ids: &[&str]) -> HashMap<Locale, Vec<String>> {
+        ids.iter()
+            .map(|id| (locale(id), vec![format!("{id}-annotations.xml.br")]))
+            .collect()
+    }
+
+    #[test]
+    fn test_from_bcp47() {
+        assert_eq!(Locale::from_bcp47("fr-CA"), Some(locale("fr_CA")));
+        assert_eq!(Locale::from_bcp47("zh-hant-tw"), Some(locale("zh_Hant_TW")));
+        assert_eq!(
+            Locale::from_bcp47("en-US-u-ca-gregory"),
+            Some(locale("en_US"))
+        );
+        assert_eq!(Locale::from_bcp47("es-419"), Some(locale("es_419")));
+        assert_eq!(Locale::from_bcp47("jv"), Some(locale("jv")));
+        assert_eq!(Locale::from_bcp47(""), None);
+        assert_eq!(Locale::from_bcp47("x-private"), None);
+    }
+
+    #[test]
+    fn test_fallback_chain() {
+        let parents = ParentLocales::parse(SUPPLEMENTAL).unwrap();
+
+        assert_eq!(
+            locale("fr_CA").fallback_chain(&parents),
+            [locale("fr_CA"), locale("fr"), Locale::root()]
+        );
+        assert_eq!(
+            locale("en_AU").fallback_chain(&parents),
+            [
+                locale("en_AU"),
+                locale("en_001"),
+                locale("en"),
+                Locale::root()
+            ]
+        );
+        assert_eq!(
+            locale("pt_AO").fallback_chain(&parents),
+            [
+                locale("pt_AO"),
+                locale("pt_PT"),
+                locale("pt"),
+                Locale::root()
+            ]
+        );
+        assert_eq!(
+            locale("zh_Hant_TW").fallback_chain(&parents),
+            [locale("zh_Hant_TW"), locale("zh_Hant"), Locale::root()]
+        );
+        // Collation parents do not apply to annotations
+        assert_eq!(
+            locale("yue").fallback_chain(&parents),
+            [locale("yue"), Locale::root()]
+        );
+    }
+
+    #[test]
+    fn test_resolve_locales() {
+        let parents = ParentLocales::parse(SUPPLEMENTAL).unwrap();
+        let available = available(&["root", "en", "en_001", "fr", "fr_CA", "pt"]);
+
+        assert_eq!(
+            resolve_locales(["fr-CA", "xx-YY", "pt-BR", "en-AU"], &available, &parents),
+            [
+                locale("fr_CA"),
+                locale("fr"),
+                locale("pt"),
+                locale("en_001"),
+                locale("en"),
+                Locale::root()
+            ]
+        );
+        assert_eq!(
+            resolve_locales(["de-DE"], &available, &parents),
+            [Locale::root()]
+        );
+        assert!(resolve_locales(["de"], &HashMap::new(), &parents).is_empty());
+    }
+
+    #[test]
+    fn test_unavailable_languages() {
+        let parents = ParentLocales::parse(SUPPLEMENTAL).unwrap();
+        let available = available(&["root", "en", "en_001", "fr"]);
+
+        assert_eq!(
+            unavailable_languages(
+                ["fr-CA", "xx-YY", "en-AU", "de-DE", "!"],
+                &available,
+                &parents
+            ),
+            ["xx-YY", "de-DE", "!"]
+        );
+    }
+
+    const FR: &str = r#"<?xml version="1.0" encoding="UTF-8" ?>
+<ldml>
+    <identity><language type="fr"/></identity>
+    <annotations>
+        <annotation cp="€">devise | euro</annotation>
+        <annotation cp="€" type="tts">euro</annotation>
+        <annotation cp="→">flèche | droite</annotation>
+        <annotation cp="→" type="tts">flèche droite</annotation>
+        <annotation cp="🤣">rire | se rouler par terre</annotation>
+        <annotation cp="🤣" type="tts">se rouler par terre de rire</annotation>
+    </annotations>
+</ldml>
+"#;
+
+    const FR_CA: &str = r#"<?xml version="1.0" encoding="UTF-8" ?>
+<ldml>
+    <identity><language type="fr"/><territory type="CA"/></identity>
+    <annotations>
+        <annotation cp="→">↑↑↑</annotation>
+        <annotation cp="→" type="tts">flèche pointant vers la droite</annotation>
+        <annotation cp="🤣">mort de rire | rire</annotation>
+        <annotation cp="🤣" type="tts">↑↑↑</annotation>
+    </annotations>
+</ldml>
+"#;
+
+    const ROOT: &str = r#"<?xml version="1.0" encoding="UTF-8" ?>
+<ldml>
+    <identity><language type="root"/></identity>
+    <annotations>
+        <annotation cp="€">↑↑↑</annotation>
+        <annotation cp="$">USD</annotation>
+    </annotations>
+</ldml>
+"#;
+
+    const EMPTY: &str = r#"<?xml version="1.0" encoding="UTF-8" ?>
+<ldml>
+    <identity><language type="root"/></identity>
+</ldml>
+"#;
+
+    fn locale_annotations(xml: &str) -> LocaleAnnotations {
+        let mut result = LocaleAnnotations::default();
+        result.extend(parse_annotation_file(xml).unwrap());
+        result
+    }
+
+    fn keywords(values: &[&str]) -> Option<Vec<Box<str>>> {
+        Some(values.iter().map(|&v| v.into()).collect())
+    }
+
+    #[test]
+    fn test_inheritance_marker_is_not_a_value() {
+        let fr_ca = locale_annotations(FR_CA);
+        let map: HashMap<_, _> = fr_ca.iter().collect();
+
+        assert_eq!(map["→"].keywords, None);
+        assert_eq!(
+            map["→"].tts.as_deref(),
+            Some("flèche pointant vers la droite")
+        );
+        assert_eq!(map["🤣"].keywords, keywords(&["mort de rire", "rire"]));
+        assert_eq!(map["🤣"].tts, None);
+    }
+
+    #[test]
+    fn test_resolve_regional_locale() {
+        let (fr_ca, fr, root) = (
+            locale_annotations(FR_CA),
+            locale_annotations(FR),
+            locale_annotations(ROOT),
+        );
+        let resolved = LocaleAnnotations::resolve([&fr_ca, &fr, &root]);
+        let map: HashMap<_, _> = resolved.iter().collect();
+
+        // Inherited keywords, own name
+        assert_eq!(map["→"].keywords, keywords(&["flèche", "droite"]));
+        assert_eq!(
+            map["→"].tts.as_deref(),
+            Some("flèche pointant vers la droite")
+        );
+        // Own keywords, inherited name
+        assert_eq!(map["🤣"].keywords, keywords(&["mort de rire", "rire"]));
+        assert_eq!(
+            map["🤣"].tts.as_deref(),
+            Some("se rouler par terre de rire")
+        );
+        // Only in the parent
+        assert_eq!(map["€"].keywords, keywords(&["devise", "euro"]));
+        assert_eq!(map["€"].tts.as_deref(), Some("euro"));
+        // Only in root, without a name
+        assert_eq!(map["$"].keywords, keywords(&["USD"]));
+        assert_eq!(map["$"].tts, None);
+        assert_eq!(map.len(), 4);
+    }
+
+    #[test]
+    fn test_resolve_parent_is_unchanged() {
+        let fr = locale_annotations(FR);
+        let resolved = LocaleAnnotations::resolve([&fr]);
+        let map: HashMap<_, _> = resolved.iter().collect();
+
+        assert_eq!(map["→"].tts.as_deref(), Some("flèche droite"));
+        assert_eq!(
+            map["🤣"].keywords,
+            keywords(&["rire", "se rouler par terre"])
+        );
+    }
+
+    #[test]
+    fn test_empty_annotation_file() {
+        assert!(parse_annotation_file(EMPTY).unwrap().is_empty());
+    }
+}
@@ -1,57 +1,191 @@
-use super::{keysym_reader::KeySymDef, mapped_string::MappedString};
-use crate::utils::functions::{fail, fail_message};
+use super::{
+    diagnostics::{DefinitionError, RebuildReport, SourceLocation},
+    keysym_reader::{char_to_keysym, KeySymDef},
+    mapped_string::MappedString,
+};
+use crate::utils::functions::fail;
 use regex::Regex;
-use std::{collections::BTreeMap, io::Read};
+use std::{collections::BTreeMap, sync::Arc};
 use windows::core::Result;
 
 const COMPOSE_REGEX_2_STR: &str =
-    r#"^<Multi_key> <([a-zA-Z0-9_]+)> <([a-zA-Z0-9_]+)>\s+: "(.*)".*$"#;
-const COMPOSE_REGEX_3_STR: &str =
-    r#"^<Multi_key> <([a-zA-Z0-9_]+)> <([a-zA-Z0-9_]+)> <([a-zA-Z0-9_]+)>\s+: "(.*)".*$"#;
-const COMPOSE_REGEX_4_STR: &str = r#"^<Multi_key> <([a-zA-Z0-9_]+)> <([a-zA-Z0-9_]+)> <([a-zA-Z0-9_]+)> <([a-zA-Z0-9_]+)>\s+: "(.*)".*$"#;
+    r#"^<Multi_key> <([a-zA-Z0-9_]+)> <([a-zA-Z0-9_]+)>\s+: "((?:[^"\\]|\\.)*)".*$"#;
+const COMPOSE_REGEX_3_STR: &str = r#"^<Multi_key> <([a-zA-Z0-9_]+)> <([a-zA-Z0-9_]+)> <([a-zA-Z0-9_]+)>\s+: "((?:[^"\\]|\\.)*)".*$"#;
+const COMPOSE_REGEX_4_STR: &str = r#"^<Multi_key> <([a-zA-Z0-9_]+)> <([a-zA-Z0-9_]+)> <([a-zA-Z0-9_]+)> <([a-zA-Z0-9_]+)>\s+: "((?:[^"\\]|\\.)*)".*$"#;
+
+/// Where a sequence comes from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(super) enum SequenceSource {
+    /// The Compose file given to `Rebuild`.
+    Compose,
+    /// Sequences shipped with the library.
+    Builtin,
+}
+
+impl SequenceSource {
+    pub(super) const ALL: [Self; 2] = [Self::Compose, Self::Builtin];
+
+    pub(super) fn as_str(&self) -> &'static str {
+        match self {
+            Self::Compose => "compose",
+            Self::Builtin => "builtin",
+        }
+    }
+
+    pub(super) fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|source| source.as_str() == name)
+    }
+}
 
 pub(super) struct ComposeDef {
-    content: BTreeMap<String, MappedString>,
+    content: BTreeMap<Vec<u32>, (MappedString, SequenceSource)>,
 }
 
 impl ComposeDef {
-    pub(super) fn build(keysym: &KeySymDef, path: &str) -> Result<Self> {
-        let content = get_compose_def(keysym, path)?;
+    /// Reads the rules of a Compose file already decompressed, plus the builtin sequences.
+    pub(super) fn parse(
+        keysym: &KeySymDef,
+        file: &str,
+        content: &str,
+        report: &mut RebuildReport,
+    ) -> Result<Self> {
+        let content = get_compose_def(keysym, file, content, report)?;
         Ok(Self { content })
     }
+
+    pub(super) fn values(&self) -> impl Iterator<Item = &MappedString> {
+        self.content.values().map(|(value, _)| value)
+    }
 }
 
 impl IntoIterator for ComposeDef {
-    type Item = (String, MappedString);
-    type IntoIter = std::collections::btree_map::IntoIter<String, MappedString>;
+    type Item = (Vec<u32>, (MappedString, SequenceSource));
+    type IntoIter = std::collections::btree_map::IntoIter<Vec<u32>, (MappedString, SequenceSource)>;
 
     fn into_iter(self) -> Self::IntoIter {
         self.content.into_iter()
     }
 }
 
-fn get_compose_def(keysym: &KeySymDef, path: &str) -> Result<BTreeMap<String, MappedString>> {
-    let mut file = std::fs::File::open(path).map_err(fail)?;
-    let mut input = brotli_decompressor::Decompressor::new(&mut file, 4096);
-    let mut buf = String::new();
-    let _num = input.read_to_string(&mut buf).map_err(fail)?;
+fn get_compose_def(
+    keysym: &KeySymDef,
+    file: &str,
+    content: &str,
+    report: &mut RebuildReport,
+) -> Result<BTreeMap<Vec<u32>, (MappedString, SequenceSource)>> {
+    let mut result = parse_compose_def(keysym, file, content, report)?;
+
+    // result.insert(">=".into(), MappedString::Basic('≥'));
+    // result.insert("oe".into(), MappedString::Basic('œ'));
+    result.insert(
+        "wkwk".chars().map(char_to_keysym).collect(),
+        (MappedString::Basic("🤣".into()), SequenceSource::Builtin),
+    );
+    result.insert(
+        "pr".chars().map(char_to_keysym).collect(),
+        (
+            MappedString::Extra("peradaban".into()),
+            SequenceSource::Builtin,
+        ),
+    );
+
+    Ok(result)
+}
 
+/// Parses the Multi_key rules of a Compose file. Multi_key rules that cannot be read and
+/// `include` lines go to the report; other lines, such as dead key rules, are skipped.
+pub(super) fn parse_compose_def(
+    keysym: &KeySymDef,
+    file: &str,
+    content: &str,
+    report: &mut RebuildReport,
+) -> Result<BTreeMap<Vec<u32>, (MappedString, SequenceSource)>> {
     let mut result = BTreeMap::new();
     let regex2 = Regex::new(COMPOSE_REGEX_2_STR).map_err(fail)?;
     let regex3 = Regex::new(COMPOSE_REGEX_3_STR).map_err(fail)?;
     let regex4 = Regex::new(COMPOSE_REGEX_4_STR).map_err(fail)?;
+    let file: Arc<str> = file.into();
+
+    for (index, line) in content.lines().enumerate() {
+        let location = SourceLocation {
+            file: file.clone(),
+            line: index + 1,
+        };
+        let trimmed = line.trim_start();
 
-    for line in buf.lines() {
-        if let Ok((key, value)) = decode_entry(&line, &regex2, &regex3, &regex4, keysym) {
-            result.insert(key, value);
+        if let Some(path) = trimmed
+            .strip_prefix("include")
+            .filter(|rest| rest.starts_with(char::is_whitespace))
+        {
+            report.warnings.push(DefinitionError::IncludeFailed {
+                location,
+                path: path.trim().into(),
+            });
+        } else if trimmed.starts_with("<Multi_key>") {
+            match decode_entry(line, &regex2, &regex3, &regex4, keysym, &location) {
+                Ok((key, value)) => {
+                    result.insert(key, (value, SequenceSource::Compose));
+                }
+                Err(e) => report.skipped.push(e),
+            }
         }
     }
 
-    // result.insert(">=".into(), MappedString::Basic('≥'));
-    // result.insert("oe".into(), MappedString::Basic('œ'));
-    result.insert("wkwk".into(), MappedString::Basic("🤣".into()));
-    result.insert("pr".into(), MappedString::Extra("peradaban".into()));
+    Ok(result)
+}
+
+/// Escapes a result for the quoted string of a Compose rule.
+pub(super) fn escape(value: &str) -> String {
+    let mut result = String::with_capacity(value.len());
+    for c in value.chars() {
+        if matches!(c, '"' | '\\') {
+            result.push('\\');
+        }
+        result.push(c);
+    }
+    result
+}
+
+/// Reads the escapes of a quoted result: `\\`, `\"`, octal `\123` and hexadecimal `\x41`.
+/// Other escapes are returned as the error.
+fn unescape(value: &str) -> std::result::Result<String, String> {
+    let mut result = String::with_capacity(value.len());
+    let mut chars = value.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
 
+        let (radix, max_digits) = match chars.peek() {
+            Some(&next @ ('"' | '\\')) => {
+                chars.next();
+                result.push(next);
+                continue;
+            }
+            Some('x' | 'X') => {
+                chars.next();
+                (16, 2)
+            }
+            Some('0'..='7') => (8, 3),
+            Some(&next) => return Err(format!("\\{next}")),
+            None => return Err("\\".to_string()),
+        };
+
+        let mut digits = String::new();
+        while let Some(&d) = chars.peek().filter(|d| d.is_digit(radix)) {
+            if digits.len() == max_digits {
+                break;
+            }
+            digits.push(d);
+            chars.next();
+        }
+        let escaped = u32::from_str_radix(&digits, radix)
+            .ok()
+            .and_then(char::from_u32)
+            .ok_or_else(|| format!("\\{}{digits}", if radix == 16 { "x" } else { "" }))?;
+        result.push(escaped);
+    }
     Ok(result)
 }
 
@@ -61,94 +195,52 @@ fn decode_entry(
     regex3: &Regex,
     regex4: &Regex,
     keysymdef: &KeySymDef,
-) -> Result<(String, MappedString)> {
-    if let Some(caps) = regex2.captures(line) {
-        let key1 = keysymdef.get_key(
-            caps.get(1)
-                .ok_or_else(|| fail_message("Regex parse"))?
-                .as_str(),
-        )?;
-        let key2 = keysymdef.get_key(
-            caps.get(2)
-                .ok_or_else(|| fail_message("Regex parse"))?
-                .as_str(),
-        )?;
-        let value = caps
-            .get(3)
-            .ok_or_else(|| fail_message("Regex parse"))?
-            .as_str();
-
-        Ok((
-            [key1, key2].into_iter().collect(),
-            MappedString::Basic(value.into()),
-        ))
-    } else if let Some(caps) = regex3.captures(line) {
-        let key1 = keysymdef.get_key(
-            caps.get(1)
-                .ok_or_else(|| fail_message("Regex parse"))?
-                .as_str(),
-        )?;
-        let key2 = keysymdef.get_key(
-            caps.get(2)
-                .ok_or_else(|| fail_message("Regex parse"))?
-                .as_str(),
-        )?;
-        let key3 = keysymdef.get_key(
-            caps.get(3)
-                .ok_or_else(|| fail_message("Regex parse"))?
-                .as_str(),
-        )?;
-        let value = caps
-            .get(4)
-            .ok_or_else(|| fail_message("Regex parse"))?
-            .as_str();
-
-        Ok((
-            [key1, key2, key3].into_iter().collect(),
-            MappedString::Basic(value.into()),
-        ))
-    } else if let Some(caps) = regex4.captures(line) {
-        let key1 = keysymdef.get_key(
-            caps.get(1)
-                .ok_or_else(|| fail_message("Regex parse"))?
-                .as_str(),
-        )?;
-        let key2 = keysymdef.get_key(
-            caps.get(2)
-                .ok_or_else(|| fail_message("Regex parse"))?
-                .as_str(),
-        )?;
-        let key3 = keysymdef.get_key(
-            caps.get(3)
-                .ok_or_else(|| fail_message("Regex parse"))?
-                .as_str(),
-        )?;
-        let key4 = keysymdef.get_key(
-            caps.get(4)
-                .ok_or_else(|| fail_message("Regex parse"))?
-                .as_str(),
-        )?;
-        let value = caps
-            .get(5)
-            .ok_or_else(|| fail_message("Regex parse"))?
-            .as_str();
-
-        Ok((
-            [key1, key2, key3, key4].into_iter().collect(),
-            MappedString::Basic(value.into()),
-        ))
-    } else {
-        Err(fail_message("Regex parse"))
-    }
+    location: &SourceLocation,
+) -> std::result::Result<(Vec<u32>, MappedString), DefinitionError> {
+    let malformed = || DefinitionError::MalformedLine {
+        location: location.clone(),
+        text: line.trim().into(),
+    };
+    let (caps, count) = [(regex2, 2), (regex3, 3), (regex4, 4)]
+        .into_iter()
+        .find_map(|(regex, count)| Some((regex.captures(line)?, count)))
+        .ok_or_else(malformed)?;
+
+    let keys = (1..=count)
+        .map(|i| {
+            let name = caps.get(i).ok_or_else(malformed)?.as_str();
+            keysymdef
+                .get_key(name)
+                .map_err(|_| DefinitionError::UnknownKeysym {
+                    location: location.clone(),
+                    name: name.into(),
+                })
+        })
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    let value = caps.get(count + 1).ok_or_else(malformed)?;
+    let value = unescape(value.as_str()).map_err(|escape| DefinitionError::BadEscape {
+        location: location.clone(),
+        escape: escape.into(),
+    })?;
+
+    Ok((keys, MappedString::from_value(value)))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::sequence_definition::diagnostics::read_compressed;
 
     const KEYSYMDEF: &str = "x11-defs/keysymdef.h.br";
     const COMPOSEDEF: &str = "x11-defs/Compose.pre.br";
 
+    fn location() -> SourceLocation {
+        SourceLocation {
+            file: "Compose".into(),
+            line: 1,
+        }
+    }
+
     #[test]
     fn test_compose_regex() {
         let regex = Regex::new(COMPOSE_REGEX_2_STR).unwrap();
@@ -165,11 +257,17 @@ mod tests {
         let regex2 = Regex::new(COMPOSE_REGEX_2_STR).unwrap();
         let regex3 = Regex::new(COMPOSE_REGEX_3_STR).unwrap();
         let regex4 = Regex::new(COMPOSE_REGEX_4_STR).unwrap();
-        let keysymdef = KeySymDef::new(KEYSYMDEF).unwrap(); // Assuming a constructor for simplicity
+        let keysymdef = KeySymDef::parse(&read_compressed(KEYSYMDEF).unwrap()).unwrap();
 
         let line = "<Multi_key> <A> <B> : \"C\".";
-        let expected = Ok(("AB".to_string(), MappedString::Basic("C".into())));
-        let result = decode_entry(line, &regex2, &regex3, &regex4, &keysymdef);
+        let expected = Ok((vec![0x41, 0x42], MappedString::Basic("C".into())));
+        let result = decode_entry(line, &regex2, &regex3, &regex4, &keysymdef, &location());
+
+        assert_eq!(result, expected);
+
+        let line = "<Multi_key> <A> <B> : \"CD\"\t# CD (builtin)";
+        let expected = Ok((vec![0x41, 0x42], MappedString::Extra("CD".into())));
+        let result = decode_entry(line, &regex2, &regex3, &regex4, &keysymdef, &location());
 
         assert_eq!(result, expected);
     }
@@ -179,11 +277,11 @@ mod tests {
         let regex2 = Regex::new(COMPOSE_REGEX_2_STR).unwrap();
         let regex3 = Regex::new(COMPOSE_REGEX_3_STR).unwrap();
         let regex4 = Regex::new(COMPOSE_REGEX_4_STR).unwrap();
-        let keysymdef = KeySymDef::new(KEYSYMDEF).unwrap(); // Assuming a constructor for simplicity
+        let keysymdef = KeySymDef::parse(&read_compressed(KEYSYMDEF).unwrap()).unwrap();
 
         let line = "<Multi_key> <A> <B> <C> : \"D\".";
-        let expected = Ok(("ABC".to_string(), MappedString::Basic("D".into())));
-        let result = decode_entry(line, &regex2, &regex3, &regex4, &keysymdef);
+        let expected = Ok((vec![0x41, 0x42, 0x43], MappedString::Basic("D".into())));
+        let result = decode_entry(line, &regex2, &regex3, &regex4, &keysymdef, &location());
 
         assert_eq!(result, expected);
     }
@@ -193,26 +291,109 @@ mod tests {
         let regex2 = Regex::new(COMPOSE_REGEX_2_STR).unwrap();
         let regex3 = Regex::new(COMPOSE_REGEX_3_STR).unwrap();
         let regex4 = Regex::new(COMPOSE_REGEX_4_STR).unwrap();
-        let keysymdef = KeySymDef::new(KEYSYMDEF).unwrap(); // Assuming a constructor for simplicity
+        let keysymdef = KeySymDef::parse(&read_compressed(KEYSYMDEF).unwrap()).unwrap();
 
         let line = "<Multi_key> <A> <B> <C> <D> : \"E\".";
-        let expected = Ok(("ABCD".to_string(), MappedString::Basic("E".into())));
-        let result = decode_entry(line, &regex2, &regex3, &regex4, &keysymdef);
+        let expected = Ok((
+            vec![0x41, 0x42, 0x43, 0x44],
+            MappedString::Basic("E".into()),
+        ));
+        let result = decode_entry(line, &regex2, &regex3, &regex4, &keysymdef, &location());
 
         assert_eq!(result, expected);
     }
 
     #[test]
     fn test_get_compose_def() {
-        let keysymdef = KeySymDef::new(KEYSYMDEF).unwrap(); // Assuming a constructor for simplicity
-        let map = get_compose_def(&keysymdef, COMPOSEDEF).unwrap();
+        let keysymdef = KeySymDef::parse(&read_compressed(KEYSYMDEF).unwrap()).unwrap();
+        let mut report = RebuildReport::default();
+        let content = read_compressed(COMPOSEDEF).unwrap();
+        let map = get_compose_def(&keysymdef, COMPOSEDEF, &content, &mut report).unwrap();
+        let get = |sequence: &[u32]| map.get(sequence).map(|(value, _)| value);
+
+        let wkwk = vec![0x77, 0x6b, 0x77, 0x6b];
+        let pr = vec![0x70, 0x72];
+        assert!(map.contains_key(&wkwk));
+        assert_eq!(get(&wkwk).unwrap(), &MappedString::Basic("🤣".into()));
+        assert_eq!(map[&wkwk].1, SequenceSource::Builtin);
+        assert!(map.contains_key(&pr));
+        assert_eq!(get(&pr).unwrap(), &MappedString::Extra("peradaban".into()));
+
+        // Rules using non-character keysyms and implicit Unicode names
+        let dead_tilde = vec![0xfe53, 0xfe53];
+        let kp_divide = vec![0xffaf, 0x4f];
+        let hamza_alef = vec![0x1000654, 0x1000627];
+        assert_eq!(get(&dead_tilde), Some(&MappedString::Basic("≈".into())));
+        assert_eq!(get(&kp_divide), Some(&MappedString::Basic("Ø".into())));
+        assert_eq!(get(&hamza_alef), Some(&MappedString::Basic("أ".into())));
+        assert_eq!(map[&dead_tilde].1, SequenceSource::Compose);
 
-        assert!(map.contains_key("wkwk"));
-        assert_eq!(map.get("wkwk").unwrap(), &MappedString::Basic("🤣".into()));
-        assert!(map.contains_key("pr"));
+        // Escaped results
+        let slash_slash = vec![0x2f, 0x2f];
+        assert_eq!(get(&slash_slash), Some(&MappedString::Basic("\\".into())));
+    }
+
+    #[test]
+    fn test_escape_roundtrip() {
+        let regex = Regex::new(COMPOSE_REGEX_2_STR).unwrap();
+        for value in ["a", "\"", "\\", "\\\"", "«\"»"] {
+            let line = format!("<Multi_key> <a> <b> : \"{}\" # test", escape(value));
+            let caps = regex.captures(&line).unwrap();
+            assert_eq!(unescape(&caps[3]).unwrap(), value);
+        }
+    }
+
+    #[test]
+    fn test_unescape() {
+        assert_eq!(unescape(r"\x41\102\x2192").unwrap(), "AB\u{21}92");
+        assert_eq!(unescape(r"\n"), Err(r"\n".to_string()));
+        assert_eq!(unescape(r"\xZ"), Err(r"\x".to_string()));
+        assert_eq!(unescape("a\\"), Err("\\".to_string()));
+    }
+
+    #[test]
+    fn test_report() {
+        let keysymdef = KeySymDef::parse(&read_compressed(KEYSYMDEF).unwrap()).unwrap();
+        let content = "\
+include \"%L\"
+<Multi_key> <A> <B> : \"C\"
+<Multi_key> <A> <nosuchkey> : \"D\"
+<Multi_key> <A> : \"E\"
+<Multi_key> <A> <C> : \"\\q\"
+<dead_acute> <a> : \"á\"
+# <Multi_key> comment
+";
+        let mut report = RebuildReport::default();
+        let map = parse_compose_def(&keysymdef, "Compose", content, &mut report).unwrap();
+        let at = |line| SourceLocation {
+            file: "Compose".into(),
+            line,
+        };
+
+        assert_eq!(map.len(), 1);
+        assert_eq!(
+            report.warnings,
+            [DefinitionError::IncludeFailed {
+                location: at(1),
+                path: "\"%L\"".into()
+            }]
+        );
         assert_eq!(
-            map.get("pr").unwrap(),
-            &MappedString::Extra("peradaban".into())
+            report.skipped,
+            [
+                DefinitionError::UnknownKeysym {
+                    location: at(3),
+                    name: "nosuchkey".into()
+                },
+                DefinitionError::MalformedLine {
+                    location: at(4),
+                    text: "<Multi_key> <A> : \"E\"".into()
+                },
+                DefinitionError::BadEscape {
+                    location: at(5),
+                    escape: "\\q".into()
+                },
+            ]
         );
     }
 }
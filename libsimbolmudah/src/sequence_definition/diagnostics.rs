@@ -0,0 +1,259 @@
+use std::{
+    fmt::Display,
+    io::Read,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use windows::{
+    core::{Error, HSTRING},
+    Win32::Foundation::E_FAIL,
+};
+
+use crate::bindings;
+
+/// A line of a source file, counted from 1.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct SourceLocation {
+    pub(super) file: Arc<str>,
+    pub(super) line: usize,
+}
+
+impl Display for SourceLocation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}", self.file, self.line)
+    }
+}
+
+/// A problem found while reading the files given to `Rebuild`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) enum DefinitionError {
+    /// A key name that keysymdef.h does not list and that is not of the form `U00E9`.
+    UnknownKeysym {
+        location: SourceLocation,
+        name: Box<str>,
+    },
+    /// A line that starts like a rule but cannot be read as one.
+    MalformedLine {
+        location: SourceLocation,
+        text: Box<str>,
+    },
+    /// An escape in a result that Compose files do not define, e.g. `\q`.
+    BadEscape {
+        location: SourceLocation,
+        escape: Box<str>,
+    },
+    /// An `include` line. Included files are not read.
+    IncludeFailed {
+        location: SourceLocation,
+        path: Box<str>,
+    },
+    /// A search language that has no annotations, not even through its parents.
+    LocaleMissing { locale: Box<str> },
+    /// A file that cannot be read.
+    Io { path: Box<str>, message: Box<str> },
+}
+
+impl DefinitionError {
+    pub(super) fn io(path: &str, error: impl std::error::Error) -> Self {
+        Self::Io {
+            path: path.into(),
+            message: error.to_string().into(),
+        }
+    }
+
+    pub(super) fn location(&self) -> Option<&SourceLocation> {
+        match self {
+            Self::UnknownKeysym { location, .. }
+            | Self::MalformedLine { location, .. }
+            | Self::BadEscape { location, .. }
+            | Self::IncludeFailed { location, .. } => Some(location),
+            Self::LocaleMissing { .. } | Self::Io { .. } => None,
+        }
+    }
+
+    /// What went wrong, without the location.
+    pub(super) fn message(&self) -> String {
+        match self {
+            Self::UnknownKeysym { name, .. } => format!("Unknown keysym <{name}>"),
+            Self::MalformedLine { text, .. } => format!("Malformed line: {text}"),
+            Self::BadEscape { escape, .. } => format!("Unsupported escape {escape}"),
+            Self::IncludeFailed { path, .. } => format!("Include of {path} is not supported"),
+            Self::LocaleMissing { locale } => format!("No annotations for language {locale}"),
+            Self::Io { path, message } => format!("Cannot read {path}: {message}"),
+        }
+    }
+}
+
+impl DefinitionError {
+    /// The error as `GetRebuildReport` lists it.
+    pub(super) fn to_diagnostic(&self, skipped: bool) -> bindings::RebuildDiagnostic {
+        bindings::RebuildDiagnostic {
+            kind: match self {
+                Self::UnknownKeysym { .. } => bindings::DiagnosticKind::UnknownKeysym,
+                Self::MalformedLine { .. } => bindings::DiagnosticKind::MalformedLine,
+                Self::BadEscape { .. } => bindings::DiagnosticKind::BadEscape,
+                Self::IncludeFailed { .. } => bindings::DiagnosticKind::IncludeFailed,
+                Self::LocaleMissing { .. } => bindings::DiagnosticKind::LocaleMissing,
+                Self::Io { .. } => bindings::DiagnosticKind::Io,
+            },
+            skipped,
+            file: self
+                .location()
+                .map(|l| HSTRING::from(l.file.as_ref()))
+                .unwrap_or_default(),
+            line: self.location().map_or(0, |l| l.line as u32),
+            message: self.message().into(),
+        }
+    }
+}
+
+impl Display for DefinitionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.location() {
+            Some(location) => write!(f, "{location}: {}", self.message()),
+            None => write!(f, "{}", self.message()),
+        }
+    }
+}
+
+impl From<DefinitionError> for Error {
+    fn from(error: DefinitionError) -> Self {
+        Error::new(E_FAIL, error.to_string())
+    }
+}
+
+/// Reads a brotli-compressed text file.
+pub(super) fn read_compressed(path: &str) -> Result<String, DefinitionError> {
+    let mut file = std::fs::File::open(path).map_err(|e| DefinitionError::io(path, e))?;
+    let mut input = brotli_decompressor::Decompressor::new(&mut file, 4096);
+    let mut buf = String::new();
+    input
+        .read_to_string(&mut buf)
+        .map_err(|e| DefinitionError::io(path, e))?;
+    Ok(buf)
+}
+
+/// What the last `Rebuild` left out or found suspicious, and what it loaded.
+#[derive(Debug, Default)]
+pub(super) struct RebuildReport {
+    /// Lines left out of the definition.
+    pub(super) skipped: Vec<DefinitionError>,
+    /// Problems that did not drop any sequence.
+    pub(super) warnings: Vec<DefinitionError>,
+    /// Sequences loaded from each source, by source name.
+    pub(super) sequences: Vec<(&'static str, usize)>,
+    /// Locales whose annotations were loaded, in search order.
+    pub(super) locales: Vec<String>,
+    /// Time spent in each phase, in the order they ran.
+    pub(super) phases: Vec<(RebuildPhase, Duration)>,
+    /// Approximate size of each table in bytes, by table name.
+    pub(super) tables: Vec<(&'static str, usize)>,
+}
+
+/// A step of `Rebuild`, timed separately.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum RebuildPhase {
+    /// Reading and decompressing keysymdef.h and the Compose file.
+    Decompress,
+    /// Reading keysyms and Compose rules.
+    Parse,
+    /// Loading CLDR annotations, shortcodes and emoji data.
+    Annotations,
+    /// Looking up the Unicode names of single-character results.
+    NameLookup,
+    /// Building the sequence FST and the tables that go with it.
+    FstBuild,
+}
+
+impl From<RebuildPhase> for bindings::RebuildPhase {
+    fn from(phase: RebuildPhase) -> Self {
+        match phase {
+            RebuildPhase::Decompress => Self::Decompress,
+            RebuildPhase::Parse => Self::Parse,
+            RebuildPhase::Annotations => Self::Annotations,
+            RebuildPhase::NameLookup => Self::NameLookup,
+            RebuildPhase::FstBuild => Self::FstBuild,
+        }
+    }
+}
+
+/// Measures the phases of a rebuild one after another.
+pub(super) struct PhaseTimer {
+    last: Instant,
+}
+
+impl PhaseTimer {
+    pub(super) fn start() -> Self {
+        Self {
+            last: Instant::now(),
+        }
+    }
+
+    /// Adds the time since the previous phase ended to `phase`.
+    pub(super) fn finish(&mut self, phase: RebuildPhase, report: &mut RebuildReport) {
+        let now = Instant::now();
+        let elapsed = now - self.last;
+        self.last = now;
+
+        match report.phases.iter_mut().find(|(p, _)| *p == phase) {
+            Some((_, total)) => *total += elapsed,
+            None => report.phases.push((phase, elapsed)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display() {
+        let location = SourceLocation {
+            file: "Compose.pre".into(),
+            line: 12,
+        };
+
+        assert_eq!(
+            DefinitionError::UnknownKeysym {
+                location: location.clone(),
+                name: "foo".into()
+            }
+            .to_string(),
+            "Compose.pre:12: Unknown keysym <foo>"
+        );
+        assert_eq!(
+            DefinitionError::BadEscape {
+                location,
+                escape: "\\q".into()
+            }
+            .message(),
+            "Unsupported escape \\q"
+        );
+        assert_eq!(
+            DefinitionError::LocaleMissing {
+                locale: "tlh".into()
+            }
+            .to_string(),
+            "No annotations for language tlh"
+        );
+    }
+
+    #[test]
+    fn test_phase_timer() {
+        let mut report = RebuildReport::default();
+        let mut timer = PhaseTimer::start();
+
+        timer.finish(RebuildPhase::Decompress, &mut report);
+        timer.finish(RebuildPhase::Parse, &mut report);
+        std::thread::sleep(Duration::from_millis(2));
+        timer.finish(RebuildPhase::Decompress, &mut report);
+
+        // A phase that runs twice is counted once, in the order it first ran
+        assert_eq!(
+            report.phases.iter().map(|(p, _)| *p).collect::<Vec<_>>(),
+            [RebuildPhase::Decompress, RebuildPhase::Parse]
+        );
+        assert!(report.phases[0].1 >= Duration::from_millis(2));
+    }
+}
@@ -0,0 +1,234 @@
+use std::collections::HashMap;
+
+use super::emoji_data::{without_variation_selectors, EmojiData};
+
+/// Skin tones as CLDR names them, from light to dark.
+pub(super) const SKIN_TONES: [&str; 5] = [
+    "light skin tone",
+    "medium-light skin tone",
+    "medium skin tone",
+    "medium-dark skin tone",
+    "dark skin tone",
+];
+
+/// Hair components as CLDR names them.
+const HAIR_STYLES: [&str; 5] = ["red hair", "curly hair", "white hair", "bald", "blond hair"];
+
+/// Prefixes of the gendered forms of an emoji named `person …`.
+const GENDERS: [&str; 2] = ["man ", "woman "];
+
+/// A base emoji and its variants, in file order.
+#[derive(Debug)]
+struct Family {
+    base: String,
+    variants: Vec<String>,
+}
+
+/// Where a variant belongs.
+#[derive(Debug)]
+struct Member {
+    /// Key of the family in `EmojiFamilies::families`.
+    family: String,
+    /// 1 to 5, following `SKIN_TONES`. Multi-person emoji keep the tone of their first person.
+    skin_tone: Option<u8>,
+    /// Whether the skin tone is all that sets it apart from the base.
+    tone_only: bool,
+}
+
+/// Base emoji with their skin tone, gender and hair variants. Both maps are keyed by the emoji
+/// without variation selectors, as annotations leave them out.
+#[derive(Debug, Default)]
+pub(super) struct EmojiFamilies {
+    families: HashMap<String, Family>,
+    members: HashMap<String, Member>,
+}
+
+impl EmojiFamilies {
+    /// Groups the fully-qualified emoji of `emoji-test.txt` by base. CLDR names a variant after
+    /// its base in the same subgroup, e.g. `man running: light skin tone` for `person running`,
+    /// with the skin tones and hair styles as qualifiers. Bases without variants are left out.
+    pub(super) fn build(emoji_data: &EmojiData) -> Self {
+        let mut by_name = HashMap::new();
+        for value in emoji_data.fully_qualified_values() {
+            if let Some(info) = emoji_data.get(value) {
+                by_name.insert((info.subgroup.as_ref(), info.name.as_ref()), value);
+            }
+        }
+
+        let mut result = Self::default();
+        for value in emoji_data.fully_qualified_values() {
+            let Some(info) = emoji_data.get(value) else {
+                continue;
+            };
+            let lookup = |name: &str| by_name.get(&(info.subgroup.as_ref(), name)).copied();
+
+            let (stem, qualifiers) = match info.name.split_once(": ") {
+                Some((stem, qualifiers)) => (stem, qualifiers.split(", ").collect()),
+                None => (info.name.as_ref(), Vec::new()),
+            };
+            let ungendered = GENDERS
+                .iter()
+                .find_map(|gender| stem.strip_prefix(gender))
+                .map(|rest| format!("person {rest}"))
+                .filter(|name| lookup(name).is_some());
+            let tones = qualifiers
+                .iter()
+                .filter_map(|q| SKIN_TONES.iter().position(|tone| tone == q))
+                .collect::<Vec<_>>();
+            // Qualifiers that are part of the base, such as the people of `kiss: woman, man`
+            let kept = qualifiers
+                .iter()
+                .filter(|q| !SKIN_TONES.contains(q) && !HAIR_STYLES.contains(q))
+                .copied()
+                .collect::<Vec<_>>();
+
+            let stem = ungendered.as_deref().unwrap_or(stem);
+            let base_name = if kept.is_empty() {
+                stem.to_string()
+            } else {
+                format!("{stem}: {}", kept.join(", "))
+            };
+            let Some(base) = lookup(&base_name).filter(|&base| base != value) else {
+                continue;
+            };
+
+            let family = without_variation_selectors(base);
+            result.members.insert(
+                without_variation_selectors(value),
+                Member {
+                    family: family.clone(),
+                    skin_tone: tones.first().map(|&tone| tone as u8 + 1),
+                    tone_only: ungendered.is_none()
+                        && tones.len() + kept.len() == qualifiers.len()
+                        && tones.windows(2).all(|pair| pair[0] == pair[1]),
+                },
+            );
+            result
+                .families
+                .entry(family)
+                .or_insert_with(|| Family {
+                    base: base.to_string(),
+                    variants: Vec::new(),
+                })
+                .variants
+                .push(value.to_string());
+        }
+        result
+    }
+
+    /// Base of an emoji, if it belongs to a family.
+    pub(super) fn base_of(&self, value: &str) -> Option<&str> {
+        let key = without_variation_selectors(value);
+        let family = self
+            .members
+            .get(&key)
+            .map_or(key.as_str(), |member| member.family.as_str());
+        self.families.get(family).map(|family| family.base.as_str())
+    }
+
+    /// Variants of a base emoji, in file order.
+    pub(super) fn variants(&self, base: &str) -> impl Iterator<Item = &str> {
+        self.families
+            .get(&without_variation_selectors(base))
+            .into_iter()
+            .flat_map(|family| family.variants.iter().map(|value| value.as_str()))
+    }
+
+    /// Skin tone of a variant, 1 to 5.
+    pub(super) fn skin_tone(&self, value: &str) -> Option<u8> {
+        self.members
+            .get(&without_variation_selectors(value))?
+            .skin_tone
+    }
+
+    /// The variant of a base emoji that only changes its skin tone.
+    pub(super) fn with_skin_tone(&self, base: &str, skin_tone: u8) -> Option<&str> {
+        self.variants(base).find(|value| {
+            self.members
+                .get(&without_variation_selectors(value))
+                .is_some_and(|member| member.tone_only && member.skin_tone == Some(skin_tone))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EMOJI_TEST: &str = "\
+# group: People & Body
+
+# subgroup: hand-fingers-closed
+1F44D                                                  ; fully-qualified     # 👍 E0.6 thumbs up
+1F44D 1F3FB                                            ; fully-qualified     # 👍🏻 E1.0 thumbs up: light skin tone
+1F44D 1F3FF                                            ; fully-qualified     # 👍🏿 E1.0 thumbs up: dark skin tone
+
+# subgroup: person
+1F9D1                                                  ; fully-qualified     # 🧑 E5.0 person
+1F9D1 1F3FB 200D 1F9B0                                 ; fully-qualified     # 🧑🏻‍🦰 E12.1 person: light skin tone, red hair
+1F468                                                  ; fully-qualified     # 👨 E0.6 man
+
+# subgroup: person-activity
+1F3C3                                                  ; fully-qualified     # 🏃 E0.6 person running
+1F3C3 1F3FD                                            ; fully-qualified     # 🏃🏽 E1.0 person running: medium skin tone
+1F3C3 200D 2642 FE0F                                   ; fully-qualified     # 🏃‍♂️ E4.0 man running
+1F3C3 200D 2642                                        ; minimally-qualified # 🏃‍♂ E4.0 man running
+1F3C3 1F3FD 200D 2642 FE0F                             ; fully-qualified     # 🏃🏽‍♂️ E4.0 man running: medium skin tone
+
+# subgroup: family
+1F48F                                                  ; fully-qualified     # 💏 E0.6 kiss
+1F469 200D 2764 FE0F 200D 1F48B 200D 1F468             ; fully-qualified     # 👩‍❤️‍💋‍👨 E2.0 kiss: woman, man
+1F48F 1F3FB                                            ; fully-qualified     # 💏🏻 E13.1 kiss: light skin tone
+1F469 1F3FB 200D 2764 FE0F 200D 1F48B 200D 1F468 1F3FB ; fully-qualified     # 👩🏻‍❤️‍💋‍👨🏻 E13.1 kiss: woman, man, light skin tone
+1F468 200D 1F469 200D 1F467                            ; fully-qualified     # 👨‍👩‍👧 E2.0 family: man, woman, girl
+
+# group: Symbols
+
+# subgroup: arrow
+2194 FE0F                                              ; fully-qualified     # ↔️ E0.6 left-right arrow
+";
+
+    #[test]
+    fn test_families() {
+        let data = EmojiData::parse(EMOJI_TEST).unwrap();
+        let families = EmojiFamilies::build(&data);
+
+        assert_eq!(families.base_of("👍🏿"), Some("👍"));
+        assert_eq!(families.base_of("👍"), Some("👍"));
+        assert_eq!(families.variants("👍").collect::<Vec<_>>(), ["👍🏻", "👍🏿"]);
+        assert_eq!(
+            families.variants("🏃").collect::<Vec<_>>(),
+            ["🏃🏽", "🏃‍♂️", "🏃🏽‍♂️"]
+        );
+        // Annotations leave out variation selectors
+        assert_eq!(families.base_of("🏃‍♂"), Some("🏃"));
+
+        // Hair and skin tone qualify the base they are named after
+        assert_eq!(families.base_of("🧑🏻‍🦰"), Some("🧑"));
+        assert_eq!(families.base_of("👨"), None);
+
+        // Qualifiers other than skin tones and hair are part of the base
+        assert_eq!(families.base_of("👩🏻‍❤️‍💋‍👨🏻"), Some("👩‍❤️‍💋‍👨"));
+        assert_eq!(families.base_of("👩‍❤️‍💋‍👨"), Some("👩‍❤️‍💋‍👨"));
+        assert_eq!(families.base_of("💏🏻"), Some("💏"));
+        assert_eq!(families.base_of("👨‍👩‍👧"), None);
+        assert_eq!(families.base_of("↔️"), None);
+    }
+
+    #[test]
+    fn test_skin_tone() {
+        let data = EmojiData::parse(EMOJI_TEST).unwrap();
+        let families = EmojiFamilies::build(&data);
+
+        assert_eq!(families.skin_tone("👍🏿"), Some(5));
+        assert_eq!(families.skin_tone("🏃🏽‍♂️"), Some(3));
+        assert_eq!(families.skin_tone("🏃‍♂️"), None);
+        assert_eq!(families.skin_tone("👍"), None);
+
+        assert_eq!(families.with_skin_tone("🏃", 3), Some("🏃🏽"));
+        assert_eq!(families.with_skin_tone("👍", 1), Some("👍🏻"));
+        assert_eq!(families.with_skin_tone("👍", 3), None);
+        assert_eq!(families.with_skin_tone("🧑", 1), None);
+        assert_eq!(families.with_skin_tone("↔️", 1), None);
+    }
+}
@@ -0,0 +1,265 @@
+use std::{collections::HashMap, io::Read, sync::Arc};
+
+use crate::utils::functions::{fail, fail_message};
+
+/// Status of an emoji sequence in `emoji-test.txt`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum Qualification {
+    Component,
+    FullyQualified,
+    MinimallyQualified,
+    Unqualified,
+}
+
+impl Qualification {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "component" => Some(Self::Component),
+            "fully-qualified" => Some(Self::FullyQualified),
+            "minimally-qualified" => Some(Self::MinimallyQualified),
+            "unqualified" => Some(Self::Unqualified),
+            _ => None,
+        }
+    }
+}
+
+/// Group of the skin tone and hair swatches, which are not meant to be used on their own.
+pub(super) const COMPONENT_GROUP: &str = "Component";
+
+/// What `emoji-test.txt` says about one emoji.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct EmojiInfo {
+    pub(super) group: Arc<str>,
+    pub(super) subgroup: Arc<str>,
+    /// Emoji version that introduced it, e.g. `13.0`.
+    pub(super) version: Box<str>,
+    pub(super) qualification: Qualification,
+    pub(super) name: Box<str>,
+}
+
+/// Groups, versions and qualification status of every emoji.
+#[derive(Debug, Default)]
+pub(super) struct EmojiData {
+    by_value: HashMap<String, EmojiInfo>,
+    /// Fully-qualified form of each emoji, keyed by the emoji without variation selectors.
+    fully_qualified: HashMap<String, String>,
+    /// Fully-qualified emoji, in file order.
+    ordered: Vec<String>,
+    /// Groups with their subgroups, in file order.
+    groups: Vec<(Arc<str>, Vec<Arc<str>>)>,
+}
+
+impl EmojiData {
+    /// Loads the compressed `emoji-test.txt`. A missing file gives no emoji data.
+    pub(super) fn load(path: &str) -> windows_core::Result<Self> {
+        let Ok(mut file) = std::fs::File::open(path) else {
+            return Ok(Self::default());
+        };
+        let mut input = brotli_decompressor::Decompressor::new(&mut file, 4096);
+        let mut buf = String::new();
+        let _num = input.read_to_string(&mut buf).map_err(fail)?;
+        Self::parse(&buf)
+    }
+
+    /// Reads lines such as
+    /// `263A FE0F ; fully-qualified # ☺️ E0.6 smiling face`
+    /// under the preceding `# group:` and `# subgroup:` comments.
+    pub(super) fn parse(text: &str) -> windows_core::Result<Self> {
+        let mut result = Self::default();
+        let mut group: Arc<str> = Arc::from("");
+        let mut subgroup: Arc<str> = Arc::from("");
+
+        for line in text.lines().map(str::trim) {
+            if let Some(name) = line.strip_prefix("# group:") {
+                group = Arc::from(name.trim());
+                result.groups.push((group.clone(), Vec::new()));
+                continue;
+            }
+            if let Some(name) = line.strip_prefix("# subgroup:") {
+                subgroup = Arc::from(name.trim());
+                if let Some((_, subgroups)) = result.groups.last_mut() {
+                    subgroups.push(subgroup.clone());
+                }
+                continue;
+            }
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let invalid = || fail_message(&format!("Invalid emoji-test line: {line}"));
+            let (fields, comment) = line.split_once('#').ok_or_else(invalid)?;
+            let (codepoints, status) = fields.split_once(';').ok_or_else(invalid)?;
+            let value = codepoints
+                .split_whitespace()
+                .map(|cp| {
+                    u32::from_str_radix(cp, 16)
+                        .ok()
+                        .and_then(char::from_u32)
+                        .ok_or_else(invalid)
+                })
+                .collect::<windows_core::Result<String>>()?;
+            let qualification = Qualification::from_name(status.trim()).ok_or_else(invalid)?;
+            // The comment repeats the emoji, then gives its version and name
+            let mut comment = comment.trim().splitn(3, ' ');
+            let version = comment
+                .nth(1)
+                .and_then(|v| v.strip_prefix('E'))
+                .ok_or_else(invalid)?;
+            let name = comment.next().unwrap_or_default();
+
+            if qualification == Qualification::FullyQualified {
+                result
+                    .fully_qualified
+                    .insert(without_variation_selectors(&value), value.clone());
+                result.ordered.push(value.clone());
+            }
+            result.by_value.insert(
+                value,
+                EmojiInfo {
+                    group: group.clone(),
+                    subgroup: subgroup.clone(),
+                    version: version.into(),
+                    qualification,
+                    name: name.into(),
+                },
+            );
+        }
+
+        Ok(result)
+    }
+
+    pub(super) fn get(&self, value: &str) -> Option<&EmojiInfo> {
+        self.by_value.get(value)
+    }
+
+    /// Fully-qualified form of an emoji, which may add or keep variation selectors.
+    pub(super) fn fully_qualified(&self, value: &str) -> Option<&str> {
+        self.fully_qualified
+            .get(&without_variation_selectors(value))
+            .map(|value| value.as_str())
+    }
+
+    /// Every fully-qualified emoji, in file order.
+    pub(super) fn fully_qualified_values(&self) -> impl Iterator<Item = &str> {
+        self.ordered.iter().map(|value| value.as_str())
+    }
+
+    /// Fully-qualified emoji of a group, in file order.
+    pub(super) fn group_members<'a>(&'a self, group: &'a str) -> impl Iterator<Item = &'a str> {
+        self.fully_qualified_values()
+            .filter(move |value| self.by_value[*value].group.as_ref() == group)
+    }
+
+    /// Group names, in file order.
+    pub(super) fn groups(&self) -> impl Iterator<Item = &str> {
+        self.groups.iter().map(|(group, _)| group.as_ref())
+    }
+}
+
+pub(super) fn without_variation_selectors(value: &str) -> String {
+    value
+        .chars()
+        .filter(|&c| c != '\u{fe0e}' && c != '\u{fe0f}')
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EMOJI_TEST: &str = "\
+# emoji-test.txt
+# Version: 15.1
+
+# group: Smileys & Emotion
+
+# subgroup: face-smiling
+1F600                                                  ; fully-qualified     # 😀 E1.0 grinning face
+
+# subgroup: face-affection
+263A FE0F                                              ; fully-qualified     # ☺️ E0.6 smiling face
+263A                                                   ; unqualified         # ☺ E0.6 smiling face
+
+# Smileys & Emotion subtotal:		3
+
+# group: People & Body
+
+# subgroup: hand-fingers-closed
+1F44D 1F3FD                                            ; fully-qualified     # 👍🏽 E1.0 thumbs up: medium skin tone
+
+# subgroup: person-activity
+1F3C3 200D 2642 FE0F                                   ; fully-qualified     # 🏃‍♂️ E4.0 man running
+1F3C3 200D 2642                                        ; minimally-qualified # 🏃‍♂ E4.0 man running
+
+# group: Component
+
+# subgroup: skin-tone
+1F3FB                                                  ; component           # 🏻 E1.0 light skin tone
+";
+
+    #[test]
+    fn test_parse() {
+        let data = EmojiData::parse(EMOJI_TEST).unwrap();
+
+        assert_eq!(
+            data.get("😀"),
+            Some(&EmojiInfo {
+                group: "Smileys & Emotion".into(),
+                subgroup: "face-smiling".into(),
+                version: "1.0".into(),
+                qualification: Qualification::FullyQualified,
+                name: "grinning face".into(),
+            })
+        );
+        assert_eq!(
+            data.get("🏃‍♂").map(|info| info.qualification),
+            Some(Qualification::MinimallyQualified)
+        );
+        assert_eq!(
+            data.get("👍🏽").map(|info| info.name.as_ref()),
+            Some("thumbs up: medium skin tone")
+        );
+        assert_eq!(
+            data.get("🏻").map(|info| info.qualification),
+            Some(Qualification::Component)
+        );
+        assert_eq!(data.get("→"), None);
+        assert_eq!(
+            data.groups().collect::<Vec<_>>(),
+            ["Smileys & Emotion", "People & Body", "Component"]
+        );
+    }
+
+    #[test]
+    fn test_fully_qualified() {
+        let data = EmojiData::parse(EMOJI_TEST).unwrap();
+
+        assert_eq!(data.fully_qualified("☺"), Some("☺️"));
+        assert_eq!(data.fully_qualified("☺️"), Some("☺️"));
+        assert_eq!(data.fully_qualified("🏃‍♂"), Some("🏃‍♂️"));
+        assert_eq!(data.fully_qualified("😀"), Some("😀"));
+        assert_eq!(data.fully_qualified("→"), None);
+    }
+
+    #[test]
+    fn test_group_members() {
+        let data = EmojiData::parse(EMOJI_TEST).unwrap();
+
+        assert_eq!(
+            data.group_members("Smileys & Emotion").collect::<Vec<_>>(),
+            ["😀", "☺️"]
+        );
+        assert_eq!(
+            data.group_members("People & Body").collect::<Vec<_>>(),
+            ["👍🏽", "🏃‍♂️"]
+        );
+        assert_eq!(data.group_members("Flags").count(), 0);
+    }
+
+    #[test]
+    fn test_invalid() {
+        assert!(EmojiData::parse("1F600 ; fully-qualified\n").is_err());
+        assert!(EmojiData::parse("1F600 ; qualified # 😀 E1.0 grinning face\n").is_err());
+        assert!(EmojiData::parse("ZZZZ ; fully-qualified # 😀 E1.0 grinning face\n").is_err());
+    }
+}
@@ -0,0 +1,249 @@
+use std::{collections::HashMap, fmt::Write};
+
+use serde::Serialize;
+
+use super::{
+    compose_reader::{escape, SequenceSource},
+    keysym_reader::keysym_to_char,
+    keysym_sequence,
+};
+use crate::utils::functions::fail;
+
+/// A sequence of the active table with everything needed to write it out.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct ExportEntry {
+    pub(super) sequence: Vec<u32>,
+    pub(super) result: String,
+    pub(super) description: String,
+    pub(super) source: SequenceSource,
+}
+
+/// Writes the entries as Compose rules that the Compose reader accepts back.
+/// The description and source are kept in the trailing comment.
+pub(super) fn to_xcompose(entries: &[ExportEntry], names: &HashMap<u32, Box<str>>) -> String {
+    let mut result = String::new();
+    for entry in entries {
+        result.push_str("<Multi_key>");
+        for &keysym in &entry.sequence {
+            let _ = write!(result, " <{}>", keysym_name(keysym, names));
+        }
+        let _ = writeln!(
+            result,
+            "\t: \"{}\"\t# {} ({})",
+            escape(&entry.result),
+            entry.description,
+            entry.source.as_str()
+        );
+    }
+    result
+}
+
+/// An entry as written to JSON.
+#[derive(Serialize)]
+struct JsonEntry<'a> {
+    sequence: String,
+    keys: Vec<String>,
+    result: &'a str,
+    description: &'a str,
+    source: &'static str,
+}
+
+/// Writes the entries as a JSON array of objects, one per line.
+pub(super) fn to_json(
+    entries: &[ExportEntry],
+    names: &HashMap<u32, Box<str>>,
+) -> windows_core::Result<String> {
+    let mut result = String::from("[");
+    for (i, entry) in entries.iter().enumerate() {
+        if i > 0 {
+            result.push(',');
+        }
+        let entry = JsonEntry {
+            sequence: keysym_sequence::to_display(&entry.sequence, names),
+            keys: entry
+                .sequence
+                .iter()
+                .map(|&keysym| keysym_name(keysym, names))
+                .collect(),
+            result: &entry.result,
+            description: &entry.description,
+            source: entry.source.as_str(),
+        };
+        result.push_str("\n  ");
+        result.push_str(&serde_json::to_string(&entry).map_err(fail)?);
+    }
+    result.push_str("\n]\n");
+    Ok(result)
+}
+
+/// Writes the entries as CSV with a header row.
+pub(super) fn to_csv(entries: &[ExportEntry], names: &HashMap<u32, Box<str>>) -> String {
+    let mut result = String::from("sequence,result,description,source\r\n");
+    for entry in entries {
+        let _ = write!(
+            result,
+            "{},{},{},{}\r\n",
+            csv_field(&keysym_sequence::to_display(&entry.sequence, names)),
+            csv_field(&entry.result),
+            csv_field(&entry.description),
+            entry.source.as_str()
+        );
+    }
+    result
+}
+
+/// Name of a keysym as written in Compose files. Characters without a named keysym use the
+/// implicit `Uxxxx` name.
+fn keysym_name(keysym: u32, names: &HashMap<u32, Box<str>>) -> String {
+    if let Some(name) = names.get(&keysym) {
+        name.to_string()
+    } else if let Some(c) = keysym_to_char(keysym) {
+        format!("U{:04X}", c as u32)
+    } else {
+        format!("{:#x}", keysym)
+    }
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\r', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sequence_definition::{
+        compose_reader::parse_compose_def,
+        diagnostics::{read_compressed, RebuildReport},
+        keysym_reader::KeySymDef,
+        mapped_string::MappedString,
+    };
+
+    const KEYSYMDEF: &str = "x11-defs/keysymdef.h.br";
+
+    fn entries() -> Vec<ExportEntry> {
+        vec![
+            ExportEntry {
+                sequence: vec![0xfe53, 0xfe53],
+                result: "≈".into(),
+                description: "ALMOST EQUAL TO".into(),
+                source: SequenceSource::Compose,
+            },
+            ExportEntry {
+                sequence: vec![0x2f, 0x2f],
+                result: "\\".into(),
+                description: "REVERSE SOLIDUS".into(),
+                source: SequenceSource::Compose,
+            },
+            ExportEntry {
+                sequence: vec![0x22, 0x1002192],
+                result: "\"→\"".into(),
+                description: "quoted, arrow".into(),
+                source: SequenceSource::Builtin,
+            },
+            ExportEntry {
+                sequence: vec![0x70, 0x72],
+                result: "peradaban".into(),
+                description: "peradaban".into(),
+                source: SequenceSource::Builtin,
+            },
+        ]
+    }
+
+    fn names() -> HashMap<u32, Box<str>> {
+        HashMap::from([
+            (0xfe53, "dead_tilde".into()),
+            (0x2f, "slash".into()),
+            (0x22, "quotedbl".into()),
+            (0x70, "p".into()),
+            (0x72, "r".into()),
+        ])
+    }
+
+    #[test]
+    fn test_xcompose() {
+        let output = to_xcompose(&entries(), &names());
+        let mut lines = output.lines();
+
+        assert_eq!(
+            lines.next(),
+            Some("<Multi_key> <dead_tilde> <dead_tilde>\t: \"≈\"\t# ALMOST EQUAL TO (compose)")
+        );
+        assert_eq!(
+            lines.next(),
+            Some("<Multi_key> <slash> <slash>\t: \"\\\\\"\t# REVERSE SOLIDUS (compose)")
+        );
+        assert_eq!(
+            lines.next(),
+            Some("<Multi_key> <quotedbl> <U2192>\t: \"\\\"→\\\"\"\t# quoted, arrow (builtin)")
+        );
+        assert_eq!(
+            lines.next(),
+            Some("<Multi_key> <p> <r>\t: \"peradaban\"\t# peradaban (builtin)")
+        );
+        assert_eq!(lines.next(), None);
+    }
+
+    #[test]
+    fn test_xcompose_roundtrip() {
+        let keysymdef = KeySymDef::parse(&read_compressed(KEYSYMDEF).unwrap()).unwrap();
+        let entries = entries();
+        let output = to_xcompose(&entries, &keysymdef.names());
+        let mut report = RebuildReport::default();
+        let parsed = parse_compose_def(&keysymdef, "export", &output, &mut report).unwrap();
+        assert!(report.skipped.is_empty());
+
+        assert_eq!(parsed.len(), entries.len());
+        for entry in &entries {
+            assert_eq!(parsed[&entry.sequence].0.to_string(), entry.result);
+        }
+        // Single characters are named by their character, longer results are text
+        assert_eq!(
+            parsed[&vec![0xfe53, 0xfe53]].0,
+            MappedString::Basic("≈".into())
+        );
+        assert_eq!(
+            parsed[&vec![0x70, 0x72]].0,
+            MappedString::Extra("peradaban".into())
+        );
+    }
+
+    #[test]
+    fn test_json() {
+        let output = to_json(&entries()[1..3], &names()).unwrap();
+        assert_eq!(
+            output,
+            concat!(
+                "[\n",
+                "  {\"sequence\":\"//\",\"keys\":[\"slash\",\"slash\"],\"result\":\"\\\\\",",
+                "\"description\":\"REVERSE SOLIDUS\",\"source\":\"compose\"},\n",
+                "  {\"sequence\":\"\\\"→\",\"keys\":[\"quotedbl\",\"U2192\"],",
+                "\"result\":\"\\\"→\\\"\",\"description\":\"quoted, arrow\",\"source\":\"builtin\"}\n",
+                "]\n"
+            )
+        );
+        assert_eq!(to_json(&[], &names()).unwrap(), "[\n]\n");
+    }
+
+    #[test]
+    fn test_csv() {
+        let output = to_csv(&entries(), &names());
+        let mut lines = output.split("\r\n");
+
+        assert_eq!(lines.next(), Some("sequence,result,description,source"));
+        assert_eq!(
+            lines.next(),
+            Some("<dead_tilde><dead_tilde>,≈,ALMOST EQUAL TO,compose")
+        );
+        assert_eq!(lines.next(), Some("//,\\,REVERSE SOLIDUS,compose"));
+        assert_eq!(
+            lines.next(),
+            Some("\"\"\"→\",\"\"\"→\"\"\",\"quoted, arrow\",builtin")
+        );
+        assert_eq!(lines.next(), Some("pr,peradaban,peradaban,builtin"));
+        assert_eq!(lines.next(), Some(""));
+    }
+}
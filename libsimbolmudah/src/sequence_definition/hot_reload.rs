@@ -0,0 +1,117 @@
+use std::time::{Duration, SystemTime};
+
+use windows::{
+    core::{Interface, Result, Weak},
+    System::Threading::{ThreadPoolTimer, TimerElapsedHandler},
+};
+
+use super::SequenceDefinition;
+use crate::bindings;
+
+/// Modification time and size of a file, `None` while it cannot be read.
+pub(super) type FileStamp = Option<(SystemTime, u64)>;
+
+pub(super) fn file_stamp(path: &str) -> FileStamp {
+    let metadata = std::fs::metadata(path).ok()?;
+    Some((metadata.modified().ok()?, metadata.len()))
+}
+
+/// Tells when a polled file has changed and then stayed the same for a whole poll interval,
+/// so that an editor writing the file in several steps causes a single reload.
+#[derive(Debug, Default)]
+pub(super) struct ChangeDebouncer {
+    path: String,
+    last: Option<FileStamp>,
+    changed: bool,
+}
+
+impl ChangeDebouncer {
+    /// Whether to reload after seeing `stamp` for `path`. The first stamp seen for a path is
+    /// where watching starts.
+    pub(super) fn observe(&mut self, path: &str, stamp: FileStamp) -> bool {
+        if self.path != path {
+            self.path = path.to_string();
+            self.last = None;
+            self.changed = false;
+        }
+
+        match &self.last {
+            Some(last) if *last == stamp => std::mem::take(&mut self.changed),
+            Some(_) => {
+                self.last = Some(stamp);
+                self.changed = true;
+                false
+            }
+            None => {
+                self.last = Some(stamp);
+                false
+            }
+        }
+    }
+}
+
+/// Polls the Compose file of the last `Rebuild` and rebuilds once it has settled.
+/// Polling stops when the watcher is dropped.
+pub(super) struct ComposeWatcher(ThreadPoolTimer);
+
+impl ComposeWatcher {
+    pub(super) fn start(
+        definition: Weak<bindings::SequenceDefinition>,
+        interval: Duration,
+    ) -> Result<Self> {
+        let mut debouncer = ChangeDebouncer::default();
+        let timer = ThreadPoolTimer::CreatePeriodicTimer(
+            &TimerElapsedHandler::new(move |_| {
+                // The definition is gone and the timer is about to be canceled
+                let Some(definition) = definition.upgrade() else {
+                    return Ok(());
+                };
+                definition
+                    .cast_object_ref::<SequenceDefinition>()?
+                    .reload_if_changed(&definition, &mut debouncer)
+            }),
+            interval.into(),
+        )?;
+        Ok(Self(timer))
+    }
+}
+
+impl Drop for ComposeWatcher {
+    fn drop(&mut self) {
+        let _ = self.0.Cancel();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_debounce() {
+        let mut debouncer = ChangeDebouncer::default();
+        let first = Some((SystemTime::UNIX_EPOCH, 10));
+        let second = Some((SystemTime::UNIX_EPOCH + Duration::from_secs(1), 12));
+
+        assert!(!debouncer.observe("Compose", first));
+        assert!(!debouncer.observe("Compose", first));
+
+        // Reload only once the file stops changing, and only once
+        assert!(!debouncer.observe("Compose", second));
+        assert!(!debouncer.observe("Compose", None));
+        assert!(debouncer.observe("Compose", None));
+        assert!(!debouncer.observe("Compose", None));
+    }
+
+    #[test]
+    fn test_new_path() {
+        let mut debouncer = ChangeDebouncer::default();
+        let stamp = Some((SystemTime::UNIX_EPOCH, 10));
+
+        assert!(!debouncer.observe("Compose", stamp));
+        assert!(!debouncer.observe("Compose", None));
+
+        // Another file starts over instead of reloading for the change to the first one
+        assert!(!debouncer.observe("XCompose", stamp));
+        assert!(!debouncer.observe("XCompose", stamp));
+    }
+}
@@ -1,87 +1,258 @@
 use crate::utils::functions::{fail, fail_message};
 use regex::Regex;
-use std::{
-    cell::{LazyCell, RefCell},
-    collections::HashMap,
-    io::Read,
-};
-use windows::{
-    core::{Error, Result},
-    Win32::Foundation::E_FAIL,
-};
+use std::{cell::LazyCell, collections::HashMap};
+use windows::core::Result;
 
 // const GENERAL_REGEX_STR: &str = r"^#define XK_([a-zA-Z_0-9]+)\s+0x([0-9a-f]+)\s*(/\*.*\*/)?\s*$";
 const UNICODE_REGEX_STR: &str =
-    r"^#define XK_([a-zA-Z_0-9]+)\s+0x([0-9a-f]+)\s*/\*[ <(]U\+([0-9A-F]{4,6}) (.*)[ >)]\*/\s*$";
+    r"^#define XK_([a-zA-Z_0-9]+)\s+0x([0-9a-fA-F]+)\s*/\*[ <(]U\+([0-9A-F]{4,6}) (.*)[ >)]\*/\s*$";
 
 thread_local! {
-    static GENERAL_KEYSYM: RefCell<HashMap<String, u32>> = RefCell::new(HashMap::new());
     static KEYPAD_REGEX: LazyCell<Regex> = LazyCell::new(|| Regex::new(
-        r"^#define XK_(KP_[a-zA-Z_0-9]+)\s+0x([0-9a-f]+)\s*(/\*[ |<].*[ |>]\*/)?\s*$"
+        r"^#define XK_(KP_[a-zA-Z_0-9]+)\s+0x([0-9a-fA-F]+)\s*(/\*[ |<].*[ |>]\*/)?\s*$"
     ).unwrap());
-    // static UNICODE_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(UNICODE_REGEX_STR).unwrap());
+    static UNICODE_REGEX: LazyCell<Regex> = LazyCell::new(|| Regex::new(UNICODE_REGEX_STR).unwrap());
     static DEPRECATED_REGEX: LazyCell<Regex> = LazyCell::new(|| Regex::new(
-        r"^#define XK_([a-zA-Z_0-9]+)\s+0x([0-9a-f]+)\s*/\* (deprecated.*) \*/\s*$"
+        r"^#define XK_([a-zA-Z_0-9]+)\s+0x([0-9a-fA-F]+)\s*/\* (deprecated.*) \*/\s*$"
     ).unwrap());
     static GENERAL_REGEX: LazyCell<Regex> = LazyCell::new(|| Regex::new(
-        r"^#define XK_([a-zA-Z_0-9]+)\s+0x([0-9a-f]+)\s*(/\*.*\*/)?\s*$"
+        r"^#define XK_([a-zA-Z_0-9]+)\s+0x([0-9a-fA-F]+)\s*(/\*.*\*/)?\s*$"
     ).unwrap());
 }
 
+/// A keysym as listed in keysymdef.h.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(super) struct KeySym {
+    pub(super) value: u32,
+    /// The Unicode character given in the comment, if any.
+    pub(super) char: Option<char>,
+    /// Keypad keysyms are distinct keys even when they type a character.
+    pub(super) keypad: bool,
+    pub(super) deprecated: bool,
+    /// The name this deprecated name is an alias for.
+    pub(super) alias_of: Option<String>,
+}
+
+impl KeySym {
+    /// The keysym used in sequences. Keysyms typing a character are folded into the keysym of
+    /// that character, so that legacy and Unicode keysyms of the same character compare equal.
+    pub(super) fn canonical(&self) -> u32 {
+        match self.char {
+            Some(c) if !self.keypad => char_to_keysym(c),
+            _ => self.value,
+        }
+    }
+}
+
 pub(super) struct KeySymDef {
-    content: HashMap<String, char>,
+    by_name: HashMap<String, KeySym>,
+    /// Preferred name of each keysym value.
+    by_value: HashMap<u32, String>,
+    /// Preferred name of the keysym typing each character.
+    by_char: HashMap<char, String>,
 }
 
 impl KeySymDef {
-    pub(super) fn new(path: &str) -> Result<Self> {
-        let unicode_regex =
-            Regex::new(UNICODE_REGEX_STR).map_err(|e| Error::new(E_FAIL, format!("{:?}", e)))?;
-        let content = get_general_keysym(&unicode_regex, path)?;
-        Ok(Self { content })
+    pub(super) fn parse(content: &str) -> Result<Self> {
+        let mut by_name = HashMap::new();
+        let mut by_value = HashMap::new();
+        let mut by_char = HashMap::new();
+
+        for line in content.lines() {
+            let Some((name, keysym)) = parse_line(line)? else {
+                continue;
+            };
+
+            // Of several names sharing a value, the first non-deprecated one listed is preferred.
+            let preferred = |current: &String, by_name: &HashMap<String, KeySym>| {
+                !keysym.deprecated && by_name.get(current).is_some_and(|k: &KeySym| k.deprecated)
+            };
+            match by_value.get(&keysym.value) {
+                Some(current) if !preferred(current, &by_name) => {}
+                _ => {
+                    by_value.insert(keysym.value, name.clone());
+                }
+            }
+            if let Some(c) = keysym.char.filter(|_| !keysym.keypad) {
+                match by_char.get(&c) {
+                    Some(current) if !preferred(current, &by_name) => {}
+                    _ => {
+                        by_char.insert(c, name.clone());
+                    }
+                }
+            }
+
+            by_name.entry(name).or_insert(keysym);
+        }
+
+        Ok(Self {
+            by_name,
+            by_value,
+            by_char,
+        })
+    }
+
+    pub(super) fn get(&self, name: &str) -> Option<&KeySym> {
+        self.by_name.get(name)
     }
 
-    pub(super) fn get_key(&self, name: &str) -> Result<&char> {
-        self.content
-            .get(name)
+    /// Returns the keysym used in sequences for a name.
+    /// Deprecated aliases resolve to the keysym they alias, and names of the form `U00E9` that are
+    /// not listed resolve to the Unicode keysym of the character.
+    pub(super) fn get_key(&self, name: &str) -> Result<u32> {
+        if let Some(keysym) = self.get(name) {
+            let target = keysym
+                .alias_of
+                .as_deref()
+                .and_then(|alias| self.get(alias))
+                .unwrap_or(keysym);
+            return Ok(target.canonical());
+        }
+
+        unicode_name_to_char(name)
+            .map(char_to_keysym)
             .ok_or_else(|| fail_message("Invalid keyname"))
     }
+
+    /// Preferred name of a keysym value as listed in the file.
+    pub(super) fn name_of_value(&self, value: u32) -> Option<&str> {
+        self.by_value.get(&value).map(String::as_str)
+    }
+
+    /// Preferred name of the keysym typing a character.
+    pub(super) fn name_of_char(&self, value: char) -> Option<&str> {
+        self.by_char.get(&value).map(String::as_str)
+    }
+
+    /// Preferred name of every keysym used in sequences.
+    pub(super) fn names(&self) -> HashMap<u32, Box<str>> {
+        self.by_name
+            .values()
+            .map(KeySym::canonical)
+            .filter_map(|keysym| {
+                let name = match keysym_to_char(keysym) {
+                    Some(c) => self.name_of_char(c),
+                    None => self.name_of_value(keysym),
+                };
+                Some((keysym, name?.into()))
+            })
+            .collect()
+    }
+}
+
+/// Parses a `#define XK_...` line. Lines that define no keysym give `None`.
+fn parse_line(line: &str) -> Result<Option<(String, KeySym)>> {
+    let deprecated = DEPRECATED_REGEX.with(|re| re.captures(line));
+    let general = GENERAL_REGEX.with(|re| re.captures(line));
+    let Some(caps) = deprecated.as_ref().or(general.as_ref()) else {
+        return Ok(None);
+    };
+
+    let name = caps[1].to_string();
+    let value = u32::from_str_radix(&caps[2], 16).map_err(fail)?;
+    let alias_of = deprecated.as_ref().and_then(|caps| {
+        caps[3]
+            .strip_prefix("deprecated alias for ")
+            .map(|alias| alias.trim().to_string())
+    });
+    let char = match UNICODE_REGEX.with(|re| re.captures(line)) {
+        Some(caps) => Some(
+            char::from_u32(u32::from_str_radix(&caps[3], 16).map_err(fail)?)
+                .ok_or_else(|| fail_message("Invalid char"))?,
+        ),
+        None => None,
+    };
+    let keypad = KEYPAD_REGEX.with(|re| re.is_match(line));
+
+    Ok(Some((
+        name,
+        KeySym {
+            value,
+            char,
+            keypad,
+            deprecated: deprecated.is_some(),
+            alias_of,
+        },
+    )))
+}
+
+/// Reads the implicit keysym names `U0020`–`U007E` and `U00A0`–`U10FFFF`.
+fn unicode_name_to_char(name: &str) -> Option<char> {
+    let hex = name.strip_prefix('U')?;
+    if !(4..=6).contains(&hex.len()) || !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return None;
+    }
+
+    let codepoint = u32::from_str_radix(hex, 16).ok()?;
+    match codepoint {
+        0x20..=0x7e | 0xa0..=0x10ffff => char::from_u32(codepoint),
+        _ => None,
+    }
 }
 
-fn get_general_keysym(unicode_regex: &Regex, path: &str) -> Result<HashMap<String, char>> {
-    let mut file = std::fs::File::open(path).map_err(fail)?;
-    let mut input = brotli_decompressor::Decompressor::new(&mut file, 4096);
-    let mut buf = String::new();
-    let _num = input.read_to_string(&mut buf).map_err(fail)?;
-
-    let mut result = HashMap::new();
-
-    for line in buf.lines() {
-        if let Some(caps) = unicode_regex.captures(&line) {
-            let name = caps
-                .get(1)
-                .ok_or_else(|| fail_message("Regex parse"))?
-                .as_str();
-            let value = char::from_u32(
-                u32::from_str_radix(
-                    caps.get(3)
-                        .ok_or_else(|| fail_message("Regex parse"))?
-                        .as_str(),
-                    16,
-                )
-                .map_err(fail)?,
-            )
-            .ok_or_else(|| fail_message("Invalid char"))?;
-
-            result.insert(name.to_string(), value);
+/// Control characters typed by keys that have their own keysym: BackSpace, Tab, Linefeed,
+/// Clear, Return, Escape and Delete.
+const FUNCTION_KEYS: [(char, u32); 7] = [
+    ('\x08', 0xff08),
+    ('\t', 0xff09),
+    ('\n', 0xff0a),
+    ('\x0b', 0xff0b),
+    ('\r', 0xff0d),
+    ('\x1b', 0xff1b),
+    ('\x7f', 0xffff),
+];
+
+/// Returns the keysym that stands for the character.
+/// Latin-1 characters keep their legacy keysym, control characters typed by a function key use
+/// that key's keysym, and everything else uses the Unicode keysym range.
+pub(crate) const fn char_to_keysym(value: char) -> u32 {
+    let codepoint = value as u32;
+    if matches!(codepoint, 0x20..=0x7e | 0xa0..=0xff) {
+        return codepoint;
+    }
+
+    let mut i = 0;
+    while i < FUNCTION_KEYS.len() {
+        if FUNCTION_KEYS[i].0 as u32 == codepoint {
+            return FUNCTION_KEYS[i].1;
         }
+        i += 1;
     }
+    0x01000000 + codepoint
+}
 
-    Ok(result)
+/// Returns the character of a keysym produced by `char_to_keysym`. KP_Tab and KP_Enter type the
+/// same characters as Tab and Return.
+pub(crate) const fn keysym_to_char(keysym: u32) -> Option<char> {
+    match keysym {
+        0x20..=0x7e | 0xa0..=0xff => char::from_u32(keysym),
+        0xff89 => Some('\t'),
+        0xff8d => Some('\r'),
+        0xff00..=0xffff => {
+            let mut i = 0;
+            while i < FUNCTION_KEYS.len() {
+                if FUNCTION_KEYS[i].1 == keysym {
+                    return Some(FUNCTION_KEYS[i].0);
+                }
+                i += 1;
+            }
+            None
+        }
+        0x01000000..=0x0110ffff => match char::from_u32(keysym - 0x01000000) {
+            // Latin-1 and the function key characters only have their own keysym
+            Some(c) if char_to_keysym(c) == keysym => Some(c),
+            _ => None,
+        },
+        _ => None,
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{DEPRECATED_REGEX, GENERAL_REGEX, KEYPAD_REGEX, UNICODE_REGEX_STR};
+    use super::{
+        char_to_keysym, keysym_to_char, KeySymDef, DEPRECATED_REGEX, GENERAL_REGEX, KEYPAD_REGEX,
+        UNICODE_REGEX_STR,
+    };
     use regex::Regex;
 
     #[test]
@@ -225,4 +396,120 @@ mod tests {
         assert_eq!("0027", &name3[2]);
         assert_eq!("deprecated", &name3[3]);
     }
+
+    #[test]
+    fn keysym_char_roundtrip() {
+        assert_eq!(char_to_keysym('a'), 0x61);
+        assert_eq!(char_to_keysym('é'), 0xe9);
+        assert_eq!(char_to_keysym('→'), 0x1002192);
+        assert_eq!(char_to_keysym('🤣'), 0x101f923);
+
+        for c in ['a', '~', 'é', 'ÿ', 'Ā', '→', '🤣'] {
+            assert_eq!(keysym_to_char(char_to_keysym(c)), Some(c));
+        }
+        assert_eq!(char_to_keysym('\r'), 0xff0d);
+        assert_eq!(char_to_keysym('\t'), 0xff09);
+        assert_eq!(char_to_keysym('\x7f'), 0xffff);
+        assert_eq!(char_to_keysym('\0'), 0x01000000);
+        for c in (0..0x20).chain(0x7f..0xa0).filter_map(char::from_u32) {
+            assert_eq!(keysym_to_char(char_to_keysym(c)), Some(c));
+        }
+        assert_eq!(keysym_to_char(0xff8d), Some('\r'));
+        assert_eq!(keysym_to_char(0x0100000d), None);
+        assert_eq!(keysym_to_char(0x010000e9), None);
+        assert_eq!(keysym_to_char(0xff51), None);
+        assert_eq!(keysym_to_char(0xfe51), None);
+    }
+
+    const FIXTURE: &str = r"
+#define XK_VoidSymbol                  0xffffff  /* Void symbol */
+#define XK_Left                          0xff51  /* Move left, left arrow */
+#define XK_Prior                         0xff55  /* Prior, previous */
+#define XK_Page_Up                       0xff55  /* deprecated alias for Prior */
+#define XK_KP_Prior                      0xff9a
+#define XK_KP_Page_Up                    0xff9a  /* deprecated alias for KP_Prior */
+#define XK_KP_9                          0xffb9  /*<U+0039 DIGIT NINE>*/
+#define XK_Multi_key                     0xff20  /* Multi-key character compose */
+#define XK_dead_acute                    0xfe51
+#define XK_apostrophe                    0x0027  /* U+0027 APOSTROPHE */
+#define XK_quoteright                    0x0027  /* deprecated */
+#define XK_eacute                        0x00e9  /* U+00E9 LATIN SMALL LETTER E WITH ACUTE */
+#define XK_Aogonek                       0x01a1  /* U+0104 LATIN CAPITAL LETTER A WITH OGONEK */
+#define XK_leftanglebracket              0x0abc  /*(U+27E8 MATHEMATICAL LEFT ANGLE BRACKET)*/
+#define XK_ballotcross                 0x1002717  /* U+2717 BALLOT X */
+#define XK_Armenian_hyphen             0x100058A  /* U+058A ARMENIAN HYPHEN */
+";
+
+    #[test]
+    fn keysymdef_by_name() {
+        let keysymdef = KeySymDef::parse(FIXTURE).unwrap();
+
+        assert_eq!(keysymdef.get_key("VoidSymbol").unwrap(), 0xffffff);
+        assert_eq!(keysymdef.get_key("Multi_key").unwrap(), 0xff20);
+        assert_eq!(keysymdef.get_key("dead_acute").unwrap(), 0xfe51);
+        assert_eq!(keysymdef.get_key("eacute").unwrap(), 0xe9);
+        assert_eq!(keysymdef.get_key("KP_9").unwrap(), 0xffb9);
+        assert_eq!(keysymdef.get("KP_9").unwrap().char, Some('9'));
+        assert!(keysymdef.get_key("NoSymbol").is_err());
+
+        // Legacy keysyms are folded into the keysym of their character
+        assert_eq!(keysymdef.get_key("Aogonek").unwrap(), char_to_keysym('Ą'));
+        assert_eq!(keysymdef.get("Aogonek").unwrap().value, 0x1a1);
+        assert_eq!(keysymdef.get_key("leftanglebracket").unwrap(), 0x10027e8);
+
+        // Uppercase hex values are read as well
+        assert_eq!(keysymdef.get_key("Armenian_hyphen").unwrap(), 0x100058a);
+    }
+
+    #[test]
+    fn keysymdef_deprecated() {
+        let keysymdef = KeySymDef::parse(FIXTURE).unwrap();
+
+        let page_up = keysymdef.get("Page_Up").unwrap();
+        assert!(page_up.deprecated);
+        assert_eq!(page_up.alias_of.as_deref(), Some("Prior"));
+        assert_eq!(keysymdef.get_key("Page_Up").unwrap(), 0xff55);
+        assert_eq!(keysymdef.get_key("KP_Page_Up").unwrap(), 0xff9a);
+
+        let quoteright = keysymdef.get("quoteright").unwrap();
+        assert!(quoteright.deprecated);
+        assert_eq!(quoteright.alias_of, None);
+        assert_eq!(keysymdef.get_key("quoteright").unwrap(), 0x27);
+        assert!(!keysymdef.get("Prior").unwrap().deprecated);
+    }
+
+    #[test]
+    fn keysymdef_implicit_unicode() {
+        let keysymdef = KeySymDef::parse(FIXTURE).unwrap();
+
+        assert_eq!(keysymdef.get_key("U00E9").unwrap(), 0xe9);
+        assert_eq!(keysymdef.get_key("U2717").unwrap(), 0x1002717);
+        assert_eq!(keysymdef.get_key("U1F923").unwrap(), 0x101f923);
+        assert_eq!(keysymdef.get_key("U0041").unwrap(), 0x41);
+        assert!(keysymdef.get_key("U0009").is_err());
+        assert!(keysymdef.get_key("U110000").is_err());
+        assert!(keysymdef.get_key("Uxyz1").is_err());
+    }
+
+    #[test]
+    fn keysymdef_by_value_and_char() {
+        let keysymdef = KeySymDef::parse(FIXTURE).unwrap();
+
+        assert_eq!(keysymdef.name_of_value(0xff55), Some("Prior"));
+        assert_eq!(keysymdef.name_of_value(0x27), Some("apostrophe"));
+        assert_eq!(keysymdef.name_of_value(0x1a1), Some("Aogonek"));
+        assert_eq!(keysymdef.name_of_value(0x1234), None);
+
+        assert_eq!(keysymdef.name_of_char('\''), Some("apostrophe"));
+        assert_eq!(keysymdef.name_of_char('Ą'), Some("Aogonek"));
+        assert_eq!(keysymdef.name_of_char('✗'), Some("ballotcross"));
+        // Keypad keysyms do not stand for their character
+        assert_eq!(keysymdef.name_of_char('9'), None);
+
+        let names = keysymdef.names();
+        assert_eq!(names.get(&0xff55).map(|n| &**n), Some("Prior"));
+        assert_eq!(names.get(&0xffb9).map(|n| &**n), Some("KP_9"));
+        assert_eq!(names.get(&0x27).map(|n| &**n), Some("apostrophe"));
+        assert_eq!(names.get(&0x1000104).map(|n| &**n), Some("Aogonek"));
+    }
 }
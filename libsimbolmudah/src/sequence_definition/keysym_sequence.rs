@@ -0,0 +1,127 @@
+use std::collections::HashMap;
+
+use super::keysym_reader::{char_to_keysym, keysym_to_char};
+
+/// Encode a keysym sequence as an FST key.
+/// Each keysym takes four big-endian bytes, so the byte order follows the keysym order and a
+/// byte prefix always ends on a keysym boundary.
+pub(super) fn encode(sequence: &[u32]) -> Vec<u8> {
+    sequence.iter().flat_map(|k| k.to_be_bytes()).collect()
+}
+
+pub(super) fn decode(bytes: &[u8]) -> Vec<u32> {
+    bytes
+        .chunks_exact(4)
+        .map(|c| u32::from_be_bytes([c[0], c[1], c[2], c[3]]))
+        .collect()
+}
+
+/// The character shown for a keysym. Keys that type a control character, such as Return, are
+/// shown by name.
+fn printable(keysym: u32) -> Option<char> {
+    keysym_to_char(keysym).filter(|c| !c.is_control())
+}
+
+/// Display form of a sequence. Keysyms with a character are shown as that character, the others
+/// by their name in angle brackets, e.g. `<Left>`.
+pub(super) fn to_display(sequence: &[u32], names: &HashMap<u32, Box<str>>) -> String {
+    let mut result = String::new();
+    for &keysym in sequence {
+        if let Some(c) = printable(keysym) {
+            result.push(c);
+        } else if let Some(name) = names.get(&keysym) {
+            result.push('<');
+            result.push_str(name);
+            result.push('>');
+        } else {
+            result.push_str(&format!("<{:#x}>", keysym));
+        }
+    }
+    result
+}
+
+/// Parse the display form back into keysyms.
+/// A `<name>` that does not name a known keysym without a character is read as plain characters,
+/// mirroring `to_display`.
+pub(super) fn from_display(display: &str, names: &HashMap<u32, Box<str>>) -> Vec<u32> {
+    let mut result = Vec::new();
+    let mut rest = display;
+
+    while let Some(c) = rest.chars().next() {
+        if c == '<' {
+            if let Some((name, tail)) = rest[1..].split_once('>') {
+                if let Some(keysym) = find_keysym(name, names) {
+                    result.push(keysym);
+                    rest = tail;
+                    continue;
+                }
+            }
+        }
+
+        result.push(char_to_keysym(c));
+        rest = &rest[c.len_utf8()..];
+    }
+
+    result
+}
+
+fn find_keysym(name: &str, names: &HashMap<u32, Box<str>>) -> Option<u32> {
+    if let Some(hex) = name.strip_prefix("0x") {
+        return u32::from_str_radix(hex, 16).ok();
+    }
+
+    names
+        .iter()
+        .find_map(|(&keysym, n)| (**n == *name && printable(keysym).is_none()).then_some(keysym))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names() -> HashMap<u32, Box<str>> {
+        HashMap::from([
+            (0xff51, "Left".into()),
+            (0xff0d, "Return".into()),
+            (0xfe51, "dead_acute".into()),
+            (0x61, "a".into()),
+        ])
+    }
+
+    #[test]
+    fn test_encode_roundtrip() {
+        let sequence = [0x61, 0xff51, 0x1002192];
+        let encoded = encode(&sequence);
+
+        assert_eq!(encoded.len(), 12);
+        assert_eq!(&encoded[4..8], &[0x00, 0x00, 0xff, 0x51]);
+        assert_eq!(decode(&encoded), sequence);
+    }
+
+    #[test]
+    fn test_encode_keeps_order() {
+        // "a" followed by anything sorts after "a" alone and before "b"
+        assert!(encode(&[0x61]) < encode(&[0x61, 0x1002192]));
+        assert!(encode(&[0x61, 0x1002192]) < encode(&[0x62]));
+    }
+
+    #[test]
+    fn test_display_roundtrip() {
+        let names = names();
+        let sequence = [0xfe51, 0x65, 0xff51, 0x1002192, 0xff0d];
+        let display = to_display(&sequence, &names);
+
+        assert_eq!(display, "<dead_acute>e<Left>→<Return>");
+        assert_eq!(from_display(&display, &names), sequence);
+    }
+
+    #[test]
+    fn test_from_display_unknown_name() {
+        let names = names();
+        assert_eq!(
+            from_display("<a>", &names),
+            ['<', 'a', '>'].map(char_to_keysym)
+        );
+        assert_eq!(from_display("<", &names), [char_to_keysym('<')]);
+    }
+}
@@ -6,6 +6,42 @@ pub(super) enum MappedString {
     Extra(Box<str>),
 }
 
+impl MappedString {
+    /// Reads a result of a rule. A single grapheme is named by its first character, anything
+    /// longer is text.
+    pub(super) fn from_value(value: String) -> Self {
+        match is_single_grapheme(&value) {
+            true => Self::Basic(value.into()),
+            false => Self::Extra(value.into()),
+        }
+    }
+}
+
+/// Characters that only change how the previous one is shown, such as variation selectors.
+pub(super) fn is_modifier(value: char) -> bool {
+    matches!(value as u32, 0x0300..=0x036f | 0x200d | 0x20e3 | 0xfe0e | 0xfe0f | 0x1f3fb..=0x1f3ff)
+}
+
+/// Whether the characters after the first only modify or join it, as in `é`, `👍🏽`, `👨‍👩‍👧` or
+/// a flag.
+fn is_single_grapheme(value: &str) -> bool {
+    let is_regional = |c: char| matches!(c as u32, 0x1f1e6..=0x1f1ff);
+    let mut chars = value.chars();
+    let Some(mut previous) = chars.next() else {
+        return false;
+    };
+    let mut flag = is_regional(previous);
+
+    for c in chars {
+        if !(is_modifier(c) || previous == '\u{200d}' || (flag && is_regional(c))) {
+            return false;
+        }
+        flag = false;
+        previous = c;
+    }
+    true
+}
+
 impl Into<String> for MappedString {
     fn into(self) -> String {
         match self {
@@ -23,3 +59,31 @@ impl Display for MappedString {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_value() {
+        for value in [
+            "é",
+            "e\u{301}",
+            "👍🏽",
+            "👨\u{200d}👩\u{200d}👧",
+            "🇮🇩",
+            "©\u{fe0f}",
+        ] {
+            assert_eq!(
+                MappedString::from_value(value.into()),
+                MappedString::Basic(value.into())
+            );
+        }
+        for value in ["peradaban", "\"→\"", "🇮🇩🇫🇷", "ǅa"] {
+            assert_eq!(
+                MappedString::from_value(value.into()),
+                MappedString::Extra(value.into())
+            );
+        }
+    }
+}
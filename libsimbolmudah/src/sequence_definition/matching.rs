@@ -0,0 +1,137 @@
+use super::keysym_reader::{char_to_keysym, keysym_to_char};
+
+/// How a sequence was found.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum SequenceFallback {
+    /// As typed.
+    #[default]
+    Exact,
+    /// With its two keys swapped, e.g. `e '` for `' e`.
+    SwappedOrder,
+    /// With its letters in the other case, e.g. `o C` for `o c`.
+    CaseFolded,
+}
+
+/// Fallbacks to try when a sequence is not found as typed. All are off by default.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub(super) struct MatchingOptions {
+    pub(super) swapped_order: bool,
+    pub(super) case_folded: bool,
+}
+
+impl MatchingOptions {
+    /// Sequences to look up instead of `sequence`, in order of preference.
+    pub(super) fn fallbacks(&self, sequence: &[u32]) -> Vec<(Vec<u32>, SequenceFallback)> {
+        let mut result = Vec::new();
+        let swapped = match sequence {
+            &[first, second] if self.swapped_order && first != second => Some(vec![second, first]),
+            _ => None,
+        };
+
+        if let Some(swapped) = &swapped {
+            result.push((swapped.clone(), SequenceFallback::SwappedOrder));
+        }
+        if self.case_folded {
+            for candidate in [Some(sequence.to_vec()), swapped].into_iter().flatten() {
+                for folded in [
+                    map_case(&candidate, char::to_lowercase),
+                    map_case(&candidate, char::to_uppercase),
+                ] {
+                    if folded != sequence && !result.iter().any(|(seq, _)| *seq == folded) {
+                        result.push((folded, SequenceFallback::CaseFolded));
+                    }
+                }
+            }
+        }
+
+        result
+    }
+}
+
+/// Changes the case of every letter that has a single-character counterpart.
+fn map_case<I: Iterator<Item = char>>(sequence: &[u32], map: impl Fn(char) -> I) -> Vec<u32> {
+    sequence
+        .iter()
+        .map(|&keysym| {
+            let Some(c) = keysym_to_char(keysym) else {
+                return keysym;
+            };
+            let mut mapped = map(c);
+            match (mapped.next(), mapped.next()) {
+                (Some(m), None) => char_to_keysym(m),
+                _ => keysym,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keysyms(sequence: &str) -> Vec<u32> {
+        sequence.chars().map(char_to_keysym).collect()
+    }
+
+    #[test]
+    fn test_no_fallbacks() {
+        assert!(MatchingOptions::default()
+            .fallbacks(&keysyms("e'"))
+            .is_empty());
+    }
+
+    #[test]
+    fn test_swapped_order() {
+        let options = MatchingOptions {
+            swapped_order: true,
+            case_folded: false,
+        };
+
+        assert_eq!(
+            options.fallbacks(&keysyms("e'")),
+            [(keysyms("'e"), SequenceFallback::SwappedOrder)]
+        );
+        // Only two-key sequences of different keys are swapped
+        assert!(options.fallbacks(&keysyms("oo")).is_empty());
+        assert!(options.fallbacks(&keysyms("'ee")).is_empty());
+    }
+
+    #[test]
+    fn test_case_folded() {
+        let options = MatchingOptions {
+            swapped_order: false,
+            case_folded: true,
+        };
+
+        assert_eq!(
+            options.fallbacks(&keysyms("oC")),
+            [
+                (keysyms("oc"), SequenceFallback::CaseFolded),
+                (keysyms("OC"), SequenceFallback::CaseFolded),
+            ]
+        );
+        assert_eq!(
+            options.fallbacks(&keysyms("'É")),
+            [(keysyms("'é"), SequenceFallback::CaseFolded)]
+        );
+        // ß has no single-character uppercase
+        assert!(options.fallbacks(&keysyms("ß=")).is_empty());
+    }
+
+    #[test]
+    fn test_both() {
+        let options = MatchingOptions {
+            swapped_order: true,
+            case_folded: true,
+        };
+
+        assert_eq!(
+            options.fallbacks(&keysyms("E'")),
+            [
+                (keysyms("'E"), SequenceFallback::SwappedOrder),
+                (keysyms("e'"), SequenceFallback::CaseFolded),
+                (keysyms("'e"), SequenceFallback::CaseFolded),
+            ]
+        );
+    }
+}
@@ -1,33 +1,81 @@
+mod categories;
+mod char_query;
+mod cheat_sheet;
 mod cldr;
 mod compose_reader;
+mod diagnostics;
+mod emoji;
+mod emoji_data;
+mod export;
+mod hot_reload;
 mod keysym_reader;
+mod keysym_sequence;
 mod mapped_string;
+mod matching;
+mod rebuild_action;
+mod rebuild_statistics;
+mod search_fold;
+mod search_index;
+mod sequence_table;
+mod shortcode;
 
-use core::str;
 use std::{
-    collections::HashMap,
-    rc::Rc,
-    sync::{RwLock, RwLockReadGuard},
+    collections::{HashMap, HashSet},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex, RwLock, RwLockReadGuard,
+    },
+    time::Duration,
 };
 
-use crate::{bindings, utils::functions::fail};
-use cldr::{load_annotation_file, AnnotationPair, SupportedLocale};
-use compose_reader::ComposeDef;
-use fst::{automaton::Str, Automaton, IntoStreamer, Map, MapBuilder, Streamer};
+use crate::{
+    bindings,
+    utils::{
+        delegate_storage::DelegateStorage,
+        functions::{fail, fail_message},
+    },
+};
+use categories::{block_neighbours, CategoryMembers, SYMBOL_CATEGORIES};
+use char_query::{parse_code_points, HtmlEntities, LetterGroups};
+use cldr::{
+    available_locales, load_annotation_file, resolve_locales, unavailable_languages,
+    AnnotationPair, Locale, LocaleAnnotations, ParentLocales,
+};
+use compose_reader::{ComposeDef, SequenceSource};
+use diagnostics::{read_compressed, DefinitionError, PhaseTimer, RebuildPhase, RebuildReport};
+use emoji::{EmojiFamilies, SKIN_TONES};
+use emoji_data::{EmojiData, Qualification, COMPONENT_GROUP};
+use export::ExportEntry;
+use fst::{IntoStreamer, Streamer};
+use hot_reload::{file_stamp, ChangeDebouncer, ComposeWatcher};
 use keysym_reader::KeySymDef;
+pub(crate) use keysym_reader::{char_to_keysym, keysym_to_char};
 use mapped_string::MappedString;
+use matching::MatchingOptions;
+pub(crate) use matching::SequenceFallback;
+use rebuild_action::rebuild_action;
+use rebuild_statistics::RebuildStatistics;
+use search_fold::SearchFold;
+use search_index::SearchIndex;
+use sequence_table::{SequenceTable, TableEntry};
+use shortcode::Shortcodes;
 use windows::{
     core::{h, implement, Error, IInspectable, HSTRING, PSTR},
-    Foundation::Collections::IVectorView,
-    Globalization::Language,
-    Storage::StorageFile,
-    System::UserProfile::GlobalizationPreferences,
+    Foundation::{
+        Collections::IVectorView, EventRegistrationToken, IAsyncActionWithProgress,
+        TypedEventHandler,
+    },
+    System::{
+        Threading::{ThreadPool, WorkItemHandler},
+        UserProfile::GlobalizationPreferences,
+    },
     Win32::{
-        Foundation::{ERROR_NO_UNICODE_TRANSLATION, E_INVALIDARG},
+        Foundation::{ERROR_NO_UNICODE_TRANSLATION, E_INVALIDARG, E_POINTER},
         Globalization::{u_charName, UErrorCode, U_EXTENDED_CHAR_NAME},
         System::WinRT::{IActivationFactory, IActivationFactory_Impl},
     },
 };
+use windows_core::{IUnknownImpl, Interface};
 
 #[derive(Debug, PartialEq)]
 pub(crate) enum SequenceDefinitionError {
@@ -52,187 +100,471 @@ impl Into<Error> for SequenceDefinitionError {
     }
 }
 
+/// A sequence that can be complete and the start of longer sequences at once.
+#[derive(Debug, Default, PartialEq)]
+pub(crate) struct SequenceMatch {
+    /// Result of the sequence itself, if it is complete.
+    pub(crate) value: Option<String>,
+    /// How many longer sequences start with it.
+    pub(crate) continuations: usize,
+    /// Which of the opt-in fallbacks found the sequence.
+    pub(crate) fallback: SequenceFallback,
+}
+
+/// Tables of one `Rebuild`. A rebuild replaces all of them at once, so a lookup that holds a
+/// snapshot never sees a sequence of one build with the values of another.
+#[derive(Default)]
+struct Snapshot {
+    /// Sequences of the Compose file and the builtin ones, with their results.
+    sequences: SequenceTable,
+    char_to_name: HashMap<String, Box<str>>,
+    /// CLDR names in the first search language that has one, following its fallback chain.
+    localized_names: HashMap<String, Box<str>>,
+    /// CLDR keywords joined with ` | `, chosen like `localized_names`.
+    localized_keywords: HashMap<String, Box<str>>,
+    keysym_names: HashMap<u32, Box<str>>,
+    /// Words of the keywords and names in every search language, for `Search`.
+    search_index: SearchIndex,
+    /// Emoji of the annotations grouped by base, to show variants as one search result.
+    emoji_families: EmojiFamilies,
+    /// Groups and qualification status from `emoji-test.txt`.
+    emoji_data: EmojiData,
+    /// Bundled shortcodes, then shortcodes derived from CLDR names and keywords.
+    shortcodes: Shortcodes,
+    /// Named character references, for queries such as `&rarr;`.
+    html_entities: HtmlEntities,
+    /// Named characters by base letter, to show the relatives of a pasted letter.
+    letter_groups: LetterGroups,
+    /// Named characters of each symbol category, to browse and to group the cheat sheet.
+    symbol_categories: CategoryMembers,
+    /// Lines skipped and warnings of the `Rebuild`.
+    report: RebuildReport,
+}
+
 #[implement(bindings::SequenceDefinition)]
 #[derive(Default)]
 pub(crate) struct SequenceDefinition {
-    prefix_map: RwLock<Map<Vec<u8>>>,
-    value_to_string: RwLock<HashMap<u64, MappedString>>,
-    char_to_name: RwLock<HashMap<String, Box<str>>>,
-    string_to_sequence: RwLock<HashMap<String, String>>,
-    annotations: RwLock<HashMap<SupportedLocale, Box<[AnnotationPair]>>>,
+    /// Tables of the last `Rebuild`. Lookups clone the `Arc` and work on it without holding
+    /// the lock, so a rebuild never waits for them.
+    snapshot: RwLock<Arc<Snapshot>>,
+    /// Search languages chosen by the app as BCP-47 tags, `None` to follow the OS preference.
+    chosen_languages: RwLock<Option<Box<[String]>>>,
+    /// Paths given to the last `Rebuild`, to load other annotations when the languages change.
+    build_paths: RwLock<Option<[HSTRING; 3]>>,
+    /// Skin tone of emoji families in search results, 1 to 5, or `None` for the base emoji.
+    preferred_skin_tone: RwLock<Option<u8>>,
+    /// Fallbacks for sequences that are not found as typed.
+    matching: RwLock<MatchingOptions>,
+    /// Tickets handed to rebuilds in the order they were requested.
+    rebuild_requests: AtomicU64,
+    /// Ticket of the rebuild whose tables are in use. Held for a whole rebuild, so rebuilds run
+    /// one at a time and one overtaken by a later request leaves the tables alone.
+    rebuilt: Mutex<u64>,
+    /// Rebuilds when the Compose file changes, while hot reload is on.
+    watcher: RwLock<Option<ComposeWatcher>>,
+    /// Handlers told why a hot reload kept the previous tables.
+    reload_failed:
+        RwLock<DelegateStorage<TypedEventHandler<bindings::SequenceDefinition, HSTRING>>>,
 }
 
 impl SequenceDefinition {
+    /// The result of a complete sequence. A sequence that is also the start of longer sequences
+    /// still gives its own result; use `match_sequence` to know about the longer ones.
     pub(crate) fn translate_sequence(
         &self,
-        sequence: &str,
+        sequence: &[u32],
     ) -> Result<String, SequenceDefinitionError> {
-        read_lock(&self.prefix_map)?
-            .get(sequence.as_bytes())
-            .map_or_else(
-                || {
-                    Err(if self.potential_prefix(sequence, 1)?.is_empty() {
-                        SequenceDefinitionError::ValueNotFound
-                    } else {
-                        SequenceDefinitionError::Incomplete
-                    })
-                },
-                |value| {
-                    Ok(read_lock(&self.value_to_string)?
-                        .get(&value)
-                        .expect("value previously mapped")
-                        .to_string())
-                },
-            )
+        match self.match_sequence(sequence)? {
+            SequenceMatch {
+                value: Some(value), ..
+            } => Ok(value),
+            SequenceMatch {
+                continuations: 0, ..
+            } => Err(SequenceDefinitionError::ValueNotFound),
+            _ => Err(SequenceDefinitionError::Incomplete),
+        }
     }
 
-    fn potential_prefix(
+    /// The result of a sequence, if complete, and how many longer sequences start with it.
+    /// A sequence that is not found as typed goes through the enabled fallbacks.
+    pub(crate) fn match_sequence(
         &self,
-        sequence: &str,
-        limit: usize,
-    ) -> Result<Vec<bindings::SequenceDescription>, SequenceDefinitionError> {
-        let prefix_map = read_lock(&self.prefix_map)?;
-        let mut stream = prefix_map
-            .search(Str::new(sequence).starts_with())
-            .into_stream();
-        let mut result = Vec::with_capacity(limit);
+        sequence: &[u32],
+    ) -> Result<SequenceMatch, SequenceDefinitionError> {
+        self.match_in(&self.snapshot()?, sequence)
+    }
 
-        for _ in 0..limit {
-            if let Some(element) = stream.next() {
-                let (seq, value) = element;
-                let mapped_value = read_lock(&self.value_to_string)?
-                    .get(&value)
-                    .expect("value previously mapped")
-                    .clone();
+    fn match_in(
+        &self,
+        snapshot: &Snapshot,
+        sequence: &[u32],
+    ) -> Result<SequenceMatch, SequenceDefinitionError> {
+        let exact = snapshot.match_exact(sequence);
+        if exact.value.is_some() || exact.continuations > 0 {
+            return Ok(exact);
+        }
 
-                result.push(bindings::SequenceDescription {
-                    sequence: unsafe { str::from_utf8_unchecked(seq) }.into(),
-                    result: mapped_value.to_string().into(),
-                    description: match mapped_value {
-                        MappedString::Basic(c) => read_lock(&self.char_to_name)?
-                            .get(&c.to_string())
-                            .unwrap()
-                            .to_string()
-                            .into(),
-                        MappedString::Extra(s) => s.to_string().into(),
-                    },
+        let options = *read_lock(&self.matching)?;
+        for (candidate, fallback) in options.fallbacks(sequence) {
+            let found = snapshot.match_exact(&candidate);
+            // Later keys are swapped no more, so a swapped sequence must be complete
+            if found.value.is_some()
+                || (found.continuations > 0 && fallback == SequenceFallback::CaseFolded)
+            {
+                return Ok(SequenceMatch { fallback, ..found });
+            }
+        }
+
+        // A key that only ends sequences may be the first of them typed in swapped order
+        if let (true, &[key]) = (options.swapped_order, sequence) {
+            let continuations = snapshot.sequences.ending_with(key);
+            if continuations > 0 {
+                return Ok(SequenceMatch {
+                    value: None,
+                    continuations,
+                    fallback: SequenceFallback::SwappedOrder,
                 });
-            } else {
-                return Ok(result);
             }
         }
 
-        Ok(result)
+        Ok(exact)
     }
 
-    fn filter_sequence(
+    /// Emoji of a shortcode typed without its colons, in its fully-qualified form.
+    pub(crate) fn translate_shortcode(
         &self,
-        tokens: Vec<String>,
-        limit: usize,
-        languages: &[SupportedLocale],
-    ) -> Result<Vec<bindings::SequenceDescription>, SequenceDefinitionError> {
-        let mut result_map = HashMap::new();
-
-        // prioritize exact character match
-        for lang in languages {
-            let annotation_map = read_lock(&self.annotations)?;
-            for pair in annotation_map
-                .get(lang)
-                .expect("language supported")
-                .iter()
-                .filter(|pair| tokens.iter().any(|t| pair.char.contains(&t.to_lowercase())))
-            {
-                if !result_map.contains_key(&pair.char.to_string()) {
-                    result_map.insert(
-                        pair.char.to_string(),
-                        read_lock(&self.char_to_name)?
-                            .get(&pair.char.to_string())
-                            .expect("already indexed")
-                            .to_string(),
-                    );
-                }
+        code: &str,
+    ) -> Result<String, SequenceDefinitionError> {
+        let snapshot = self.snapshot()?;
+        let value = snapshot
+            .shortcodes
+            .get(code)
+            .ok_or(SequenceDefinitionError::ValueNotFound)?;
+        Ok(snapshot
+            .emoji_data
+            .fully_qualified(value)
+            .unwrap_or(value)
+            .to_string())
+    }
 
-                if result_map.len() >= limit {
-                    return self.process_map(&result_map);
-                }
-            }
+    /// Whether some shortcode starts with `prefix`.
+    pub(crate) fn is_shortcode_prefix(
+        &self,
+        prefix: &str,
+    ) -> Result<bool, SequenceDefinitionError> {
+        Ok(self
+            .snapshot()?
+            .shortcodes
+            .with_prefix(prefix)
+            .next()
+            .is_some())
+    }
+
+    /// A definition with only the given sequences, for tests that need sequences the Compose
+    /// file does not have.
+    #[cfg(test)]
+    pub(crate) fn from_sequences(sequences: &[(&str, &str)]) -> Self {
+        let sequences: std::collections::BTreeMap<_, _> = sequences
+            .iter()
+            .map(|(sequence, value)| {
+                (
+                    sequence.chars().map(char_to_keysym).collect::<Vec<_>>(),
+                    (
+                        MappedString::Basic((*value).into()),
+                        SequenceSource::Compose,
+                    ),
+                )
+            })
+            .collect();
+
+        Self {
+            snapshot: RwLock::new(Arc::new(Snapshot {
+                sequences: SequenceTable::build(sequences).expect("small table"),
+                ..Default::default()
+            })),
+            ..Default::default()
         }
+    }
 
-        for token in tokens.iter() {
-            if !result_map.contains_key(token) {
-                if let Some(name) = read_lock(&self.char_to_name)?.get(&token.to_string()) {
-                    result_map.insert(token.to_string(), name.to_string());
-                }
+    /// The tables of the last `Rebuild`, which stay the same while the caller holds them.
+    fn snapshot(&self) -> Result<Arc<Snapshot>, SequenceDefinitionError> {
+        Ok(read_lock(&self.snapshot)?.clone())
+    }
+
+    /// A ticket for `rebuild`, taken when the rebuild is requested.
+    fn request_rebuild(&self) -> u64 {
+        self.rebuild_requests.fetch_add(1, Ordering::SeqCst) + 1
+    }
+
+    /// Builds a new snapshot from the files and swaps it in at the end, so lookups are served
+    /// from the previous one until then. `progress` is called as each phase starts and can stop
+    /// the rebuild by failing. With `fail_on_skipped`, a Compose file with lines that cannot be
+    /// read fails the rebuild instead of leaving those lines out. Does nothing when the tables
+    /// already come from a rebuild with a later `ticket`.
+    fn rebuild(
+        &self,
+        ticket: u64,
+        build_paths: &[HSTRING; 3],
+        fail_on_skipped: bool,
+        mut progress: impl FnMut(RebuildPhase) -> windows_core::Result<()>,
+    ) -> windows_core::Result<()> {
+        let mut rebuilt = self.rebuilt.lock().map_err(fail)?;
+        if *rebuilt > ticket {
+            return Ok(());
+        }
+
+        let [keysymdef, composedef, annotations] = build_paths;
+        let mut report = RebuildReport::default();
+        let mut timer = PhaseTimer::start();
+        progress(RebuildPhase::Decompress)?;
+        let keysym_text = read_compressed(&keysymdef.to_string())?;
+        let compose_path = composedef.to_string();
+        let compose_text = read_compressed(&compose_path)?;
+        timer.finish(RebuildPhase::Decompress, &mut report);
+        progress(RebuildPhase::Parse)?;
+
+        let keysymdef = KeySymDef::parse(&keysym_text)?;
+        let composedef = ComposeDef::parse(&keysymdef, &compose_path, &compose_text, &mut report)?;
+        let keysym_names = keysymdef.names();
+        if let (true, Some(first)) = (fail_on_skipped, report.skipped.first()) {
+            return Err(fail_message(&match report.skipped.len() - 1 {
+                0 => first.to_string(),
+                more => format!("{first} (and {more} more lines)"),
+            }));
+        }
+        timer.finish(RebuildPhase::Parse, &mut report);
+        progress(RebuildPhase::Annotations)?;
+
+        let mut annotation_map = HashMap::new();
+        let mut char_to_name: HashMap<String, Box<str>> = HashMap::new();
+        let mut localized_names: HashMap<String, Box<str>> = HashMap::new();
+        let mut localized_keywords: HashMap<String, Box<str>> = HashMap::new();
+        let annotations = annotations.to_string();
+        let mut available = available_locales(&annotations)?;
+        // Without the explicit parents, locales only fall back by dropping subtags
+        let parents = match read_compressed(&format!("{annotations}\\supplementalData.xml.br")) {
+            Ok(text) => ParentLocales::parse(&text)?,
+            Err(error) => {
+                report.warnings.push(error);
+                ParentLocales::default()
             }
+        };
+        let user_langs = self.search_languages()?;
+        let languages =
+            resolve_locales(user_langs.iter().map(|l| l.as_str()), &available, &parents);
+        report.warnings.extend(
+            unavailable_languages(user_langs.iter().map(|l| l.as_str()), &available, &parents)
+                .into_iter()
+                .map(|locale| DefinitionError::LocaleMissing {
+                    locale: locale.into(),
+                }),
+        );
+        let chains = languages
+            .iter()
+            .map(|locale| {
+                locale
+                    .fallback_chain(&parents)
+                    .into_iter()
+                    .filter(|l| available.contains_key(l))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
 
-            if result_map.len() >= limit {
-                return self.process_map(&result_map);
+        // Every locale file is read once, even when several languages inherit from it
+        let mut locale_annotations = HashMap::new();
+        for locale in chains.iter().flatten() {
+            if let Some(paths) = available.remove(locale) {
+                let mut current = LocaleAnnotations::default();
+                for path in paths {
+                    current.extend(load_annotation_file(&path)?);
+                }
+                locale_annotations.insert(locale.clone(), current);
             }
         }
 
-        // search in descriptions
-        for lang in languages {
-            let annotation_map = read_lock(&self.annotations)?;
-            for pair in annotation_map
-                .get(lang)
-                .expect("language supported")
-                .iter()
-                .filter(|pair| tokens.iter().all(|t| pair.desc.contains(&t.to_lowercase())))
-            {
-                if !result_map.contains_key(&pair.char.to_string()) {
-                    result_map.insert(
-                        pair.char.to_string(),
-                        read_lock(&self.char_to_name)?
-                            .get(&pair.char.to_string())
-                            .expect("already indexed")
-                            .to_string(),
-                    );
+        let mut shortcodes = Shortcodes::load(&format!("{annotations}\\shortcodes.txt.br"))?;
+        // Code points still work in queries without the named references
+        let html_entities = match read_compressed(&format!("{annotations}\\html_entities.txt.br")) {
+            Ok(text) => HtmlEntities::parse(&text)?,
+            Err(error) => {
+                report.warnings.push(error);
+                HtmlEntities::default()
+            }
+        };
+        let mut keyword_shortcodes = Vec::new();
+        for (locale, chain) in languages.iter().zip(&chains) {
+            let resolved =
+                LocaleAnnotations::resolve(chain.iter().filter_map(|l| locale_annotations.get(l)));
+            let mut result_vec = Vec::new();
+            for (cp, a) in resolved.iter() {
+                if let Some(tts) = &a.tts {
+                    if !localized_names.contains_key(cp) {
+                        localized_names.insert(cp.to_string(), tts.clone());
+                    }
+                    if !char_to_name.contains_key(cp) {
+                        char_to_name.insert(cp.to_string(), tts.clone());
+                    }
+                    shortcodes.insert_derived(tts, cp);
                 }
 
-                if result_map.len() >= limit {
-                    return self.process_map(&result_map);
+                if let Some(keywords) = &a.keywords {
+                    if !localized_keywords.contains_key(cp) {
+                        localized_keywords.insert(cp.to_string(), keywords.join(" | ").into());
+                    }
+                }
+
+                let main_char: Arc<str> = Arc::from(cp);
+                for word in a.keywords.iter().flatten() {
+                    keyword_shortcodes.push((word.clone(), main_char.clone()));
+                    result_vec.push(AnnotationPair {
+                        char: main_char.clone(),
+                        desc: word.clone(),
+                    });
                 }
             }
+
+            annotation_map.insert(locale.clone(), result_vec.into_boxed_slice());
         }
 
-        for (c, n) in read_lock(&self.char_to_name)?
-            .iter()
-            .filter(|(_, n)| tokens.iter().all(|t| n.contains(&t.to_uppercase())))
-        {
-            let c = c.to_string();
-            if !result_map.contains_key(&c) {
-                result_map.insert(c, n.to_string());
-            }
+        // Names make better shortcodes than keywords, which many characters share
+        for (word, cp) in keyword_shortcodes {
+            shortcodes.insert_derived(&word, &cp);
+        }
 
-            if result_map.len() >= limit {
-                return self.process_map(&result_map);
+        let emoji_data = EmojiData::load(&format!("{annotations}\\emoji-test.txt.br"))?;
+        let emoji_families = EmojiFamilies::build(&emoji_data);
+        timer.finish(RebuildPhase::Annotations, &mut report);
+        progress(RebuildPhase::NameLookup)?;
+
+        // Unicode names take precedence over CLDR names for single characters
+        for value in composedef.values() {
+            if let MappedString::Basic(e) = value {
+                char_to_name.insert(
+                    e.to_string(),
+                    char_to_unicode_name(e.chars().next().expect("string not empty"))?,
+                );
             }
         }
+        timer.finish(RebuildPhase::NameLookup, &mut report);
+        progress(RebuildPhase::FstBuild)?;
+
+        let sequences = SequenceTable::build(composedef)?;
+        let mut names = char_to_name
+            .iter()
+            .map(|(c, name)| (c.as_str(), name.as_ref()))
+            .collect::<Vec<_>>();
+        names.sort_unstable();
+        let named_chars = names
+            .iter()
+            .filter_map(|(c, _)| {
+                let mut chars = c.chars();
+                chars.next().filter(|_| chars.next().is_none())
+            })
+            .collect::<Vec<_>>();
+        // Letters related by accents follow the language searched in first
+        let letter_groups = LetterGroups::build(
+            named_chars.iter().copied(),
+            SearchFold::for_language(languages.first().map_or("", |l| l.language())),
+        );
+        let symbol_categories = CategoryMembers::build(named_chars);
+        // Keywords are folded with the rules of their own language
+        let search_index = SearchIndex::build(
+            languages.iter().filter_map(|locale| {
+                let pairs = annotation_map
+                    .get(locale)?
+                    .iter()
+                    .map(|pair: &AnnotationPair| (pair.char.as_ref(), pair.desc.as_ref()));
+                Some((SearchFold::for_language(locale.language()), pairs))
+            }),
+            names,
+        )?;
+        timer.finish(RebuildPhase::FstBuild, &mut report);
+
+        report.sequences = SequenceSource::ALL
+            .iter()
+            .map(|source| (source.as_str(), sequences.count(*source)))
+            .collect();
+        report.locales = languages.iter().map(|l| l.to_string()).collect();
+        report.tables = sequences.sizes().to_vec();
+        report.tables.extend([
+            (
+                "char_to_name",
+                map_size(&char_to_name, |k, v| k.len() + v.len()),
+            ),
+            (
+                "localized_names",
+                map_size(&localized_names, |k, v| k.len() + v.len()),
+            ),
+            (
+                "localized_keywords",
+                map_size(&localized_keywords, |k, v| k.len() + v.len()),
+            ),
+            ("keysym_names", map_size(&keysym_names, |_, v| v.len())),
+        ]);
+        report.tables.extend(search_index.sizes());
+        report.tables.extend([
+            ("html_entities", html_entities.size()),
+            ("letter_groups", letter_groups.size()),
+            ("symbol_categories", symbol_categories.size()),
+        ]);
+
+        *self.snapshot.write().map_err(fail)? = Arc::new(Snapshot {
+            sequences,
+            char_to_name,
+            localized_names,
+            localized_keywords,
+            keysym_names,
+            search_index,
+            emoji_families,
+            emoji_data,
+            shortcodes,
+            html_entities,
+            letter_groups,
+            symbol_categories,
+            report,
+        });
+        *self.build_paths.write().map_err(fail)? = Some(build_paths.clone());
+        *rebuilt = ticket;
 
-        self.process_map(&result_map)
+        Ok(())
     }
 
-    fn process_map(
+    /// Rebuilds from the files of the last `Rebuild` once its Compose file has changed and
+    /// settled. Only that brotli-compressed file is watched, not keysymdef.h or the
+    /// annotations. When the rebuild fails, the previous tables stay and `OnReloadFailed` says
+    /// why.
+    fn reload_if_changed(
         &self,
-        map: &HashMap<String, String>,
-    ) -> Result<Vec<bindings::SequenceDescription>, SequenceDefinitionError> {
-        let mut result = Vec::with_capacity(map.len());
-        for (char, desc) in map {
-            let given_sequence =
-                if let Some(sequence) = read_lock(&self.string_to_sequence)?.get(char) {
-                    sequence.into()
-                } else {
-                    HSTRING::from("")
-                };
+        this: &bindings::SequenceDefinition,
+        debouncer: &mut ChangeDebouncer,
+    ) -> windows_core::Result<()> {
+        let Some(build_paths) = read_lock(&self.build_paths)
+            .map_err(|e| Into::<Error>::into(e))?
+            .clone()
+        else {
+            return Ok(());
+        };
+        let path = build_paths[1].to_string();
+        if !debouncer.observe(&path, file_stamp(&path)) {
+            return Ok(());
+        }
 
-            result.push(bindings::SequenceDescription {
-                sequence: given_sequence,
-                result: char.into(),
-                description: desc.into(),
-            });
+        match self.rebuild(self.request_rebuild(), &build_paths, true, |_| Ok(())) {
+            Ok(()) => Ok(()),
+            Err(e) => self
+                .reload_failed
+                .write()
+                .map_err(fail)?
+                .invoke_all(|d| d.Invoke(this, &e.message().into())),
         }
+    }
 
-        Ok(result)
+    /// The chosen search languages, or the OS preference if none were chosen.
+    fn search_languages(&self) -> windows_core::Result<Vec<String>> {
+        match &*read_lock(&self.chosen_languages).map_err(|e| Into::<Error>::into(e))? {
+            Some(languages) => Ok(languages.to_vec()),
+            None => get_user_langs(),
+        }
     }
 
     fn tokenize(&self, keyword: &HSTRING) -> Vec<String> {
@@ -244,6 +576,318 @@ impl SequenceDefinition {
     }
 }
 
+impl Snapshot {
+    fn match_exact(&self, sequence: &[u32]) -> SequenceMatch {
+        let prefix = keysym_sequence::encode(sequence);
+        let mut stream = self
+            .sequences
+            .prefix_map()
+            .range()
+            .ge(&prefix)
+            .into_stream();
+        let mut result = SequenceMatch::default();
+
+        while let Some((seq, value)) = stream.next().filter(|(seq, _)| seq.starts_with(&prefix)) {
+            if seq == prefix {
+                result.value = Some(self.sequences.entry(value).result.to_string());
+            } else {
+                result.continuations += 1;
+            }
+        }
+
+        result
+    }
+
+    fn potential_prefix(
+        &self,
+        sequence: &[u32],
+        limit: usize,
+    ) -> Vec<bindings::SequenceDescription> {
+        let prefix = keysym_sequence::encode(sequence);
+        let mut stream = self
+            .sequences
+            .prefix_map()
+            .range()
+            .ge(&prefix)
+            .into_stream();
+        let mut result = Vec::with_capacity(limit);
+
+        for _ in 0..limit {
+            if let Some(element) = stream.next().filter(|(seq, _)| seq.starts_with(&prefix)) {
+                let (seq, value) = element;
+                let entry = self.sequences.entry(value);
+
+                result.push(bindings::SequenceDescription {
+                    sequence: keysym_sequence::to_display(
+                        &keysym_sequence::decode(seq),
+                        &self.keysym_names,
+                    )
+                    .into(),
+                    result: entry.result.into(),
+                    description: self.describe_localized(entry).into(),
+                    keywords: self.keywords(entry.result),
+                });
+            } else {
+                return result;
+            }
+        }
+
+        result
+    }
+
+    fn describe(&self, entry: TableEntry) -> String {
+        match entry.basic {
+            true => self.char_to_name.get(entry.result).unwrap().to_string(),
+            false => entry.result.to_string(),
+        }
+    }
+
+    /// Like `describe`, but prefers the CLDR name in the search languages.
+    fn describe_localized(&self, entry: TableEntry) -> String {
+        if entry.basic {
+            if let Some(name) = self.localized_names.get(entry.result) {
+                return name.to_string();
+            }
+        }
+        self.describe(entry)
+    }
+
+    /// Display form of a sequence that types `value`, empty if there is none.
+    fn sequence_of(&self, value: &str) -> HSTRING {
+        self.sequences
+            .sequence_of(value)
+            .map_or_else(HSTRING::new, |sequence| {
+                keysym_sequence::to_display(&sequence, &self.keysym_names).into()
+            })
+    }
+
+    /// CLDR keywords of a result in the search languages, empty if there are none.
+    fn keywords(&self, value: &str) -> HSTRING {
+        self.localized_keywords
+            .get(value)
+            .map_or_else(HSTRING::new, |keywords| HSTRING::from(keywords.as_ref()))
+    }
+
+    /// A result outside of any sequence table, such as an emoji variant.
+    fn describe_value(&self, value: &str) -> windows_core::Result<bindings::SequenceDescription> {
+        let description = if let Some(name) = self.localized_names.get(value) {
+            name.to_string()
+        } else if let Some(name) = self.char_to_name.get(value) {
+            name.to_string()
+        } else if let Some(info) = self.emoji_data.get(value) {
+            info.name.to_string()
+        } else if let (Some(c), None) = (value.chars().next(), value.chars().nth(1)) {
+            char_to_unicode_name(c)?.to_string()
+        } else {
+            String::new()
+        };
+
+        Ok(bindings::SequenceDescription {
+            sequence: self.sequence_of(value),
+            result: self
+                .emoji_data
+                .fully_qualified(value)
+                .unwrap_or(value)
+                .into(),
+            description: description.into(),
+            keywords: self.keywords(value),
+        })
+    }
+
+    /// Members of a symbol category or emoji group, in browsing order. Emoji with a skin tone
+    /// are left out, as they are reached through `GetEmojiVariants`.
+    fn category_members(&self, category: &str) -> windows_core::Result<Vec<String>> {
+        if let Some(symbols) = self.symbol_categories.find(category) {
+            return Ok(symbols.iter().copied().map(String::from).collect());
+        }
+
+        if category != COMPONENT_GROUP && self.emoji_data.groups().any(|group| group == category) {
+            return Ok(self
+                .emoji_data
+                .group_members(category)
+                .filter(|value| self.emoji_families.skin_tone(value).is_none())
+                .map(String::from)
+                .collect());
+        }
+
+        Err(Error::new(
+            E_INVALIDARG,
+            format!("Unknown category: {category}"),
+        ))
+    }
+
+    /// Every sequence of the table, in sequence order.
+    fn export_entries(&self) -> Vec<ExportEntry> {
+        let prefix_map = self.sequences.prefix_map();
+        let mut stream = prefix_map.stream();
+        let mut result = Vec::with_capacity(prefix_map.len());
+
+        while let Some((seq, value)) = stream.next() {
+            let entry = self.sequences.entry(value);
+            result.push(ExportEntry {
+                sequence: keysym_sequence::decode(seq),
+                result: entry.result.to_string(),
+                description: self.describe(entry),
+                source: entry.source,
+            });
+        }
+
+        result
+    }
+
+    /// Characters whose keywords or name have words starting with every token, best ranked
+    /// first: keywords in the search languages in order, then names.
+    fn filter_sequence(
+        &self,
+        tokens: Vec<String>,
+        limit: usize,
+        skin_tone: Option<u8>,
+    ) -> Vec<bindings::SequenceDescription> {
+        let matches = self
+            .search_index
+            .search(&tokens)
+            .take(limit)
+            .map(|c| {
+                let name = self
+                    .char_to_name
+                    .get(c)
+                    .map_or_else(String::new, |n| n.to_string());
+                (c.to_string(), name)
+            })
+            .collect::<Vec<_>>();
+
+        self.process_map(matches, skin_tone)
+    }
+
+    /// Results of `Search`. A query that gives an example character rather than describing one
+    /// returns that character and its relatives. A single letter or digit is also the start of
+    /// a word, so its relatives only follow the characters found by word.
+    fn search(
+        &self,
+        tokens: Vec<String>,
+        limit: usize,
+        skin_tone: Option<u8>,
+    ) -> windows_core::Result<Vec<bindings::SequenceDescription>> {
+        if let Some(value) = self.example_character(&tokens) {
+            return self.related_characters(&value, limit);
+        }
+
+        let letter = match tokens.as_slice() {
+            [token] if token.chars().nth(1).is_none() => Some(token.clone()),
+            _ => None,
+        };
+        let mut result = self.filter_sequence(tokens, limit, skin_tone);
+        if let Some(letter) = letter {
+            let shown = result
+                .iter()
+                .map(|desc| desc.result.to_string())
+                .collect::<HashSet<_>>();
+            let remaining = limit.saturating_sub(result.len());
+            result.extend(
+                self.related_characters(&letter, limit)?
+                    .into_iter()
+                    .filter(|desc| !shown.contains(&desc.result.to_string()))
+                    .take(remaining),
+            );
+        }
+        Ok(result)
+    }
+
+    /// The character a query gives as an example rather than describes: a code point such as
+    /// `U+2192` or `&rarr;`, a pasted emoji, or a single symbol.
+    fn example_character(&self, tokens: &[String]) -> Option<String> {
+        if let Some(value) = parse_code_points(tokens, &self.html_entities) {
+            return Some(value);
+        }
+
+        let [token] = tokens else {
+            return None;
+        };
+        let mut chars = token.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) if !c.is_alphanumeric() => Some(token.clone()),
+            _ => self.emoji_data.get(token).map(|_| token.clone()),
+        }
+    }
+
+    /// `value` itself, then its emoji variants, characters with the same base letter, and the
+    /// rest of its Unicode block from the nearest.
+    fn related_characters(
+        &self,
+        value: &str,
+        limit: usize,
+    ) -> windows_core::Result<Vec<bindings::SequenceDescription>> {
+        let family = self.emoji_families.base_of(value);
+        let mut chars = value.chars();
+        let single = chars.next().filter(|_| chars.next().is_none());
+
+        let variants = family.iter().flat_map(|base| {
+            std::iter::once(base.to_string())
+                .chain(self.emoji_families.variants(base).map(String::from))
+        });
+        let neighbours = single.into_iter().flat_map(|c| {
+            self.letter_groups
+                .related(c)
+                .iter()
+                .copied()
+                .chain(block_neighbours(c))
+                .map(String::from)
+        });
+
+        let mut shown = HashSet::new();
+        std::iter::once(value.to_string())
+            .chain(variants)
+            .chain(neighbours)
+            .filter(|v| shown.insert(self.emoji_data.fully_qualified(v).unwrap_or(v).to_string()))
+            .take(limit)
+            .map(|v| self.describe_value(&v))
+            .collect()
+    }
+
+    fn process_map(
+        &self,
+        matches: Vec<(String, String)>,
+        skin_tone: Option<u8>,
+    ) -> Vec<bindings::SequenceDescription> {
+        let families = &self.emoji_families;
+        let mut shown = HashSet::new();
+        let mut result = Vec::with_capacity(matches.len());
+        for (char, desc) in matches {
+            // Emoji variants are shown once, through their base in the preferred skin tone
+            let (char, desc) = match families.base_of(&char) {
+                Some(base) => {
+                    let value = skin_tone
+                        .and_then(|tone| families.with_skin_tone(base, tone))
+                        .unwrap_or(base)
+                        .to_string();
+                    let desc = self
+                        .char_to_name
+                        .get(&value)
+                        .map_or(desc, |name| name.to_string());
+                    (value, desc)
+                }
+                None => (char, desc),
+            };
+            if !shown.insert(char.clone()) {
+                continue;
+            }
+
+            result.push(bindings::SequenceDescription {
+                sequence: self.sequence_of(&char),
+                result: self
+                    .emoji_data
+                    .fully_qualified(&char)
+                    .unwrap_or(&char)
+                    .into(),
+                description: desc.into(),
+                keywords: self.keywords(&char),
+            });
+        }
+
+        result
+    }
+}
+
 impl bindings::ISequenceDefinition_Impl for SequenceDefinition_Impl {
     fn Rebuild(
         &self,
@@ -251,109 +895,352 @@ impl bindings::ISequenceDefinition_Impl for SequenceDefinition_Impl {
         composedef: &HSTRING,
         annotations: &HSTRING,
     ) -> windows_core::Result<()> {
-        let keysymdef = KeySymDef::new(&keysymdef.to_string())?;
-        let composedef = ComposeDef::build(&keysymdef, &composedef.to_string())?;
+        let build_paths = [keysymdef.clone(), composedef.clone(), annotations.clone()];
+        self.rebuild(self.request_rebuild(), &build_paths, false, |_| Ok(()))
+    }
 
-        let mut annotation_map = HashMap::new();
-        let mut char_to_name: HashMap<String, Box<str>> = HashMap::new();
-        let languages = get_user_langs()?;
-        let prefetch = languages
-            .iter()
-            .map(|locale| {
-                (
-                    locale,
-                    ["annotations", "annotationsDerived"].map(move |variant| {
-                        StorageFile::GetFileFromPathAsync(
-                            &format!("{}\\{locale}-{variant}.xml.br", annotations.to_string())
-                                .into(),
-                        )
-                    }),
-                )
+    fn RebuildAsync(
+        &self,
+        keysymdef: &HSTRING,
+        composedef: &HSTRING,
+        annotations: &HSTRING,
+    ) -> windows_core::Result<IAsyncActionWithProgress<bindings::RebuildPhase>> {
+        let build_paths = [keysymdef.clone(), composedef.clone(), annotations.clone()];
+        let this: bindings::SequenceDefinition = self.to_interface();
+        let ticket = self.request_rebuild();
+        let (action, reporter) = rebuild_action();
+        let mut reporter = Some(reporter);
+
+        ThreadPool::RunAsync(&WorkItemHandler::new(move |_| {
+            let Some(reporter) = reporter.take() else {
+                return Ok(());
+            };
+            let definition = this.cast_object_ref::<SequenceDefinition>()?;
+
+            let result = definition.rebuild(ticket, &build_paths, false, |phase| {
+                reporter.report(phase.into())
+            });
+            reporter.finish(result)
+        }))?;
+
+        Ok(action)
+    }
+
+    fn SetHotReload(&self, enabled: bool, debouncemilliseconds: u32) -> windows_core::Result<()> {
+        let watcher = match (enabled, debouncemilliseconds) {
+            (false, _) => None,
+            (true, 0) => return Err(Error::new(E_INVALIDARG, "Debounce interval is zero")),
+            (true, interval) => Some(ComposeWatcher::start(
+                self.to_interface::<bindings::SequenceDefinition>()
+                    .downgrade()?,
+                Duration::from_millis(interval.into()),
+            )?),
+        };
+        *self.watcher.write().map_err(fail)? = watcher;
+        Ok(())
+    }
+
+    fn OnReloadFailed(
+        &self,
+        handler: Option<&TypedEventHandler<bindings::SequenceDefinition, HSTRING>>,
+    ) -> windows_core::Result<EventRegistrationToken> {
+        let handler = handler.ok_or_else(|| Error::new(E_POINTER, "delegate is null"))?;
+        self.reload_failed.write().map_err(fail)?.insert(handler)
+    }
+
+    fn RemoveOnReloadFailed(&self, token: &EventRegistrationToken) -> windows_core::Result<()> {
+        self.reload_failed.write().map_err(fail)?.remove(*token);
+        Ok(())
+    }
+
+    fn PotentialPrefix(
+        &self,
+        sequence: &HSTRING,
+        limit: u32,
+    ) -> windows_core::Result<IVectorView<bindings::SequenceDescription>> {
+        let snapshot = self.snapshot().map_err(|e| Into::<Error>::into(e))?;
+        let sequence = keysym_sequence::from_display(&sequence.to_string(), &snapshot.keysym_names);
+        snapshot
+            .potential_prefix(&sequence, limit as usize)
+            .try_into()
+    }
+
+    fn Search(
+        &self,
+        sequence: &HSTRING,
+        limit: u32,
+    ) -> windows_core::Result<IVectorView<bindings::SequenceDescription>> {
+        let skin_tone =
+            *read_lock(&self.preferred_skin_tone).map_err(|e| Into::<Error>::into(e))?;
+        let snapshot = self.snapshot().map_err(|e| Into::<Error>::into(e))?;
+        snapshot
+            .search(self.tokenize(sequence), limit as usize, skin_tone)?
+            .try_into()
+    }
+
+    fn ShortcodePrefix(
+        &self,
+        prefix: &HSTRING,
+        limit: u32,
+    ) -> windows_core::Result<IVectorView<bindings::SequenceDescription>> {
+        let prefix = prefix.to_string();
+        let prefix = prefix.trim_start_matches(':');
+        let snapshot = self.snapshot().map_err(|e| Into::<Error>::into(e))?;
+        snapshot
+            .shortcodes
+            .with_prefix(prefix)
+            .take(limit as usize)
+            .map(|(code, value)| {
+                Ok(bindings::SequenceDescription {
+                    sequence: format!(":{code}:").into(),
+                    ..snapshot.describe_value(value)?
+                })
+            })
+            .collect::<windows_core::Result<Vec<_>>>()?
+            .try_into()
+    }
+
+    fn MatchSequence(&self, sequence: &HSTRING) -> windows_core::Result<bindings::SequenceMatch> {
+        let snapshot = self.snapshot().map_err(|e| Into::<Error>::into(e))?;
+        let sequence = keysym_sequence::from_display(&sequence.to_string(), &snapshot.keysym_names);
+        let found = self
+            .match_in(&snapshot, &sequence)
+            .map_err(|e| Into::<Error>::into(e))?;
+
+        Ok(bindings::SequenceMatch {
+            result: found.value.unwrap_or_default().into(),
+            continuations: found.continuations as u32,
+            fallback: match found.fallback {
+                SequenceFallback::Exact => bindings::SequenceFallback::Exact,
+                SequenceFallback::SwappedOrder => bindings::SequenceFallback::SwappedOrder,
+                SequenceFallback::CaseFolded => bindings::SequenceFallback::CaseFolded,
+            },
+        })
+    }
+
+    fn GetRebuildReport(&self) -> windows_core::Result<IVectorView<bindings::RebuildDiagnostic>> {
+        let snapshot = self.snapshot().map_err(|e| Into::<Error>::into(e))?;
+        let report = &snapshot.report;
+        let skipped = report.skipped.iter().map(|error| (error, true));
+        let warnings = report.warnings.iter().map(|error| (error, false));
+
+        skipped
+            .chain(warnings)
+            .map(|(error, skipped)| error.to_diagnostic(skipped))
+            .collect::<Vec<_>>()
+            .try_into()
+    }
+
+    fn GetRebuildStatistics(&self) -> windows_core::Result<bindings::RebuildReport> {
+        let snapshot = self.snapshot().map_err(|e| Into::<Error>::into(e))?;
+        Ok(RebuildStatistics::from(&snapshot.report).into())
+    }
+
+    fn SetMatchingOptions(&self, swappedorder: bool, casefolded: bool) -> windows_core::Result<()> {
+        *self.matching.write().map_err(fail)? = MatchingOptions {
+            swapped_order: swappedorder,
+            case_folded: casefolded,
+        };
+        Ok(())
+    }
+
+    fn Export(&self, format: bindings::ExportFormat) -> windows_core::Result<HSTRING> {
+        let snapshot = self.snapshot().map_err(|e| Into::<Error>::into(e))?;
+        let entries = snapshot.export_entries();
+        let names = &snapshot.keysym_names;
+
+        Ok(match format {
+            bindings::ExportFormat::XCompose => export::to_xcompose(&entries, &names),
+            bindings::ExportFormat::Json => export::to_json(&entries, &names)?,
+            bindings::ExportFormat::Csv => export::to_csv(&entries, &names),
+            _ => return Err(E_INVALIDARG.into()),
+        }
+        .into())
+    }
+
+    fn CheatSheet(
+        &self,
+        format: bindings::CheatSheetFormat,
+        sources: &HSTRING,
+    ) -> windows_core::Result<HSTRING> {
+        let sources = parse_sources(&sources.to_string())?;
+        let snapshot = self.snapshot().map_err(|e| Into::<Error>::into(e))?;
+        let entries = snapshot
+            .export_entries()
+            .into_iter()
+            .filter(|entry| sources.contains(&entry.source))
+            .map(|entry| match snapshot.localized_names.get(&entry.result) {
+                Some(name) => ExportEntry {
+                    description: name.to_string(),
+                    ..entry
+                },
+                None => entry,
             })
             .collect::<Vec<_>>();
+        let names = &snapshot.keysym_names;
 
-        for (locale, files) in prefetch {
-            let mut result_vec = Vec::new();
-            for variant in files {
-                for a in load_annotation_file(&variant?.get()?.Path()?)? {
-                    if a.r#type.is_some() {
-                        if !char_to_name.contains_key(&a.cp) {
-                            char_to_name.insert(a.cp, Box::from(a.text));
-                        }
-                    } else {
-                        let main_char: Rc<str> = Rc::from(a.cp);
-                        let words = a
-                            .text
-                            .split_terminator("|")
-                            .map(|s| s.trim())
-                            .collect::<Vec<_>>();
-                        for word in words {
-                            result_vec.push(AnnotationPair {
-                                char: main_char.clone(),
-                                desc: Box::from(word),
-                            });
-                        }
-                    }
-                }
-            }
+        Ok(match format {
+            bindings::CheatSheetFormat::Markdown => cheat_sheet::to_markdown(
+                &entries,
+                &names,
+                &snapshot.emoji_data,
+                &snapshot.symbol_categories,
+            ),
+            bindings::CheatSheetFormat::Html => cheat_sheet::to_html(
+                &entries,
+                &names,
+                &snapshot.emoji_data,
+                &snapshot.symbol_categories,
+            ),
+            _ => return Err(E_INVALIDARG.into()),
+        }
+        .into())
+    }
 
-            annotation_map.insert(*locale, result_vec.into_boxed_slice());
-        }
-
-        let mut build = MapBuilder::memory();
-        let mut value_to_string = HashMap::new();
-        let mut string_to_sequence = HashMap::new();
-        let mut basic_index = 0;
-        let mut extra_index = u32::MAX.into();
-
-        for (key, value) in composedef {
-            match value {
-                MappedString::Basic(e) => {
-                    basic_index += 1;
-                    value_to_string.insert(basic_index, MappedString::Basic(e.clone()));
-                    string_to_sequence.insert(e.to_string(), key.clone());
-                    build.insert(key.clone(), basic_index).map_err(fail)?;
-                    char_to_name.insert(
-                        e.to_string(),
-                        char_to_unicode_name(e.chars().next().expect("string not empty"))?,
-                    );
-                }
-                MappedString::Extra(_) => {
-                    extra_index += 1;
-                    value_to_string.insert(extra_index, value.clone());
-                    string_to_sequence.insert(value.to_string(), key.clone());
-                    build.insert(key.clone(), extra_index).map_err(fail)?;
-                }
+    fn SetSearchLanguages(
+        &self,
+        languages: Option<&IVectorView<HSTRING>>,
+    ) -> windows_core::Result<()> {
+        let languages = languages
+            .into_iter()
+            .flatten()
+            .map(|lang| lang.to_string())
+            .collect::<Vec<_>>();
+        let previous = std::mem::replace(
+            &mut *self.chosen_languages.write().map_err(fail)?,
+            (!languages.is_empty()).then(|| languages.into_boxed_slice()),
+        );
+
+        // Load the annotations of the new languages. The languages and the tables stay as they
+        // were when that fails.
+        let build_paths = read_lock(&self.build_paths)
+            .map_err(|e| Into::<Error>::into(e))?
+            .clone();
+        if let Some([keysymdef, composedef, annotations]) = build_paths {
+            if let Err(e) = self.Rebuild(&keysymdef, &composedef, &annotations) {
+                *self.chosen_languages.write().map_err(fail)? = previous;
+                return Err(e);
             }
         }
 
-        *self.prefix_map.write().map_err(fail)? = build.into_map();
-        *self.value_to_string.write().map_err(fail)? = value_to_string;
-        *self.char_to_name.write().map_err(fail)? = char_to_name;
-        *self.string_to_sequence.write().map_err(fail)? = string_to_sequence;
-        *self.annotations.write().map_err(fail)? = annotation_map;
+        Ok(())
+    }
+
+    fn GetSearchLanguages(&self) -> windows_core::Result<IVectorView<HSTRING>> {
+        self.search_languages()?
+            .into_iter()
+            .map(HSTRING::from)
+            .collect::<Vec<_>>()
+            .try_into()
+    }
 
+    fn GetEmojiVariants(
+        &self,
+        emoji: &HSTRING,
+    ) -> windows_core::Result<IVectorView<bindings::SequenceDescription>> {
+        let emoji = emoji.to_string();
+        let snapshot = self.snapshot().map_err(|e| Into::<Error>::into(e))?;
+        let families = &snapshot.emoji_families;
+        let Some(base) = families.base_of(&emoji) else {
+            return vec![snapshot.describe_value(&emoji)?].try_into();
+        };
+
+        std::iter::once(base)
+            .chain(families.variants(base))
+            .map(|value| snapshot.describe_value(value))
+            .collect::<windows_core::Result<Vec<_>>>()?
+            .try_into()
+    }
+
+    fn SetPreferredSkinTone(&self, tone: u8) -> windows_core::Result<()> {
+        if usize::from(tone) > SKIN_TONES.len() {
+            return Err(Error::new(
+                E_INVALIDARG,
+                "Skin tone must be between 0 and 5",
+            ));
+        }
+
+        *self.preferred_skin_tone.write().map_err(fail)? = (tone != 0).then_some(tone);
         Ok(())
     }
 
-    fn PotentialPrefix(
-        &self,
-        sequence: &HSTRING,
-        limit: u32,
-    ) -> windows_core::Result<IVectorView<bindings::SequenceDescription>> {
-        self.potential_prefix(&sequence.to_string(), limit as usize)
-            .map_err(|e| Into::<Error>::into(e))?
+    fn GetPreferredSkinTone(&self) -> windows_core::Result<u8> {
+        Ok(read_lock(&self.preferred_skin_tone)
+            .map_err(|e| Into::<Error>::into(e))?
+            .unwrap_or(0))
+    }
+
+    fn GetEmojiInfo(&self, emoji: &HSTRING) -> windows_core::Result<bindings::EmojiInfo> {
+        let emoji = emoji.to_string();
+        let snapshot = self.snapshot().map_err(|e| Into::<Error>::into(e))?;
+        let emoji_data = &snapshot.emoji_data;
+        let fully_qualified = emoji_data.fully_qualified(&emoji);
+        let info = emoji_data
+            .get(&emoji)
+            .or_else(|| emoji_data.get(fully_qualified?))
+            .ok_or_else(|| ERROR_NO_UNICODE_TRANSLATION)?;
+
+        Ok(bindings::EmojiInfo {
+            group: info.group.as_ref().into(),
+            subgroup: info.subgroup.as_ref().into(),
+            version: info.version.as_ref().into(),
+            qualification: match info.qualification {
+                Qualification::Component => bindings::EmojiQualification::Component,
+                Qualification::FullyQualified => bindings::EmojiQualification::FullyQualified,
+                Qualification::MinimallyQualified => {
+                    bindings::EmojiQualification::MinimallyQualified
+                }
+                Qualification::Unqualified => bindings::EmojiQualification::Unqualified,
+            },
+            fullyQualified: fully_qualified.unwrap_or(&emoji).into(),
+        })
+    }
+
+    fn GetEmojiGroups(&self) -> windows_core::Result<IVectorView<HSTRING>> {
+        self.snapshot()
+            .map_err(|e| Into::<Error>::into(e))?
+            .emoji_data
+            .groups()
+            .map(HSTRING::from)
+            .collect::<Vec<_>>()
+            .try_into()
+    }
+
+    fn GetCategories(&self) -> windows_core::Result<IVectorView<HSTRING>> {
+        let snapshot = self.snapshot().map_err(|e| Into::<Error>::into(e))?;
+        SYMBOL_CATEGORIES
+            .iter()
+            .map(|category| category.name)
+            .chain(
+                snapshot
+                    .emoji_data
+                    .groups()
+                    .filter(|&group| group != COMPONENT_GROUP),
+            )
+            .map(HSTRING::from)
+            .collect::<Vec<_>>()
             .try_into()
     }
 
-    fn Search(
+    fn GetCategorySize(&self, category: &HSTRING) -> windows_core::Result<u32> {
+        let snapshot = self.snapshot().map_err(|e| Into::<Error>::into(e))?;
+        Ok(snapshot.category_members(&category.to_string())?.len() as u32)
+    }
+
+    fn GetCategoryMembers(
         &self,
-        sequence: &HSTRING,
+        category: &HSTRING,
+        offset: u32,
         limit: u32,
     ) -> windows_core::Result<IVectorView<bindings::SequenceDescription>> {
-        let user_langs = get_user_langs()?;
-        self.filter_sequence(self.tokenize(sequence), limit as usize, &user_langs)
-            .map_err(|e| Into::<Error>::into(e))?
+        let snapshot = self.snapshot().map_err(|e| Into::<Error>::into(e))?;
+        snapshot
+            .category_members(&category.to_string())?
+            .iter()
+            .skip(offset as usize)
+            .take(limit as usize)
+            .map(|value| snapshot.describe_value(value))
+            .collect::<windows_core::Result<Vec<_>>>()?
             .try_into()
     }
 
@@ -363,20 +1250,22 @@ impl bindings::ISequenceDefinition_Impl for SequenceDefinition_Impl {
     ) -> windows_core::Result<bindings::SequenceDescription> {
         let valid_char = char::from_u32(codepoint).ok_or_else(|| ERROR_NO_UNICODE_TRANSLATION)?;
         let valid_string = valid_char.to_string();
-        let description = if let Some(mapped) = read_lock(&self.char_to_name)
-            .map_err(|e| Into::<Error>::into(e))?
-            .get(&valid_string)
-        {
+        let snapshot = self.snapshot().map_err(|e| Into::<Error>::into(e))?;
+        let description = if let Some(mapped) = snapshot.localized_names.get(&valid_string) {
+            mapped.to_string()
+        } else if let Some(mapped) = snapshot.char_to_name.get(&valid_string) {
             mapped.to_string()
         } else {
             char_to_unicode_name(valid_char)?.to_string()
         }
         .into();
+        let keywords = snapshot.keywords(&valid_string);
 
         Ok(bindings::SequenceDescription {
             sequence: h!("").to_owned(),
             result: valid_string.into(),
             description,
+            keywords,
         })
     }
 }
@@ -390,13 +1279,29 @@ impl IActivationFactory_Impl for SequenceDefinitionFactory_Impl {
     }
 }
 
-fn get_user_langs() -> windows_core::Result<Box<[SupportedLocale]>> {
-    let user_langs = GlobalizationPreferences::Languages()?;
-    let mut valid_langs = Vec::new();
-    for lang in user_langs {
-        valid_langs.push(Language::CreateLanguage(&lang)?.try_into()?);
+/// BCP-47 tags of the user languages, most preferred first.
+fn get_user_langs() -> windows_core::Result<Vec<String>> {
+    Ok(GlobalizationPreferences::Languages()?
+        .into_iter()
+        .map(|lang| lang.to_string())
+        .collect())
+}
+
+/// Parses a comma-separated list of rule packs, named after the sequence sources.
+/// An empty list enables every pack.
+fn parse_sources(sources: &str) -> windows_core::Result<Vec<SequenceSource>> {
+    if sources.trim().is_empty() {
+        return Ok(SequenceSource::ALL.to_vec());
     }
-    Ok(valid_langs.into_boxed_slice())
+
+    sources
+        .split(',')
+        .map(|name| {
+            SequenceSource::from_name(name.trim()).ok_or_else(|| {
+                Error::new(E_INVALIDARG, format!("Unknown rule pack: {}", name.trim()))
+            })
+        })
+        .collect()
 }
 
 fn char_to_unicode_name(value: char) -> windows_core::Result<Box<str>> {
@@ -412,6 +1317,12 @@ fn char_to_unicode_name(value: char) -> windows_core::Result<Box<str>> {
     Ok(name.into_boxed_str())
 }
 
+/// Approximate size of a map in bytes: its buckets plus what `heap` says each entry owns.
+fn map_size<K, V>(map: &HashMap<K, V>, heap: impl Fn(&K, &V) -> usize) -> usize {
+    map.capacity() * std::mem::size_of::<(K, V)>()
+        + map.iter().map(|(k, v)| heap(k, v)).sum::<usize>()
+}
+
 fn read_lock<T>(
     lock: &RwLock<T>,
 ) -> std::result::Result<RwLockReadGuard<'_, T>, SequenceDefinitionError> {
@@ -423,12 +1334,17 @@ mod tests {
     use std::str;
 
     use super::*;
+    use windows::Foundation::{AsyncActionProgressHandler, AsyncStatus};
     use windows_core::{ComObjectInner, Interface, Result};
 
     const KEYSYMDEF: &str = "x11-defs/keysymdef.h.br";
     const COMPOSEDEF: &str = "x11-defs/Compose.pre.br";
     const ANNOTATIONS: &str = concat!(env!("CARGO_MANIFEST_DIR"), "\\cldr");
 
+    fn keysyms(sequence: &str) -> Vec<u32> {
+        sequence.chars().map(char_to_keysym).collect()
+    }
+
     #[test]
     fn test_check_languages() -> Result<()> {
         // print BCP-47 language tag
@@ -453,7 +1369,7 @@ mod tests {
         // Attempt to translate an incomplete sequence
         let result = seqdef
             .cast_object_ref::<SequenceDefinition>()?
-            .translate_sequence("f");
+            .translate_sequence(&keysyms("f"));
         assert!(matches!(result, Err(SequenceDefinitionError::Incomplete)));
         Ok(())
     }
@@ -471,7 +1387,7 @@ mod tests {
         // Attempt to translate a nonexistent sequence
         let result = seqdef
             .cast_object_ref::<SequenceDefinition>()?
-            .translate_sequence("nonexistent");
+            .translate_sequence(&keysyms("nonexistent"));
         assert!(matches!(
             result,
             Err(SequenceDefinitionError::ValueNotFound)
@@ -479,6 +1395,109 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_match_sequence() {
+        let seqdef = SequenceDefinition::from_sequences(&[("oc", "©"), ("ocd", "🐙"), ("ox", "⊗")]);
+
+        assert_eq!(
+            seqdef.match_sequence(&keysyms("o")),
+            Ok(SequenceMatch {
+                value: None,
+                continuations: 3,
+                fallback: SequenceFallback::Exact,
+            })
+        );
+        assert_eq!(
+            seqdef.match_sequence(&keysyms("oc")),
+            Ok(SequenceMatch {
+                value: Some("©".to_string()),
+                continuations: 1,
+                fallback: SequenceFallback::Exact,
+            })
+        );
+        assert_eq!(
+            seqdef.match_sequence(&keysyms("q")),
+            Ok(SequenceMatch::default())
+        );
+
+        // The shorter sequence wins without confirmation
+        assert_eq!(
+            seqdef.translate_sequence(&keysyms("oc")),
+            Ok("©".to_string())
+        );
+        assert_eq!(
+            seqdef.translate_sequence(&keysyms("ocd")),
+            Ok("🐙".to_string())
+        );
+        assert_eq!(
+            seqdef.translate_sequence(&keysyms("o")),
+            Err(SequenceDefinitionError::Incomplete)
+        );
+        assert_eq!(
+            seqdef.translate_sequence(&keysyms("q")),
+            Err(SequenceDefinitionError::ValueNotFound)
+        );
+    }
+
+    #[test]
+    fn test_matching_fallbacks() -> Result<()> {
+        let seqdef: bindings::SequenceDefinition = SequenceDefinition::from_sequences(&[
+            ("'e", "é"),
+            ("'E", "É"),
+            ("oc", "©"),
+            ("ocd", "🐙"),
+        ])
+        .into();
+        let inner = seqdef.cast_object_ref::<SequenceDefinition>()?;
+
+        // Off by default
+        assert_eq!(
+            inner.translate_sequence(&keysyms("e'")),
+            Err(SequenceDefinitionError::ValueNotFound)
+        );
+
+        seqdef.SetMatchingOptions(true, true)?;
+        // The first key of a swapped sequence waits for the second
+        assert_eq!(
+            inner.translate_sequence(&keysyms("e")),
+            Err(SequenceDefinitionError::Incomplete)
+        );
+        assert_eq!(
+            inner.translate_sequence(&keysyms("q")),
+            Err(SequenceDefinitionError::ValueNotFound)
+        );
+        assert_eq!(
+            inner.match_sequence(&keysyms("e'")),
+            Ok(SequenceMatch {
+                value: Some("é".to_string()),
+                continuations: 0,
+                fallback: SequenceFallback::SwappedOrder,
+            })
+        );
+        // A distinct rule for the other case is kept
+        assert_eq!(
+            inner.translate_sequence(&keysyms("'E")),
+            Ok("É".to_string())
+        );
+        assert_eq!(
+            inner.match_sequence(&keysyms("OC")),
+            Ok(SequenceMatch {
+                value: Some("©".to_string()),
+                continuations: 1,
+                fallback: SequenceFallback::CaseFolded,
+            })
+        );
+        assert_eq!(
+            inner.translate_sequence(&keysyms("OCD")),
+            Ok("🐙".to_string())
+        );
+
+        let found = seqdef.MatchSequence(h!("e'"))?;
+        assert_eq!(found.result, "é");
+        assert_eq!(found.fallback, bindings::SequenceFallback::SwappedOrder);
+        Ok(())
+    }
+
     #[test]
     fn test_translate_valid_sequence() -> Result<()> {
         // Create and build the SequenceDefinition
@@ -492,13 +1511,648 @@ mod tests {
         // Assuming "fl" is a valid sequence mapped to a basic MappedString for this test
         let result = seqdef
             .cast_object_ref::<SequenceDefinition>()?
-            .translate_sequence("fl");
+            .translate_sequence(&keysyms("fl"));
         assert!(result.is_ok());
         let expected = "ﬂ"; // Expected result for the sequence "fl"
         assert_eq!(result.unwrap(), expected);
         Ok(())
     }
 
+    #[test]
+    fn test_export() -> Result<()> {
+        let seqdef = SequenceDefinitionFactory
+            .into_object()
+            .ActivateInstance()?
+            .cast::<bindings::SequenceDefinition>()?;
+
+        seqdef.Rebuild(&KEYSYMDEF.into(), &COMPOSEDEF.into(), &ANNOTATIONS.into())?;
+
+        let xcompose = seqdef.Export(bindings::ExportFormat::XCompose)?.to_string();
+        assert!(xcompose
+            .contains("<Multi_key> <f> <l>\t: \"ﬂ\"\t# LATIN SMALL LIGATURE FL (compose)\n"));
+        assert!(xcompose.contains("<Multi_key> <p> <r>\t: \"peradaban\"\t# peradaban (builtin)\n"));
+
+        let csv = seqdef.Export(bindings::ExportFormat::Csv)?.to_string();
+        assert!(csv.starts_with("sequence,result,description,source\r\n"));
+        assert_eq!(csv.lines().count(), xcompose.lines().count() + 1);
+
+        let json = seqdef.Export(bindings::ExportFormat::Json)?.to_string();
+        assert!(json.contains("{\"sequence\":\"fl\",\"keys\":[\"f\",\"l\"],\"result\":\"ﬂ\","));
+        Ok(())
+    }
+
+    #[test]
+    fn test_cheat_sheet() -> Result<()> {
+        let seqdef = SequenceDefinitionFactory
+            .into_object()
+            .ActivateInstance()?
+            .cast::<bindings::SequenceDefinition>()?;
+
+        seqdef.Rebuild(&KEYSYMDEF.into(), &COMPOSEDEF.into(), &ANNOTATIONS.into())?;
+
+        let markdown = seqdef
+            .CheatSheet(bindings::CheatSheetFormat::Markdown, h!(""))?
+            .to_string();
+        assert!(markdown.contains("\n## Arrows\n"));
+        assert!(markdown.contains("| `Compose` `-` `>` | → |"));
+        assert!(markdown.contains("| `Compose` `p` `r` | peradaban |"));
+
+        let builtin = seqdef
+            .CheatSheet(bindings::CheatSheetFormat::Html, h!("builtin"))?
+            .to_string();
+        assert!(builtin.contains("<kbd>Compose</kbd><kbd>w</kbd><kbd>k</kbd>"));
+        assert!(!builtin.contains("<h2>Arrows</h2>"));
+
+        assert!(seqdef
+            .CheatSheet(bindings::CheatSheetFormat::Html, h!("compose, nonexistent"))
+            .is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_sources() -> Result<()> {
+        assert_eq!(parse_sources("")?, SequenceSource::ALL);
+        assert_eq!(
+            parse_sources("builtin, compose")?,
+            [SequenceSource::Builtin, SequenceSource::Compose]
+        );
+        assert!(parse_sources("builtin,").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_search_languages() -> Result<()> {
+        let seqdef = SequenceDefinitionFactory
+            .into_object()
+            .ActivateInstance()?
+            .cast::<bindings::SequenceDefinition>()?;
+
+        // Before the first build, the languages are only stored
+        let chosen: IVectorView<HSTRING> =
+            vec![h!("fr-CA").clone(), h!("en").clone()].try_into()?;
+        seqdef.SetSearchLanguages(&chosen)?;
+        assert_eq!(
+            seqdef.GetSearchLanguages()?.into_iter().collect::<Vec<_>>(),
+            [h!("fr-CA").clone(), h!("en").clone()]
+        );
+
+        seqdef.Rebuild(&KEYSYMDEF.into(), &COMPOSEDEF.into(), &ANNOTATIONS.into())?;
+        let languages = seqdef
+            .cast_object_ref::<SequenceDefinition>()?
+            .snapshot()
+            .unwrap()
+            .report
+            .locales
+            .clone();
+        assert_eq!(
+            languages.first(),
+            Locale::from_bcp47("fr-CA").map(|l| l.to_string()).as_ref()
+        );
+
+        // Changing the languages loads their annotations
+        let chosen: IVectorView<HSTRING> = vec![h!("id").clone()].try_into()?;
+        seqdef.SetSearchLanguages(&chosen)?;
+        let languages = seqdef
+            .cast_object_ref::<SequenceDefinition>()?
+            .snapshot()
+            .unwrap()
+            .report
+            .locales
+            .clone();
+        assert_eq!(
+            languages.first(),
+            Locale::from_bcp47("id").map(|l| l.to_string()).as_ref()
+        );
+
+        // Languages whose rebuild fails are not kept
+        let inner = seqdef.cast_object_ref::<SequenceDefinition>()?;
+        let build_paths = inner.build_paths.write().map_err(fail)?.replace([
+            KEYSYMDEF.into(),
+            h!("missing/Compose.pre.br").clone(),
+            ANNOTATIONS.into(),
+        ]);
+        let chosen: IVectorView<HSTRING> = vec![h!("sv").clone()].try_into()?;
+        assert!(seqdef.SetSearchLanguages(&chosen).is_err());
+        assert_eq!(
+            seqdef.GetSearchLanguages()?.into_iter().collect::<Vec<_>>(),
+            [h!("id").clone()]
+        );
+        *inner.build_paths.write().map_err(fail)? = build_paths;
+
+        // An empty list goes back to the OS preference
+        let chosen: IVectorView<HSTRING> = Vec::<HSTRING>::new().try_into()?;
+        seqdef.SetSearchLanguages(&chosen)?;
+        assert_eq!(
+            seqdef.GetSearchLanguages()?.into_iter().collect::<Vec<_>>(),
+            GlobalizationPreferences::Languages()?
+                .into_iter()
+                .collect::<Vec<_>>()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_search_in_languages() -> Result<()> {
+        let seqdef = SequenceDefinitionFactory
+            .into_object()
+            .ActivateInstance()?
+            .cast::<bindings::SequenceDefinition>()?;
+
+        let chosen: IVectorView<HSTRING> = vec![
+            h!("fr").clone(),
+            h!("id").clone(),
+            h!("sv").clone(),
+            h!("tr").clone(),
+        ]
+        .try_into()?;
+        seqdef.SetSearchLanguages(&chosen)?;
+        seqdef.Rebuild(&KEYSYMDEF.into(), &COMPOSEDEF.into(), &ANNOTATIONS.into())?;
+        let finds_star = |query: &str| -> Result<bool> {
+            Ok(seqdef
+                .Search(&query.into(), u32::MAX)?
+                .into_iter()
+                .any(|desc| desc.result == "⭐"))
+        };
+
+        assert!(finds_star("etoile")?);
+        assert!(finds_star("bintang")?);
+        // Swedish keeps ä apart from a, although French is searched first
+        assert!(finds_star("stjärna")?);
+        assert!(!finds_star("stjarna")?);
+        // Turkish folds I to ı
+        assert!(finds_star("YILDIZ")?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_localized_prefix_descriptions() -> Result<()> {
+        let seqdef = SequenceDefinitionFactory
+            .into_object()
+            .ActivateInstance()?
+            .cast::<bindings::SequenceDefinition>()?;
+
+        let chosen: IVectorView<HSTRING> = vec![h!("fr-CA").clone()].try_into()?;
+        seqdef.SetSearchLanguages(&chosen)?;
+        seqdef.Rebuild(&KEYSYMDEF.into(), &COMPOSEDEF.into(), &ANNOTATIONS.into())?;
+
+        let results = seqdef.PotentialPrefix(h!("->"), 1)?;
+        let arrow = results.GetAt(0)?;
+        assert_eq!(arrow.result, "→");
+        // French name and keywords, inherited by fr_CA from fr
+        assert_ne!(arrow.description, "RIGHTWARDS ARROW");
+        assert!(arrow.keywords.to_string().contains("flèche"));
+
+        // Sequences without CLDR data keep their Unicode name
+        let results = seqdef.PotentialPrefix(h!("fl"), 1)?;
+        let ligature = results.GetAt(0)?;
+        assert_eq!(ligature.description, "LATIN SMALL LIGATURE FL");
+        assert!(ligature.keywords.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_emoji_variants() -> Result<()> {
+        let seqdef = SequenceDefinitionFactory
+            .into_object()
+            .ActivateInstance()?
+            .cast::<bindings::SequenceDefinition>()?;
+
+        let chosen: IVectorView<HSTRING> = vec![h!("en").clone()].try_into()?;
+        seqdef.SetSearchLanguages(&chosen)?;
+        seqdef.Rebuild(&KEYSYMDEF.into(), &COMPOSEDEF.into(), &ANNOTATIONS.into())?;
+
+        // A variant brings its whole family, base first
+        let family = seqdef
+            .GetEmojiVariants(h!("👍🏽"))?
+            .into_iter()
+            .map(|desc| desc.result.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(family, ["👍", "👍🏻", "👍🏼", "👍🏽", "👍🏾", "👍🏿"]);
+        assert_eq!(seqdef.GetEmojiVariants(h!("→"))?.Size()?, 1);
+
+        // Search shows the family once, in the preferred skin tone
+        let results = seqdef.Search(h!("thumbs up"), 10)?;
+        let thumbs = results
+            .into_iter()
+            .filter(|desc| desc.result.to_string().starts_with('👍'))
+            .collect::<Vec<_>>();
+        assert_eq!(thumbs.len(), 1);
+        assert_eq!(thumbs[0].result, "👍");
+
+        seqdef.SetPreferredSkinTone(4)?;
+        assert_eq!(seqdef.GetPreferredSkinTone()?, 4);
+        let results = seqdef.Search(h!("thumbs up"), 10)?;
+        assert!(results.into_iter().any(|desc| desc.result == "👍🏾"));
+
+        assert!(seqdef.SetPreferredSkinTone(6).is_err());
+        seqdef.SetPreferredSkinTone(0)?;
+        assert_eq!(seqdef.GetPreferredSkinTone()?, 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_emoji_info() -> Result<()> {
+        let seqdef = SequenceDefinitionFactory
+            .into_object()
+            .ActivateInstance()?
+            .cast::<bindings::SequenceDefinition>()?;
+
+        seqdef.Rebuild(&KEYSYMDEF.into(), &COMPOSEDEF.into(), &ANNOTATIONS.into())?;
+
+        let smiling = seqdef.GetEmojiInfo(h!("☺"))?;
+        assert_eq!(smiling.group, "Smileys & Emotion");
+        assert_eq!(
+            smiling.qualification,
+            bindings::EmojiQualification::Unqualified
+        );
+        assert_eq!(smiling.fullyQualified, "☺️");
+        assert_eq!(
+            seqdef.GetEmojiInfo(h!("👍🏽"))?.qualification,
+            bindings::EmojiQualification::FullyQualified
+        );
+        assert!(seqdef.GetEmojiInfo(h!("→")).is_err());
+
+        let groups = seqdef.GetEmojiGroups()?.into_iter().collect::<Vec<_>>();
+        assert_eq!(groups.first(), Some(h!("Smileys & Emotion")));
+        assert!(groups.contains(h!("Flags")));
+
+        // Search returns the fully-qualified form of annotated emoji
+        let results = seqdef.Search(h!("☺"), 5)?;
+        assert!(results.into_iter().any(|desc| desc.result == "☺️"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_search_characters() -> Result<()> {
+        let seqdef = SequenceDefinitionFactory
+            .into_object()
+            .ActivateInstance()?
+            .cast::<bindings::SequenceDefinition>()?;
+
+        let chosen: IVectorView<HSTRING> = vec![h!("en").clone()].try_into()?;
+        seqdef.SetSearchLanguages(&chosen)?;
+        seqdef.Rebuild(&KEYSYMDEF.into(), &COMPOSEDEF.into(), &ANNOTATIONS.into())?;
+        let search = |query: &HSTRING| -> Result<Vec<String>> {
+            Ok(seqdef
+                .Search(query, u32::MAX)?
+                .into_iter()
+                .map(|desc| desc.result.to_string())
+                .collect())
+        };
+
+        // Code points and character references give the character first
+        for query in ["U+2192", "0x2192", "&rarr;", "&#8594;", "&#x2192;", "→"] {
+            let results = seqdef.Search(&query.into(), 5)?;
+            assert_eq!(results.GetAt(0)?.result, "→", "{query}");
+            assert_eq!(results.GetAt(0)?.sequence, "->");
+        }
+        assert!(search(h!("U+2192 arrow"))?.is_empty());
+
+        // Then characters of the same block and emoji variants
+        assert!(search(h!("→"))?.contains(&"←".to_string()));
+        let thumbs = search(h!("👍🏽"))?;
+        assert!(thumbs.contains(&"👍".to_string()));
+        assert!(thumbs.contains(&"👍🏿".to_string()));
+
+        // A letter is searched as the start of a word, followed by the letters of the same base
+        let letters = search(h!("é"))?;
+        assert!(letters.contains(&"🥚".to_string()));
+        assert!(letters.contains(&"É".to_string()));
+        assert!(letters.contains(&"è".to_string()));
+        Ok(())
+    }
+
+    #[test]
+    fn test_categories() -> Result<()> {
+        let seqdef = SequenceDefinitionFactory
+            .into_object()
+            .ActivateInstance()?
+            .cast::<bindings::SequenceDefinition>()?;
+
+        seqdef.Rebuild(&KEYSYMDEF.into(), &COMPOSEDEF.into(), &ANNOTATIONS.into())?;
+
+        let categories = seqdef.GetCategories()?.into_iter().collect::<Vec<_>>();
+        assert_eq!(categories.first(), Some(h!("Arrows")));
+        assert!(categories.contains(h!("Smileys & Emotion")));
+        assert!(!categories.contains(h!("Component")));
+
+        // Members come with their name and compose sequence
+        let arrows = seqdef.GetCategoryMembers(h!("Arrows"), 0, 4)?;
+        assert_eq!(arrows.Size()?, 4);
+        let right = arrows.GetAt(2)?;
+        assert_eq!(right.result, "→");
+        assert_eq!(right.sequence, "->");
+        assert!(!right.description.is_empty());
+
+        // Pages follow each other
+        let size = seqdef.GetCategorySize(h!("Smileys & Emotion"))?;
+        assert!(size > 100);
+        let page = seqdef.GetCategoryMembers(h!("Smileys & Emotion"), 1, 2)?;
+        let first = seqdef.GetCategoryMembers(h!("Smileys & Emotion"), 0, 3)?;
+        assert_eq!(page.GetAt(0)?.result, first.GetAt(1)?.result);
+        assert_eq!(
+            seqdef
+                .GetCategoryMembers(h!("Smileys & Emotion"), size, 10)?
+                .Size()?,
+            0
+        );
+
+        // Skin tones are left to the emoji families
+        let people = seqdef.GetCategoryMembers(h!("People & Body"), 0, u32::MAX)?;
+        assert!(people.into_iter().any(|desc| desc.result == "👍"));
+        let people = seqdef.GetCategoryMembers(h!("People & Body"), 0, u32::MAX)?;
+        assert!(!people.into_iter().any(|desc| desc.result == "👍🏽"));
+
+        assert!(seqdef.GetCategorySize(h!("Nonexistent")).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_rebuild_report() -> Result<()> {
+        let seqdef = SequenceDefinitionFactory
+            .into_object()
+            .ActivateInstance()?
+            .cast::<bindings::SequenceDefinition>()?;
+
+        let chosen: IVectorView<HSTRING> = vec![h!("en").clone(), h!("tlh").clone()].try_into()?;
+        seqdef.SetSearchLanguages(&chosen)?;
+        seqdef.Rebuild(&KEYSYMDEF.into(), &COMPOSEDEF.into(), &ANNOTATIONS.into())?;
+
+        let report = seqdef.GetRebuildReport()?;
+        let missing = report
+            .into_iter()
+            .find(|d| d.kind == bindings::DiagnosticKind::LocaleMissing)
+            .expect("tlh has no annotations");
+        assert!(!missing.skipped);
+        assert_eq!(missing.message, "No annotations for language tlh");
+        // Optional files such as the HTML entities are bundled too
+        assert!(!seqdef
+            .GetRebuildReport()?
+            .into_iter()
+            .any(|d| d.kind == bindings::DiagnosticKind::Io));
+
+        // Files that cannot be read fail the rebuild and name the file
+        let error = seqdef
+            .Rebuild(
+                &KEYSYMDEF.into(),
+                h!("missing/Compose.pre.br"),
+                &ANNOTATIONS.into(),
+            )
+            .unwrap_err();
+        assert!(error.message().contains("missing/Compose.pre.br"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_rebuild_async() -> Result<()> {
+        let seqdef = SequenceDefinitionFactory
+            .into_object()
+            .ActivateInstance()?
+            .cast::<bindings::SequenceDefinition>()?;
+
+        let inner = seqdef.cast_object_ref::<SequenceDefinition>()?;
+
+        // A rebuild waits for the one in progress, so the handler is set before the first phase
+        let (tx, rx) = std::sync::mpsc::channel();
+        let rebuilding = inner.rebuilt.lock().map_err(fail)?;
+        let action =
+            seqdef.RebuildAsync(&KEYSYMDEF.into(), &COMPOSEDEF.into(), &ANNOTATIONS.into())?;
+        action.SetProgress(&AsyncActionProgressHandler::new(move |_, phase| {
+            tx.send(*phase).map_err(fail)
+        }))?;
+        drop(rebuilding);
+        action.get()?;
+        assert_eq!(
+            rx.try_iter().collect::<Vec<_>>(),
+            [
+                bindings::RebuildPhase::Decompress,
+                bindings::RebuildPhase::Parse,
+                bindings::RebuildPhase::Annotations,
+                bindings::RebuildPhase::NameLookup,
+                bindings::RebuildPhase::FstBuild,
+            ]
+        );
+
+        // Cancel as soon as the second rebuild starts; the tables of the first stay in use
+        let rebuilding = inner.rebuilt.lock().map_err(fail)?;
+        let action =
+            seqdef.RebuildAsync(&KEYSYMDEF.into(), &COMPOSEDEF.into(), &ANNOTATIONS.into())?;
+        action.SetProgress(&AsyncActionProgressHandler::new(|action, phase| {
+            if let (Some(action), bindings::RebuildPhase::Decompress) = (action, *phase) {
+                action.Cancel()?;
+            }
+            Ok(())
+        }))?;
+        drop(rebuilding);
+        assert!(action.get().is_err());
+        assert_eq!(action.Status()?, AsyncStatus::Canceled);
+        assert_eq!(
+            inner.translate_sequence(&keysyms("oc")),
+            Ok("©".to_string())
+        );
+
+        // A rebuild overtaken by a later request leaves the tables of the later one
+        let earlier = inner.request_rebuild();
+        let later = inner.request_rebuild();
+        let build_paths = [KEYSYMDEF.into(), COMPOSEDEF.into(), ANNOTATIONS.into()];
+        inner.rebuild(later, &build_paths, false, |_| Ok(()))?;
+        let missing = [
+            KEYSYMDEF.into(),
+            h!("missing/Compose.pre.br").clone(),
+            ANNOTATIONS.into(),
+        ];
+        inner.rebuild(earlier, &missing, false, |_| Ok(()))?;
+        assert_eq!(
+            inner.translate_sequence(&keysyms("oc")),
+            Ok("©".to_string())
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_query_during_rebuild() -> Result<()> {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<Snapshot>();
+
+        let seqdef = SequenceDefinitionFactory
+            .into_object()
+            .ActivateInstance()?
+            .cast::<bindings::SequenceDefinition>()?;
+        seqdef.Rebuild(&KEYSYMDEF.into(), &COMPOSEDEF.into(), &ANNOTATIONS.into())?;
+        let prefixed = seqdef.PotentialPrefix(h!("o"), 50)?.Size()?;
+
+        // Every lookup sees one whole table, however the rebuilds interleave with it
+        let done = std::sync::atomic::AtomicBool::new(false);
+        std::thread::scope(|scope| {
+            let readers = (0..4)
+                .map(|_| {
+                    scope.spawn(|| -> Result<()> {
+                        let definition = seqdef.cast_object_ref::<SequenceDefinition>()?;
+                        while !done.load(std::sync::atomic::Ordering::Relaxed) {
+                            assert_eq!(seqdef.PotentialPrefix(h!("o"), 50)?.Size()?, prefixed);
+                            assert_eq!(
+                                definition.translate_sequence(&keysyms("oc")),
+                                Ok("©".to_string())
+                            );
+                            assert!(!seqdef.Export(bindings::ExportFormat::Csv)?.is_empty());
+                        }
+                        Ok(())
+                    })
+                })
+                .collect::<Vec<_>>();
+
+            let rebuilt = (0..5).try_for_each(|_| {
+                seqdef.Rebuild(&KEYSYMDEF.into(), &COMPOSEDEF.into(), &ANNOTATIONS.into())
+            });
+            done.store(true, std::sync::atomic::Ordering::Relaxed);
+            readers
+                .into_iter()
+                .try_for_each(|reader| reader.join().expect("reader panicked"))?;
+            rebuilt
+        })
+    }
+
+    #[test]
+    fn test_hot_reload() -> Result<()> {
+        let seqdef = SequenceDefinitionFactory
+            .into_object()
+            .ActivateInstance()?
+            .cast::<bindings::SequenceDefinition>()?;
+
+        let path = std::env::temp_dir().join("simbolmudah-hot-reload.pre.br");
+        std::fs::copy(COMPOSEDEF, &path).map_err(fail)?;
+        let compose = HSTRING::from(path.to_string_lossy().as_ref());
+        seqdef.Rebuild(&KEYSYMDEF.into(), &compose, &ANNOTATIONS.into())?;
+
+        let (tx, rx) = std::sync::mpsc::channel();
+        let token =
+            seqdef.OnReloadFailed(&TypedEventHandler::new(move |_, message: &HSTRING| {
+                tx.send(message.to_string()).map_err(fail)
+            }))?;
+        seqdef.SetHotReload(true, 50)?;
+
+        // A Compose file that cannot be read any more keeps the tables it gave
+        std::fs::remove_file(&path).map_err(fail)?;
+        let message = rx.recv_timeout(Duration::from_secs(5)).map_err(fail)?;
+        assert!(message.contains("simbolmudah-hot-reload.pre.br"));
+        assert_eq!(
+            seqdef
+                .cast_object_ref::<SequenceDefinition>()?
+                .translate_sequence(&keysyms("oc")),
+            Ok("©".to_string())
+        );
+
+        seqdef.SetHotReload(false, 0)?;
+        seqdef.RemoveOnReloadFailed(token)?;
+        assert!(seqdef.SetHotReload(true, 0).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_rebuild_statistics() -> Result<()> {
+        let seqdef = SequenceDefinitionFactory
+            .into_object()
+            .ActivateInstance()?
+            .cast::<bindings::SequenceDefinition>()?;
+
+        let chosen: IVectorView<HSTRING> = vec![h!("id").clone(), h!("en").clone()].try_into()?;
+        seqdef.SetSearchLanguages(&chosen)?;
+        seqdef.Rebuild(&KEYSYMDEF.into(), &COMPOSEDEF.into(), &ANNOTATIONS.into())?;
+
+        let statistics = seqdef.GetRebuildStatistics()?;
+
+        let sequences = statistics.GetSequenceCounts()?;
+        assert_eq!(sequences.GetAt(0)?.source, "compose");
+        assert!(sequences.GetAt(0)?.count > 1000);
+        assert_eq!(
+            sequences.GetAt(1)?,
+            bindings::RebuildSequenceCount {
+                source: "builtin".into(),
+                count: 2
+            }
+        );
+
+        assert_eq!(
+            statistics.GetLocales()?.into_iter().collect::<Vec<_>>(),
+            [h!("id").clone(), h!("en").clone()]
+        );
+        assert_eq!(
+            statistics
+                .GetPhaseTimings()?
+                .into_iter()
+                .map(|timing| timing.phase)
+                .collect::<Vec<_>>(),
+            [
+                bindings::RebuildPhase::Decompress,
+                bindings::RebuildPhase::Parse,
+                bindings::RebuildPhase::Annotations,
+                bindings::RebuildPhase::NameLookup,
+                bindings::RebuildPhase::FstBuild,
+            ]
+        );
+        let tables = statistics.GetTableSizes()?.into_iter().collect::<Vec<_>>();
+        assert!(tables
+            .iter()
+            .any(|table| table.name == "prefix_map" && table.bytes > 0));
+        assert!(!tables.iter().any(|table| table.name == "value_to_string"));
+
+        let skipped = seqdef
+            .GetRebuildReport()?
+            .into_iter()
+            .filter(|d| d.skipped)
+            .collect::<Vec<_>>();
+        assert_eq!(statistics.GetSkippedLineCount()? as usize, skipped.len());
+        assert_eq!(
+            statistics
+                .GetSkippedLineSamples()?
+                .into_iter()
+                .collect::<Vec<_>>(),
+            skipped[..skipped.len().min(rebuild_statistics::SKIPPED_LINE_SAMPLES)]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_shortcodes() -> Result<()> {
+        let seqdef = SequenceDefinitionFactory
+            .into_object()
+            .ActivateInstance()?
+            .cast::<bindings::SequenceDefinition>()?;
+
+        let chosen: IVectorView<HSTRING> = vec![h!("en").clone()].try_into()?;
+        seqdef.SetSearchLanguages(&chosen)?;
+        seqdef.Rebuild(&KEYSYMDEF.into(), &COMPOSEDEF.into(), &ANNOTATIONS.into())?;
+        let definition = seqdef.cast_object_ref::<SequenceDefinition>()?;
+
+        // From the bundled table, then from the CLDR names and keywords
+        assert_eq!(
+            definition.translate_shortcode("thumbsup"),
+            Ok("👍".to_string())
+        );
+        assert_eq!(
+            definition.translate_shortcode("heart"),
+            Ok("❤️".to_string())
+        );
+        assert_eq!(
+            definition.translate_shortcode("thumbs_up"),
+            Ok("👍".to_string())
+        );
+        assert_eq!(
+            definition.translate_shortcode("nonexistent"),
+            Err(SequenceDefinitionError::ValueNotFound)
+        );
+        assert_eq!(definition.is_shortcode_prefix("thumb"), Ok(true));
+        assert_eq!(definition.is_shortcode_prefix("qqq"), Ok(false));
+
+        let suggestions = seqdef.ShortcodePrefix(h!(":smi"), 3)?;
+        assert_eq!(suggestions.Size()?, 3);
+        let smile = suggestions.GetAt(0)?;
+        assert_eq!(smile.sequence, ":smile:");
+        assert_eq!(smile.result, "😄");
+        assert!(!smile.description.is_empty());
+        Ok(())
+    }
+
     #[test]
     fn test_unicode() -> Result<()> {
         let name: Box<str> = "#⃣".to_string().into();
@@ -0,0 +1,197 @@
+use std::sync::{
+    atomic::{AtomicU32, Ordering},
+    Arc, Mutex,
+};
+
+use windows::{
+    core::{implement, AgileReference, Error, Result, HRESULT},
+    Foundation::{
+        AsyncActionProgressHandler, AsyncActionWithProgressCompletedHandler, AsyncStatus,
+        IAsyncActionWithProgress, IAsyncActionWithProgress_Impl, IAsyncInfo, IAsyncInfo_Impl,
+    },
+    Win32::Foundation::{E_ABORT, E_ILLEGAL_DELEGATE_ASSIGNMENT, E_ILLEGAL_METHOD_CALL},
+};
+use windows_core::IUnknownImpl;
+
+use crate::{bindings, utils::functions::fail};
+
+/// Ids of the actions of this process, for `IAsyncInfo::Id`.
+static NEXT_ID: AtomicU32 = AtomicU32::new(1);
+
+struct ActionState {
+    status: AsyncStatus,
+    /// Code and message of the failure, kept apart as `Error` cannot cross threads.
+    error: Option<(HRESULT, String)>,
+    /// Handlers are called from the thread pool, so they are held like `DelegateStorage` does.
+    progress: Option<AgileReference<AsyncActionProgressHandler<bindings::RebuildPhase>>>,
+    completed:
+        Option<AgileReference<AsyncActionWithProgressCompletedHandler<bindings::RebuildPhase>>>,
+}
+
+/// The action returned by `RebuildAsync`. It reports each phase to its own progress handler,
+/// so callers of concurrent rebuilds are only told about theirs.
+#[implement(IAsyncActionWithProgress<bindings::RebuildPhase>, IAsyncInfo)]
+struct RebuildAction {
+    id: u32,
+    state: Arc<Mutex<ActionState>>,
+}
+
+/// The side of a `RebuildAction` that the rebuild reports to.
+pub(super) struct RebuildReporter {
+    action: IAsyncActionWithProgress<bindings::RebuildPhase>,
+    state: Arc<Mutex<ActionState>>,
+}
+
+/// Creates an action that stays started until `RebuildReporter::finish`.
+pub(super) fn rebuild_action() -> (
+    IAsyncActionWithProgress<bindings::RebuildPhase>,
+    RebuildReporter,
+) {
+    let state = Arc::new(Mutex::new(ActionState {
+        status: AsyncStatus::Started,
+        error: None,
+        progress: None,
+        completed: None,
+    }));
+    let action: IAsyncActionWithProgress<bindings::RebuildPhase> = RebuildAction {
+        id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
+        state: state.clone(),
+    }
+    .into();
+
+    (action.clone(), RebuildReporter { action, state })
+}
+
+impl RebuildReporter {
+    /// Tells the progress handler that `phase` starts. Fails once the action is canceled, which
+    /// stops the rebuild.
+    pub(super) fn report(&self, phase: bindings::RebuildPhase) -> Result<()> {
+        let handler = {
+            let state = self.state.lock().map_err(fail)?;
+            if state.status == AsyncStatus::Canceled {
+                return Err(Error::new(E_ABORT, "Operation canceled"));
+            }
+            state.progress.clone()
+        };
+
+        match handler {
+            Some(handler) => handler.resolve()?.Invoke(&self.action, phase),
+            None => Ok(()),
+        }
+    }
+
+    /// Ends the action with the result of the rebuild and tells the completion handler.
+    pub(super) fn finish(self, result: Result<()>) -> Result<()> {
+        let (status, handler) = {
+            let mut state = self.state.lock().map_err(fail)?;
+            match result {
+                Ok(()) => state.status = AsyncStatus::Completed,
+                Err(error) => {
+                    if state.status != AsyncStatus::Canceled {
+                        state.status = AsyncStatus::Error;
+                    }
+                    state.error = Some((error.code(), error.message()));
+                }
+            }
+            (state.status, state.completed.take())
+        };
+
+        match handler {
+            Some(handler) => handler.resolve()?.Invoke(&self.action, status),
+            None => Ok(()),
+        }
+    }
+}
+
+impl IAsyncInfo_Impl for RebuildAction_Impl {
+    fn Id(&self) -> Result<u32> {
+        Ok(self.id)
+    }
+
+    fn Status(&self) -> Result<AsyncStatus> {
+        Ok(self.state.lock().map_err(fail)?.status)
+    }
+
+    fn ErrorCode(&self) -> Result<HRESULT> {
+        let state = self.state.lock().map_err(fail)?;
+        Ok(state.error.as_ref().map_or(HRESULT(0), |(code, _)| *code))
+    }
+
+    fn Cancel(&self) -> Result<()> {
+        let mut state = self.state.lock().map_err(fail)?;
+        if state.status == AsyncStatus::Started {
+            state.status = AsyncStatus::Canceled;
+        }
+        Ok(())
+    }
+
+    fn Close(&self) -> Result<()> {
+        Ok(())
+    }
+}
+
+impl IAsyncActionWithProgress_Impl<bindings::RebuildPhase> for RebuildAction_Impl {
+    fn SetProgress(
+        &self,
+        handler: Option<&AsyncActionProgressHandler<bindings::RebuildPhase>>,
+    ) -> Result<()> {
+        self.state.lock().map_err(fail)?.progress = handler.map(AgileReference::new).transpose()?;
+        Ok(())
+    }
+
+    fn Progress(&self) -> Result<AsyncActionProgressHandler<bindings::RebuildPhase>> {
+        // An empty error stands for a null handler
+        self.state
+            .lock()
+            .map_err(fail)?
+            .progress
+            .as_ref()
+            .ok_or_else(Error::empty)?
+            .resolve()
+    }
+
+    fn SetCompleted(
+        &self,
+        handler: Option<&AsyncActionWithProgressCompletedHandler<bindings::RebuildPhase>>,
+    ) -> Result<()> {
+        let Some(handler) = handler else {
+            return Ok(());
+        };
+
+        let status = {
+            let mut state = self.state.lock().map_err(fail)?;
+            if state.completed.is_some() {
+                return Err(E_ILLEGAL_DELEGATE_ASSIGNMENT.into());
+            }
+            if state.status == AsyncStatus::Started {
+                state.completed = Some(AgileReference::new(handler)?);
+                return Ok(());
+            }
+            state.status
+        };
+
+        // Already finished, so the handler is told at once
+        let action: IAsyncActionWithProgress<bindings::RebuildPhase> = self.to_interface();
+        handler.Invoke(&action, status)
+    }
+
+    fn Completed(&self) -> Result<AsyncActionWithProgressCompletedHandler<bindings::RebuildPhase>> {
+        self.state
+            .lock()
+            .map_err(fail)?
+            .completed
+            .as_ref()
+            .ok_or_else(Error::empty)?
+            .resolve()
+    }
+
+    fn GetResults(&self) -> Result<()> {
+        let state = self.state.lock().map_err(fail)?;
+        match (state.status, &state.error) {
+            (AsyncStatus::Completed, _) => Ok(()),
+            (AsyncStatus::Started, _) => Err(E_ILLEGAL_METHOD_CALL.into()),
+            (_, Some((code, message))) => Err(Error::new(*code, message)),
+            (_, None) => Err(Error::new(E_ABORT, "Operation canceled")),
+        }
+    }
+}
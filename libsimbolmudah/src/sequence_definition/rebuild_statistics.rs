@@ -0,0 +1,87 @@
+use windows::{
+    core::{implement, Result, HSTRING},
+    Foundation::Collections::IVectorView,
+};
+
+use super::diagnostics::RebuildReport;
+use crate::bindings;
+
+/// Skipped lines listed by `GetSkippedLineSamples`; `GetRebuildReport` lists all of them.
+pub(super) const SKIPPED_LINE_SAMPLES: usize = 5;
+
+/// The object returned by `GetRebuildStatistics`, copied from the report of one rebuild so it
+/// stays the same when the definition is rebuilt again.
+#[implement(bindings::RebuildReport)]
+pub(super) struct RebuildStatistics {
+    sequences: Vec<bindings::RebuildSequenceCount>,
+    skipped: u32,
+    samples: Vec<bindings::RebuildDiagnostic>,
+    locales: Vec<HSTRING>,
+    phases: Vec<bindings::RebuildPhaseTiming>,
+    tables: Vec<bindings::RebuildTableSize>,
+}
+
+impl From<&RebuildReport> for RebuildStatistics {
+    fn from(report: &RebuildReport) -> Self {
+        Self {
+            sequences: report
+                .sequences
+                .iter()
+                .map(|(source, count)| bindings::RebuildSequenceCount {
+                    source: (*source).into(),
+                    count: *count as u32,
+                })
+                .collect(),
+            skipped: report.skipped.len() as u32,
+            samples: report
+                .skipped
+                .iter()
+                .take(SKIPPED_LINE_SAMPLES)
+                .map(|error| error.to_diagnostic(true))
+                .collect(),
+            locales: report.locales.iter().map(HSTRING::from).collect(),
+            phases: report
+                .phases
+                .iter()
+                .map(|(phase, duration)| bindings::RebuildPhaseTiming {
+                    phase: (*phase).into(),
+                    microseconds: duration.as_micros() as u64,
+                })
+                .collect(),
+            tables: report
+                .tables
+                .iter()
+                .map(|(name, bytes)| bindings::RebuildTableSize {
+                    name: (*name).into(),
+                    bytes: *bytes as u64,
+                })
+                .collect(),
+        }
+    }
+}
+
+impl bindings::IRebuildReport_Impl for RebuildStatistics_Impl {
+    fn GetSequenceCounts(&self) -> Result<IVectorView<bindings::RebuildSequenceCount>> {
+        self.sequences.clone().try_into()
+    }
+
+    fn GetSkippedLineCount(&self) -> Result<u32> {
+        Ok(self.skipped)
+    }
+
+    fn GetSkippedLineSamples(&self) -> Result<IVectorView<bindings::RebuildDiagnostic>> {
+        self.samples.clone().try_into()
+    }
+
+    fn GetLocales(&self) -> Result<IVectorView<HSTRING>> {
+        self.locales.clone().try_into()
+    }
+
+    fn GetPhaseTimings(&self) -> Result<IVectorView<bindings::RebuildPhaseTiming>> {
+        self.phases.clone().try_into()
+    }
+
+    fn GetTableSizes(&self) -> Result<IVectorView<bindings::RebuildTableSize>> {
+        self.tables.clone().try_into()
+    }
+}
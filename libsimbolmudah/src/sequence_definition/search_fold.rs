@@ -0,0 +1,185 @@
+use windows::Win32::Globalization::{NormalizationD, NormalizationKD, NormalizeString, NORM_FORM};
+
+/// Letters that are letters of their own in a language rather than a base letter with an accent,
+/// so that searching for the base letter does not find them.
+const KEPT_LETTERS: [(&str, &str); 9] = [
+    ("da", "åÅ"),
+    ("es", "ñÑ"),
+    ("et", "äÄöÖõÕüÜ"),
+    ("fi", "åÅäÄöÖ"),
+    ("is", "áÁéÉíÍóÓúÚýÝöÖ"),
+    ("nb", "åÅ"),
+    ("nn", "åÅ"),
+    ("no", "åÅ"),
+    ("sv", "åÅäÄöÖ"),
+];
+
+/// Languages where dotted and dotless i are different letters.
+const TURKIC_LANGUAGES: [&str; 2] = ["az", "tr"];
+
+const COMBINING_DOT_ABOVE: char = '\u{307}';
+
+/// Turns words into the form they are compared in by `Search`: decomposed, without accents and
+/// case-folded, so that `Étoile`, `etoile` and `ÉTOILE` are the same. The same folding must be
+/// used for the index and the query.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub(super) struct SearchFold {
+    /// Base letters and the accent that stays with them, from `KEPT_LETTERS`.
+    kept: Vec<(char, char)>,
+    /// Whether `I` folds to `ı`, and `İ` to `i`.
+    turkic: bool,
+}
+
+impl SearchFold {
+    /// Folding for the main search language, given as the language subtag such as `sv`.
+    pub(super) fn for_language(language: &str) -> Self {
+        let letters = KEPT_LETTERS
+            .iter()
+            .find(|(l, _)| *l == language)
+            .map_or("", |(_, letters)| letters);
+
+        Self {
+            kept: decompose(letters, NormalizationD)
+                .chars()
+                .collect::<Vec<_>>()
+                .chunks_exact(2)
+                .map(|pair| (pair[0], pair[1]))
+                .collect(),
+            turkic: TURKIC_LANGUAGES.contains(&language),
+        }
+    }
+
+    pub(super) fn fold(&self, text: &str) -> String {
+        self.fold_decomposed(&decompose(text, NormalizationD))
+    }
+
+    /// The folded letter a character is written with, also through compatibility forms, so that
+    /// `é`, `E` and `ｅ` all give `e`. `None` when it is not a single letter once folded.
+    pub(super) fn base_letter(&self, value: char) -> Option<String> {
+        let folded =
+            self.fold_decomposed(&decompose(value.encode_utf8(&mut [0; 4]), NormalizationKD));
+        let mut chars = folded.chars();
+        // Kept accents stay with their letter
+        (chars.next()?.is_alphabetic() && chars.all(is_accent)).then_some(folded)
+    }
+
+    fn fold_decomposed(&self, text: &str) -> String {
+        let mut result = String::with_capacity(text.len());
+        let mut base = None;
+
+        for c in text.chars() {
+            if is_accent(c) {
+                match base.take() {
+                    Some('I') if self.turkic && c == COMBINING_DOT_ABOVE => {
+                        result.pop();
+                        result.push('i');
+                    }
+                    Some(base) if self.kept.contains(&(base, c)) => result.push(c),
+                    _ => {}
+                }
+                continue;
+            }
+
+            base = Some(c);
+            match c {
+                'I' if self.turkic => result.push('ı'),
+                'ß' | 'ẞ' => result.push_str("ss"),
+                'ς' => result.push('σ'),
+                _ => result.extend(c.to_lowercase()),
+            }
+        }
+
+        result
+    }
+}
+
+/// Combining marks used as accents on Latin, Greek and Cyrillic letters. Marks that are part of
+/// other scripts, such as Indic vowel signs, are kept.
+fn is_accent(value: char) -> bool {
+    matches!(
+        value,
+        '\u{300}'..='\u{36f}' | '\u{1ab0}'..='\u{1aff}' | '\u{1dc0}'..='\u{1dff}' | '\u{fe20}'..='\u{fe2f}'
+    )
+}
+
+/// Decomposition in `form`, NFD or NFKD. Text that cannot be normalized is left as it is.
+fn decompose(text: &str, form: NORM_FORM) -> String {
+    if text.is_ascii() {
+        return text.to_string();
+    }
+
+    let source = text.encode_utf16().collect::<Vec<_>>();
+    // The first call only estimates the length
+    let estimate = unsafe { NormalizeString(form, &source, None) };
+    if estimate <= 0 {
+        return text.to_string();
+    }
+
+    let mut buffer = vec![0; estimate as usize];
+    match unsafe { NormalizeString(form, &source, Some(&mut buffer)) } {
+        length if length > 0 => String::from_utf16_lossy(&buffer[..length as usize]),
+        _ => text.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_accents_and_case() {
+        let fold = SearchFold::for_language("fr");
+
+        assert_eq!(fold.fold("Étoile"), "etoile");
+        assert_eq!(fold.fold("ÉTOILE"), fold.fold("etoile"));
+        assert_eq!(fold.fold("café"), fold.fold("Cafe"));
+        assert_eq!(fold.fold("naïve"), "naive");
+        assert_eq!(fold.fold("Straße"), "strasse");
+        assert_eq!(fold.fold("ΟΔΟΣ"), fold.fold("οδός"));
+        // Not an accent
+        assert_eq!(fold.fold("हिन्दी"), "हिन्दी");
+    }
+
+    #[test]
+    fn test_kept_letters() {
+        let swedish = SearchFold::for_language("sv");
+        assert_ne!(swedish.fold("får"), swedish.fold("far"));
+        assert_eq!(swedish.fold("FÅR"), swedish.fold("får"));
+        assert_eq!(swedish.fold("café"), "cafe");
+
+        let spanish = SearchFold::for_language("es");
+        assert_ne!(spanish.fold("niño"), spanish.fold("nino"));
+        assert_eq!(spanish.fold("NIÑO"), spanish.fold("niño"));
+        assert_eq!(spanish.fold("canción"), "cancion");
+
+        // Other languages see a base letter with an accent
+        assert_eq!(SearchFold::for_language("en").fold("niño"), "nino");
+    }
+
+    #[test]
+    fn test_turkic_i() {
+        let turkish = SearchFold::for_language("tr");
+        assert_eq!(turkish.fold("IŞIK"), "ısık");
+        assert_eq!(turkish.fold("İstanbul"), "istanbul");
+        assert_ne!(turkish.fold("ISIK"), turkish.fold("isik"));
+
+        let english = SearchFold::for_language("en");
+        assert_eq!(english.fold("IŞIK"), "isik");
+        assert_eq!(english.fold("İstanbul"), "istanbul");
+    }
+
+    #[test]
+    fn test_base_letter() {
+        let fold = SearchFold::for_language("en");
+        for letter in ['é', 'E', 'ｅ', 'ⓔ', '𝐞'] {
+            assert_eq!(fold.base_letter(letter).as_deref(), Some("e"));
+        }
+        assert_eq!(fold.base_letter('→'), None);
+        assert_eq!(fold.base_letter('ﬁ'), None);
+
+        // Letters of their own are their own base
+        let swedish = SearchFold::for_language("sv");
+        assert_eq!(swedish.base_letter('Å'), swedish.base_letter('å'));
+        assert_ne!(swedish.base_letter('å'), swedish.base_letter('a'));
+    }
+}
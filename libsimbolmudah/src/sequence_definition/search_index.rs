@@ -0,0 +1,242 @@
+use std::collections::{BTreeMap, HashMap};
+
+use fst::{
+    automaton::{Automaton, Str},
+    IntoStreamer, Map, MapBuilder, Streamer,
+};
+
+use super::search_fold::SearchFold;
+use crate::utils::functions::fail;
+
+/// Words of the CLDR keywords and character names, and the characters themselves, to the
+/// characters they describe. Built once per `Rebuild` so that `Search` looks up the words of the
+/// query instead of scanning every annotation.
+#[derive(Debug, Default)]
+pub(super) struct SearchIndex {
+    /// Folded words and characters as given, to the span of their list in `postings`.
+    words: Map<Vec<u8>>,
+    /// Ids of characters, sorted within each list.
+    postings: Vec<u32>,
+    /// Characters by id. Ids follow the order the characters were given to `build` in, so
+    /// lower ids come first in results.
+    characters: Box<[Box<str>]>,
+    /// How the words of each search language were folded, and the default folding of names.
+    /// Queries are folded in every one of them.
+    folds: Vec<SearchFold>,
+}
+
+impl SearchIndex {
+    /// Indexes characters with the keywords of each language, folded with the rules of that
+    /// language, then characters with their names. A character is ranked by where it is first
+    /// given.
+    pub(super) fn build<'a, K>(
+        keywords: impl IntoIterator<Item = (SearchFold, K)>,
+        names: impl IntoIterator<Item = (&'a str, &'a str)>,
+    ) -> windows_core::Result<Self>
+    where
+        K: IntoIterator<Item = (&'a str, &'a str)>,
+    {
+        let mut ids: HashMap<&str, u32> = HashMap::new();
+        let mut characters = Vec::new();
+        let mut lists: BTreeMap<String, Vec<u32>> = BTreeMap::new();
+        let mut index = |character: &'a str, text: &str, fold: &SearchFold| {
+            let id = *ids.entry(character).or_insert_with(|| {
+                characters.push(Box::from(character));
+                characters.len() as u32 - 1
+            });
+
+            lists.entry(character.to_string()).or_default().push(id);
+            for word in text.split_whitespace() {
+                lists.entry(fold.fold(word)).or_default().push(id);
+            }
+        };
+
+        let mut folds = Vec::new();
+        for (fold, pairs) in keywords {
+            for (character, text) in pairs {
+                index(character, text, &fold);
+            }
+            if !folds.contains(&fold) {
+                folds.push(fold);
+            }
+        }
+        let fold = SearchFold::default();
+        for (character, text) in names {
+            index(character, text, &fold);
+        }
+        if !folds.contains(&fold) {
+            folds.push(fold);
+        }
+
+        let mut words = MapBuilder::memory();
+        let mut postings = Vec::new();
+        for (word, mut list) in lists {
+            list.sort_unstable();
+            list.dedup();
+            let span = ((postings.len() as u64) << 32) | list.len() as u64;
+            postings.extend(list);
+            words.insert(word, span).map_err(fail)?;
+        }
+
+        Ok(Self {
+            words: words.into_map(),
+            postings,
+            characters: characters.into_boxed_slice(),
+            folds,
+        })
+    }
+
+    /// Characters with a word starting with each token once folded in any of the languages,
+    /// best ranked first. A token also matches characters starting with it as typed.
+    pub(super) fn search<'a>(&'a self, tokens: &[String]) -> impl Iterator<Item = &'a str> + 'a {
+        let mut found: Option<Vec<u32>> = None;
+        for token in tokens {
+            let mut matches = self.prefixed(token);
+            let mut folded = self
+                .folds
+                .iter()
+                .map(|fold| fold.fold(token))
+                .filter(|folded| folded != token)
+                .collect::<Vec<_>>();
+            folded.sort_unstable();
+            folded.dedup();
+            for folded in folded {
+                matches.extend(self.prefixed(&folded));
+            }
+            matches.sort_unstable();
+            matches.dedup();
+
+            found = Some(match found {
+                Some(mut found) => {
+                    found.retain(|id| matches.binary_search(id).is_ok());
+                    found
+                }
+                None => matches,
+            });
+        }
+
+        found
+            .unwrap_or_default()
+            .into_iter()
+            .map(|id| self.characters[id as usize].as_ref())
+    }
+
+    /// Ids of the characters under every word starting with `prefix`, unsorted.
+    fn prefixed(&self, prefix: &str) -> Vec<u32> {
+        let mut stream = self
+            .words
+            .search(Str::new(prefix).starts_with())
+            .into_stream();
+        let mut result = Vec::new();
+        while let Some((_, span)) = stream.next() {
+            let (offset, length) = ((span >> 32) as usize, span as u32 as usize);
+            result.extend_from_slice(&self.postings[offset..offset + length]);
+        }
+        result
+    }
+
+    /// Bytes taken by each part of the index, for the rebuild statistics.
+    pub(super) fn sizes(&self) -> [(&'static str, usize); 2] {
+        [
+            ("search_words", self.words.as_fst().as_bytes().len()),
+            (
+                "search_postings",
+                self.postings.len() * std::mem::size_of::<u32>()
+                    + self.characters.iter().map(|c| c.len()).sum::<usize>()
+                    + self.characters.len() * std::mem::size_of::<Box<str>>(),
+            ),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn index() -> SearchIndex {
+        SearchIndex::build(
+            [(
+                SearchFold::for_language("en"),
+                vec![
+                    ("→", "arrow"),
+                    ("→", "right"),
+                    ("👍", "thumbs up"),
+                    ("👎", "thumbs down"),
+                ],
+            )],
+            [
+                ("→", "RIGHTWARDS ARROW"),
+                ("←", "LEFTWARDS ARROW"),
+                ("É", "LATIN CAPITAL LETTER E WITH ACUTE"),
+                ("⭐", "étoile"),
+            ],
+        )
+        .unwrap()
+    }
+
+    fn search(index: &SearchIndex, query: &str) -> Vec<String> {
+        let tokens = query
+            .split_whitespace()
+            .map(String::from)
+            .collect::<Vec<_>>();
+        index.search(&tokens).map(String::from).collect()
+    }
+
+    #[test]
+    fn test_words() {
+        let index = index();
+
+        // Keywords rank before names
+        assert_eq!(search(&index, "arrow"), ["→", "←"]);
+        assert_eq!(search(&index, "thumbs up"), ["👍"]);
+        assert_eq!(search(&index, "THUMB"), ["👍", "👎"]);
+        assert_eq!(search(&index, "left arr"), ["←"]);
+        assert!(search(&index, "arrow thumbs").is_empty());
+        assert!(search(&index, "ward").is_empty());
+        assert!(search(&index, "").is_empty());
+    }
+
+    #[test]
+    fn test_characters() {
+        let index = index();
+
+        assert_eq!(search(&index, "→"), ["→"]);
+        assert_eq!(search(&index, "→ arrow"), ["→"]);
+    }
+
+    #[test]
+    fn test_folded_words() {
+        let index = index();
+
+        assert_eq!(search(&index, "etoile"), ["⭐"]);
+        assert_eq!(search(&index, "Étoi"), ["⭐"]);
+        assert_eq!(search(&index, "ÉTOILE"), ["⭐"]);
+        // The letter itself, and words starting with it without the accent
+        assert_eq!(search(&index, "É"), ["É", "⭐"]);
+        assert_eq!(search(&index, "é"), ["É", "⭐"]);
+    }
+
+    #[test]
+    fn test_language_folds() {
+        let index = SearchIndex::build(
+            [
+                (SearchFold::for_language("fr"), vec![("⭐", "étoile")]),
+                (SearchFold::for_language("sv"), vec![("🐑", "får")]),
+                (
+                    SearchFold::for_language("tr"),
+                    vec![("🌟", "Işıltılı yıldız")],
+                ),
+            ],
+            [("⭐", "WHITE MEDIUM STAR")],
+        )
+        .unwrap();
+
+        assert_eq!(search(&index, "etoile"), ["⭐"]);
+        // Swedish keeps å apart from a, even when French comes first
+        assert_eq!(search(&index, "får"), ["🐑"]);
+        assert!(search(&index, "far").is_empty());
+        // Turkish folds I to ı, and other languages to i
+        assert_eq!(search(&index, "IŞIL"), ["🌟"]);
+        assert_eq!(search(&index, "star"), ["⭐"]);
+    }
+}
@@ -0,0 +1,273 @@
+use std::collections::HashMap;
+
+use fst::{Map, MapBuilder, Streamer};
+
+use super::{compose_reader::SequenceSource, keysym_sequence, mapped_string::MappedString};
+use crate::utils::functions::{fail, fail_message};
+
+/// Values of `prefix_map` hold the span of the result in `results` and what kind of result it
+/// is: the offset in the upper 32 bits, then 24 bits of length, the `Basic` flag, and the
+/// index of the source in `SequenceSource::ALL`.
+const LENGTH_SHIFT: u32 = 8;
+const MAX_LENGTH: usize = (1 << 24) - 1;
+const BASIC_FLAG: u64 = 1 << 7;
+const SOURCE_MASK: u64 = BASIC_FLAG - 1;
+
+/// The sequences of a `Rebuild`. Every distinct result is stored once in a string arena that the
+/// FST values point into, so a lookup reads its result without going through a hash map.
+#[derive(Debug, Default)]
+pub(super) struct SequenceTable {
+    /// Keys from `keysym_sequence::encode`, to packed result spans.
+    prefix_map: Map<Vec<u8>>,
+    /// Results back to back.
+    results: String,
+    /// Results to the span of their sequence in `keys`. A result typed by several sequences
+    /// gets the last one.
+    by_result: Map<Vec<u8>>,
+    /// Sequence keys back to back, for `by_result`.
+    keys: Vec<u8>,
+    /// Second keys of the two-key sequences of different keys, sorted, to count the sequences a
+    /// key can start in swapped order.
+    second_keys: Box<[u32]>,
+}
+
+/// A result of the table, borrowed from its arena.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) struct TableEntry<'a> {
+    pub(super) result: &'a str,
+    /// Whether the result came from `MappedString::Basic`, which is named by its character.
+    pub(super) basic: bool,
+    pub(super) source: SequenceSource,
+}
+
+impl SequenceTable {
+    /// Builds the table from sequences in key order, as a `ComposeDef` gives them.
+    pub(super) fn build(
+        sequences: impl IntoIterator<Item = (Vec<u32>, (MappedString, SequenceSource))>,
+    ) -> windows_core::Result<Self> {
+        let mut prefix_map = MapBuilder::memory();
+        let mut results = String::new();
+        let mut spans: HashMap<Box<str>, u64> = HashMap::new();
+        let mut keys = Vec::new();
+        let mut reverse = Vec::new();
+        let mut second_keys = Vec::new();
+
+        for (sequence, (value, source)) in sequences {
+            if let &[first, second] = sequence.as_slice() {
+                if first != second {
+                    second_keys.push(second);
+                }
+            }
+
+            let (basic, result) = match value {
+                MappedString::Basic(result) => (true, result),
+                MappedString::Extra(result) => (false, result),
+            };
+            let span = match spans.get(&result) {
+                Some(&span) => span,
+                None => {
+                    let span = pack_span(results.len(), result.len())?;
+                    results.push_str(&result);
+                    spans.insert(result, span);
+                    span
+                }
+            };
+            let source = SequenceSource::ALL
+                .iter()
+                .position(|s| *s == source)
+                .expect("source listed") as u64;
+
+            let key = keysym_sequence::encode(&sequence);
+            reverse.push((span, pack_span(keys.len(), key.len())?));
+            keys.extend_from_slice(&key);
+            prefix_map
+                .insert(&key, span | if basic { BASIC_FLAG } else { 0 } | source)
+                .map_err(fail)?;
+        }
+
+        // Stable, so the last sequence of a result comes last among its equals
+        reverse.sort_by(|(a, _), (b, _)| unpack_str(&results, *a).cmp(unpack_str(&results, *b)));
+        let mut by_result = MapBuilder::memory();
+        for (index, (result, key)) in reverse.iter().enumerate() {
+            let result = unpack_str(&results, *result);
+            if reverse
+                .get(index + 1)
+                .is_some_and(|(next, _)| unpack_str(&results, *next) == result)
+            {
+                continue;
+            }
+            by_result.insert(result, *key).map_err(fail)?;
+        }
+
+        Ok(Self {
+            prefix_map: prefix_map.into_map(),
+            results,
+            by_result: by_result.into_map(),
+            keys,
+            second_keys: {
+                second_keys.sort_unstable();
+                second_keys.into_boxed_slice()
+            },
+        })
+    }
+
+    pub(super) fn prefix_map(&self) -> &Map<Vec<u8>> {
+        &self.prefix_map
+    }
+
+    /// The result behind a value of `prefix_map`.
+    pub(super) fn entry(&self, value: u64) -> TableEntry<'_> {
+        TableEntry {
+            result: unpack_str(&self.results, value),
+            basic: value & BASIC_FLAG != 0,
+            source: SequenceSource::ALL[(value & SOURCE_MASK) as usize],
+        }
+    }
+
+    /// A sequence that types `result`, if any.
+    pub(super) fn sequence_of(&self, result: &str) -> Option<Vec<u32>> {
+        let (offset, length) = unpack_span(self.by_result.get(result)?);
+        Some(keysym_sequence::decode(&self.keys[offset..offset + length]))
+    }
+
+    /// How many two-key sequences of different keys end with `key`.
+    pub(super) fn ending_with(&self, key: u32) -> usize {
+        let start = self.second_keys.partition_point(|&k| k < key);
+        let end = self.second_keys.partition_point(|&k| k <= key);
+        end - start
+    }
+
+    /// How many sequences come from `source`.
+    pub(super) fn count(&self, source: SequenceSource) -> usize {
+        let mut stream = self.prefix_map.stream();
+        let mut count = 0;
+        while let Some((_, value)) = stream.next() {
+            if self.entry(value).source == source {
+                count += 1;
+            }
+        }
+        count
+    }
+
+    /// Bytes taken by each part of the table, for the rebuild statistics.
+    pub(super) fn sizes(&self) -> [(&'static str, usize); 4] {
+        [
+            ("prefix_map", self.prefix_map.as_fst().as_bytes().len()),
+            ("results", self.results.len() + self.keys.len()),
+            ("by_result", self.by_result.as_fst().as_bytes().len()),
+            (
+                "second_keys",
+                self.second_keys.len() * std::mem::size_of::<u32>(),
+            ),
+        ]
+    }
+}
+
+fn pack_span(offset: usize, length: usize) -> windows_core::Result<u64> {
+    match u32::try_from(offset) {
+        Ok(offset) if length <= MAX_LENGTH => {
+            Ok((u64::from(offset) << 32) | ((length as u64) << LENGTH_SHIFT))
+        }
+        _ => Err(fail_message("Sequence table is too large")),
+    }
+}
+
+fn unpack_span(value: u64) -> (usize, usize) {
+    (
+        (value >> 32) as usize,
+        (value >> LENGTH_SHIFT) as usize & MAX_LENGTH,
+    )
+}
+
+fn unpack_str(text: &str, value: u64) -> &str {
+    let (offset, length) = unpack_span(value);
+    &text[offset..offset + length]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table() -> SequenceTable {
+        SequenceTable::build([
+            (
+                vec![0x27, 0x65],
+                (MappedString::Basic("é".into()), SequenceSource::Compose),
+            ),
+            (
+                vec![0x63, 0x2f],
+                (MappedString::Basic("¢".into()), SequenceSource::Compose),
+            ),
+            (
+                vec![0x65, 0x27],
+                (MappedString::Basic("é".into()), SequenceSource::Compose),
+            ),
+            (
+                vec![0x70, 0x72],
+                (
+                    MappedString::Extra("peradaban".into()),
+                    SequenceSource::Builtin,
+                ),
+            ),
+        ])
+        .unwrap()
+    }
+
+    #[test]
+    fn test_entries() {
+        let table = table();
+        let value = |sequence: &[u32]| {
+            table
+                .prefix_map()
+                .get(keysym_sequence::encode(sequence))
+                .unwrap()
+        };
+
+        assert_eq!(
+            table.entry(value(&[0x63, 0x2f])),
+            TableEntry {
+                result: "¢",
+                basic: true,
+                source: SequenceSource::Compose,
+            }
+        );
+        assert_eq!(
+            table.entry(value(&[0x70, 0x72])),
+            TableEntry {
+                result: "peradaban",
+                basic: false,
+                source: SequenceSource::Builtin,
+            }
+        );
+        assert_eq!(table.count(SequenceSource::Compose), 3);
+        assert_eq!(table.count(SequenceSource::Builtin), 1);
+    }
+
+    #[test]
+    fn test_shared_results() {
+        let table = table();
+
+        // Both sequences of é point at the same text
+        assert_eq!(table.results, "é¢peradaban");
+        assert_eq!(table.sequence_of("é"), Some(vec![0x65, 0x27]));
+        assert_eq!(table.sequence_of("peradaban"), Some(vec![0x70, 0x72]));
+        assert_eq!(table.sequence_of("£"), None);
+    }
+
+    #[test]
+    fn test_ending_with() {
+        let table = table();
+        assert_eq!(table.ending_with(0x27), 1);
+        assert_eq!(table.ending_with(0x65), 1);
+        assert_eq!(table.ending_with(0x2f), 1);
+        assert_eq!(table.ending_with(0x63), 0);
+    }
+
+    #[test]
+    fn test_empty() {
+        let table = SequenceTable::default();
+        assert_eq!(table.prefix_map().len(), 0);
+        assert_eq!(table.sequence_of("é"), None);
+        assert_eq!(table.ending_with(0x65), 0);
+    }
+}
@@ -0,0 +1,137 @@
+use std::{collections::BTreeMap, io::Read, ops::Bound};
+
+use crate::utils::functions::{fail, fail_message};
+
+/// Emoji shortcodes such as `thumbsup` for `:thumbsup:`, sorted for prefix lookups.
+#[derive(Debug, Default)]
+pub(super) struct Shortcodes(BTreeMap<Box<str>, Box<str>>);
+
+impl Shortcodes {
+    /// Loads the compressed shortcode table. A missing file gives no shortcodes.
+    pub(super) fn load(path: &str) -> windows_core::Result<Self> {
+        let Ok(mut file) = std::fs::File::open(path) else {
+            return Ok(Self::default());
+        };
+        let mut input = brotli_decompressor::Decompressor::new(&mut file, 4096);
+        let mut buf = String::new();
+        let _num = input.read_to_string(&mut buf).map_err(fail)?;
+        Self::parse(&buf)
+    }
+
+    /// Reads lines of a shortcode and its emoji separated by a tab, e.g. `+1\t👍`.
+    /// Lines starting with `#` are comments. The first line of a shortcode wins.
+    pub(super) fn parse(text: &str) -> windows_core::Result<Self> {
+        let mut result = Self::default();
+        for line in text.lines() {
+            if line.trim().is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let (code, value) = line
+                .split_once('\t')
+                .filter(|(code, value)| is_shortcode(code) && !value.trim().is_empty())
+                .ok_or_else(|| fail_message(&format!("Invalid shortcode line: {line}")))?;
+            result
+                .0
+                .entry(code.into())
+                .or_insert_with(|| value.trim().into());
+        }
+        Ok(result)
+    }
+
+    /// Adds a shortcode made from a CLDR name or keyword, unless the table already has it.
+    pub(super) fn insert_derived(&mut self, name: &str, value: &str) {
+        let code = derive_shortcode(name);
+        if !code.is_empty() {
+            self.0.entry(code.into()).or_insert_with(|| value.into());
+        }
+    }
+
+    pub(super) fn get(&self, code: &str) -> Option<&str> {
+        self.0.get(code).map(|value| value.as_ref())
+    }
+
+    /// Shortcodes starting with `prefix`, in order.
+    pub(super) fn with_prefix<'a>(
+        &'a self,
+        prefix: &'a str,
+    ) -> impl Iterator<Item = (&'a str, &'a str)> {
+        self.0
+            .range::<str, _>((Bound::Included(prefix), Bound::Unbounded))
+            .take_while(move |(code, _)| code.starts_with(prefix))
+            .map(|(code, value)| (code.as_ref(), value.as_ref()))
+    }
+}
+
+/// Characters allowed between the colons of a shortcode.
+pub(super) fn is_shortcode_char(value: char) -> bool {
+    value.is_alphanumeric() || matches!(value, '_' | '-' | '+')
+}
+
+fn is_shortcode(value: &str) -> bool {
+    !value.is_empty() && value.chars().all(is_shortcode_char)
+}
+
+/// Turns a name like `thumbs up: medium skin tone` into `thumbs_up_medium_skin_tone`.
+fn derive_shortcode(name: &str) -> String {
+    name.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join("_")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SHORTCODES: &str = "\
+# shortcode\temoji
++1\t👍
+thumbsup\t👍
+smile\t😄
+smiley\t😃
+thumbsup\t👎
+";
+
+    #[test]
+    fn test_parse() {
+        let shortcodes = Shortcodes::parse(SHORTCODES).unwrap();
+
+        assert_eq!(shortcodes.get("+1"), Some("👍"));
+        assert_eq!(shortcodes.get("thumbsup"), Some("👍"));
+        assert_eq!(shortcodes.get("smile"), Some("😄"));
+        assert_eq!(shortcodes.get("frown"), None);
+
+        assert!(Shortcodes::parse("no tab here\n").is_err());
+        assert!(Shortcodes::parse("two words\t😄\n").is_err());
+        assert!(Shortcodes::parse("empty\t\n").is_err());
+    }
+
+    #[test]
+    fn test_prefix() {
+        let shortcodes = Shortcodes::parse(SHORTCODES).unwrap();
+
+        assert_eq!(
+            shortcodes.with_prefix("smi").collect::<Vec<_>>(),
+            [("smile", "😄"), ("smiley", "😃")]
+        );
+        assert_eq!(shortcodes.with_prefix("thumbs").count(), 1);
+        assert_eq!(shortcodes.with_prefix("x").count(), 0);
+        assert_eq!(shortcodes.with_prefix("").count(), 4);
+    }
+
+    #[test]
+    fn test_derived() {
+        let mut shortcodes = Shortcodes::parse(SHORTCODES).unwrap();
+        shortcodes.insert_derived("thumbs up: medium skin tone", "👍🏽");
+        shortcodes.insert_derived("Smile", "😊");
+        shortcodes.insert_derived("…", "…");
+
+        assert_eq!(shortcodes.get("thumbs_up_medium_skin_tone"), Some("👍🏽"));
+        // The table comes first
+        assert_eq!(shortcodes.get("smile"), Some("😄"));
+        assert_eq!(derive_shortcode("flag: Indonesia"), "flag_indonesia");
+        assert_eq!(derive_shortcode("…"), "");
+    }
+}
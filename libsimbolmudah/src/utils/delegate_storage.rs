@@ -44,11 +44,17 @@ impl<T: Interface> Delegate<T> {
     }
 }
 
-#[derive(Debug, Default)]
+#[derive(Debug)]
 pub(crate) struct DelegateStorage<D: Interface> {
     delegates: HashMap<i64, Delegate<D>>,
 }
 
+impl<D: Interface> Default for DelegateStorage<D> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl<D: Interface> DelegateStorage<D> {
     pub(crate) fn new() -> Self {
         Self {
@@ -82,6 +82,11 @@ impl<T: 'static> SingleThreaded<T> {
         })
     }
 
+    /// Whether `initialize` was called and `destroy` was not.
+    pub(crate) fn is_initialized(&'static self) -> Result<bool> {
+        Ok(self.thread.read().map_err(fail)?.is_some())
+    }
+
     pub(crate) fn with_borrow<F>(&'static self, f: F) -> Result<()>
     where
         F: FnOnce(&T) -> Result<()> + Send + 'static,
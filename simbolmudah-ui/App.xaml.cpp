@@ -32,6 +32,9 @@ namespace winrt::simbolmudah_ui::implementation
         // Do not close the application when the last window is closed.
         this->DispatcherShutdownMode(DispatcherShutdownMode::OnExplicitShutdown);
 
+        // Search results show emoji in the skin tone chosen last time.
+        this->sequenceDefinition.SetPreferredSkinTone(this->appManager.PreferredSkinTone());
+
         // Build the keyboard translator finite state automaton.
         this->buildProgress = this->RebuildDefinition();
 
@@ -168,6 +171,7 @@ namespace winrt::simbolmudah_ui::implementation
         this->SwitchKeyboardHook(this->appManager.HookEnabled());
         this->SwitchPopupWindow(this->appManager.UseHookPopup() && this->appManager.HookEnabled());
         this->SwitchNotifyIcon(this->appManager.NotifyIconEnabled());
+        this->sequenceDefinition.SetPreferredSkinTone(this->appManager.PreferredSkinTone());
     }
 
     /// <summary>
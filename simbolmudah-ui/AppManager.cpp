@@ -44,6 +44,15 @@ namespace winrt::simbolmudah_ui::implementation
         {
             values.Insert(L"mainWindowOpened", box_value(this->mainWindowOpened));
         }
+
+        if (values.HasKey(L"preferredSkinTone"))
+        {
+            this->preferredSkinTone = unbox_value<uint8_t>(values.Lookup(L"preferredSkinTone"));
+        }
+        else
+        {
+            values.Insert(L"preferredSkinTone", box_value(this->preferredSkinTone));
+        }
     }
 
     void AppManager::HookEnabled(bool value)
@@ -85,4 +94,14 @@ namespace winrt::simbolmudah_ui::implementation
             this->RaisePropertyChanged(L"MainWindowOpened");
         }
     }
+
+    void AppManager::PreferredSkinTone(uint8_t value)
+    {
+        if (this->preferredSkinTone != value)
+        {
+            this->preferredSkinTone = value;
+            this->localSettings.Values().Insert(L"preferredSkinTone", box_value(this->preferredSkinTone));
+            this->RaisePropertyChanged(L"PreferredSkinTone");
+        }
+    }
 }
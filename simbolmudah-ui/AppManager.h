@@ -19,6 +19,8 @@ namespace winrt::simbolmudah_ui::implementation
         void NotifyIconEnabled(bool value);
         bool MainWindowOpened() const noexcept { return this->mainWindowOpened; }
         void MainWindowOpened(bool value);
+        uint8_t PreferredSkinTone() const noexcept { return this->preferredSkinTone; }
+        void PreferredSkinTone(uint8_t value);
         bool FirstInstall() const noexcept { return !this->localSettings.Values().HasKey(L"firstInstall"); }
         void FirstInstall(bool value) { this->localSettings.Values().Insert(L"firstInstall", box_value(value)); }
 
@@ -29,6 +31,7 @@ namespace winrt::simbolmudah_ui::implementation
         bool useHookPopup{ true };
         bool notifyIconEnabled{ true };
         bool mainWindowOpened{ true };
+        uint8_t preferredSkinTone{ 0 };
     };
 }
 